@@ -0,0 +1,148 @@
+use crate::engine::action::{Action, Direction, Observation};
+use crate::engine::rng::SimRng;
+use super::{Agent, LegacyAgent};
+
+/// Runs a self-moving `LegacyAgent` as an action-returning `Agent`.
+///
+/// Each tick the wrapped agent is synced to the world's position and
+/// energy, updated as before, and its change in position/energy is
+/// translated into the equivalent `Action`. The world then applies that
+/// action, so illegal moves are rejected and energy is charged by the
+/// world; whatever the agent charged itself is overwritten next tick.
+/// Legacy agents only know `grid.goal`, so when the world points the
+/// agent at a different goal they are shown a copy of the grid with that
+/// goal as the primary one.
+pub struct LegacyAdapter<A: LegacyAgent> {
+    agent: A,
+}
+
+impl<A: LegacyAgent> LegacyAdapter<A> {
+    pub fn new(agent: A) -> Self {
+        Self { agent }
+    }
+
+    /// The wrapped agent.
+    pub fn inner(&self) -> &A {
+        &self.agent
+    }
+}
+
+/// Box a legacy agent for use in a `World`/`MultiWorld`.
+pub fn legacy<A: LegacyAgent + 'static>(agent: A) -> Box<dyn Agent> {
+    Box::new(LegacyAdapter::new(agent))
+}
+
+impl<A: LegacyAgent> Agent for LegacyAdapter<A> {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        if self.agent.position() != obs.position {
+            self.agent.set_position(obs.position);
+        }
+        self.agent.set_energy(obs.energy);
+        let mut retargeted;
+        let grid = if obs.goal == obs.grid.goal {
+            obs.grid
+        } else {
+            retargeted = obs.grid.clone();
+            retargeted.goal = obs.goal;
+            &retargeted
+        };
+        let energy_before = self.agent.energy();
+        self.agent.update(grid, rng);
+
+        if let Some(dir) = Direction::between(obs.position, self.agent.position()) {
+            return Action::Move(dir);
+        }
+        match (energy_before, self.agent.energy()) {
+            (Some(before), Some(after)) if after > before => {
+                if obs.grid.is_goal(obs.position) {
+                    Action::Interact
+                } else {
+                    Action::Rest
+                }
+            }
+            _ => Action::Wait,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.agent.name()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.agent.as_any()
+    }
+
+    fn is_stuck(&self) -> bool {
+        self.agent.is_stuck()
+    }
+
+    fn debug_state(&self) -> String {
+        self.agent.debug_state()
+    }
+
+    fn replans(&self) -> Option<u32> {
+        self.agent.replans()
+    }
+
+    fn decision_trace(&self) -> Vec<super::behavior_tree::TraceStep> {
+        self.agent.decision_trace()
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.agent.did_noise_trigger()
+    }
+
+    fn planning_radius(&self) -> Option<f32> {
+        self.agent.planning_radius()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::astar::AStarAgent;
+    use crate::agents::fsm::FSMAgent;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::{Grid, Position};
+
+    #[test]
+    fn translates_moves_and_resyncs_position() {
+        let grid = Grid::new(5, 1, Position { x: 4, y: 0 });
+        let mut rng = seeded(0);
+        let mut agent = LegacyAdapter::new(AStarAgent::new(0, 0));
+
+        let obs = Observation { grid: &grid, position: Position { x: 0, y: 0 }, energy: 100, tick: 0, goal: grid.goal };
+        assert_eq!(agent.act(&obs, &mut rng), Action::Move(Direction::East));
+
+        // The world rejected the move: the agent is put back and replans.
+        let obs = Observation { tick: 1, ..obs };
+        assert_eq!(agent.act(&obs, &mut rng), Action::Move(Direction::East));
+        assert_eq!(agent.inner().position(), Position { x: 1, y: 0 });
+    }
+
+    #[test]
+    fn energy_gain_becomes_rest() {
+        // Goal walled off so the FSM wanders until it runs low.
+        let grid = Grid::with_obstacles(5, 5, Position { x: 4, y: 4 }, &[(3, 4), (4, 3)]);
+        let mut rng = seeded(0);
+        let mut fsm = FSMAgent::new(2, 2);
+        // Drain the FSM until it decides to rest.
+        while fsm.energy() >= 10 {
+            fsm.update(&grid, &mut rng);
+        }
+        let position = fsm.position();
+        let mut agent = LegacyAdapter::new(fsm);
+        let obs = Observation { grid: &grid, position, energy: 5, tick: 0, goal: grid.goal };
+        assert_eq!(agent.act(&obs, &mut rng), Action::Rest);
+    }
+
+    #[test]
+    fn decides_from_body_energy() {
+        // A fresh FSM thinks it has full energy; the body says otherwise.
+        let grid = Grid::new(5, 5, Position { x: 4, y: 4 });
+        let mut rng = seeded(0);
+        let mut agent = LegacyAdapter::new(FSMAgent::new(2, 2));
+        let obs = Observation { grid: &grid, position: Position { x: 2, y: 2 }, energy: 3, tick: 0, goal: grid.goal };
+        assert_eq!(agent.act(&obs, &mut rng), Action::Rest);
+    }
+}
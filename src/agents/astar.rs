@@ -1,6 +1,8 @@
 use rand::Rng;
 use crate::algorithms::astar::find_path;
+use crate::engine::rng::SimRng;
 use crate::engine::world::{Grid, Position};
+use super::belief::{BeliefMap, Sensor};
 use super::memory::SpatialMemory;
 
 /// Agent that uses A* pathfinding to move toward the goal.
@@ -18,6 +20,11 @@ pub struct AStarAgent {
     decay_rate: f32,
     memory: SpatialMemory,
     noise_triggered: bool,
+    /// Number of times `find_path` has been called this episode.
+    plans: u32,
+    /// Limited perception; `None` plans on the full grid.
+    sensor: Option<Sensor>,
+    belief: Option<BeliefMap>,
 }
 
 impl AStarAgent {
@@ -36,6 +43,9 @@ impl AStarAgent {
             decay_rate: 1.0,
             memory: SpatialMemory::new(0),
             noise_triggered: false,
+            plans: 0,
+            sensor: None,
+            belief: None,
         }
     }
 
@@ -66,18 +76,37 @@ impl AStarAgent {
         }
     }
 
+    /// Restrict perception to `sensor`; the agent then plans on its
+    /// belief map, treating unseen cells as open.
+    pub fn with_sensor(self, sensor: Sensor) -> Self {
+        Self {
+            sensor: Some(sensor),
+            ..self
+        }
+    }
+
     pub fn position(&self) -> Position {
         self.pos
     }
 
+    /// What the agent believes about the grid (only with a sensor).
+    pub fn belief(&self) -> Option<&BeliefMap> {
+        self.belief.as_ref()
+    }
+
     /// Whether the agent has determined that no path exists and stopped trying.
     pub fn is_stuck(&self) -> bool {
         self.stuck
     }
 
+    /// Plans made after the first one (noise moves or invalidated paths).
+    pub fn replans(&self) -> u32 {
+        self.plans.saturating_sub(1)
+    }
+
     /// Update the agent: if we don't have a path, compute one.
     /// Then advance one step along the path toward the goal.
-    pub fn update(&mut self, grid: &Grid) {
+    pub fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
         self.noise_triggered = false;
         // Record current position in memory.
         self.memory.record(self.pos);
@@ -95,11 +124,40 @@ impl AStarAgent {
             return;
         }
 
+        // With a sensor, look around and plan on the belief instead of the
+        // true grid. All eight neighbors are in view, so moves stay legal.
+        let belief = match self.sensor {
+            Some(sensor) => {
+                let belief = self.belief.get_or_insert_with(|| BeliefMap::new(grid));
+                belief.observe(grid, self.pos, &sensor);
+                self.belief.take()
+            }
+            None => None,
+        };
+        let plan_grid = belief.as_ref().map_or(grid, BeliefMap::grid);
+        self.follow_plan(plan_grid, rng);
+        self.belief = belief;
+    }
+
+    /// Validate, (re)plan if needed and take one step on `grid`.
+    fn follow_plan(&mut self, grid: &Grid, rng: &mut SimRng) {
+        // Validate the rest of the plan: a blocked cell means the grid
+        // changed underneath it, so drop it and plan again below.
+        if !self.path.is_empty()
+            && self.path[self.path_index + 1..]
+                .iter()
+                .any(|&(x, y)| !grid.is_walkable(x, y))
+        {
+            println!("A*: Path invalidated by a blocked cell, replanning");
+            self.path.clear();
+        }
+
         // Plan a path if needed or if we've exhausted the previous plan.
         if self.path.is_empty() || self.path_index + 1 >= self.path.len() {
             let start = (self.pos.x, self.pos.y);
             let goal = (grid.goal.x, grid.goal.y);
 
+            self.plans += 1;
             match find_path(start, goal, grid, self.planning_limit) {
                 Some(path) => {
                     let path: Vec<(usize, usize)> = path;
@@ -126,9 +184,8 @@ impl AStarAgent {
         if self.path_index + 1 < self.path.len() {
             // Decision noise (modulated by exploration rate).
             let effective_noise = self.noise * self.exploration_rate;
-            let mut rng = rand::thread_rng();
             if effective_noise > 0.0 && rng.r#gen::<f32>() < effective_noise {
-                if let Some((nx, ny)) = grid.random_walkable_neighbor(self.pos.x, self.pos.y) {
+                if let Some((nx, ny)) = grid.random_walkable_neighbor(self.pos.x, self.pos.y, rng) {
                     self.pos = Position { x: nx, y: ny };
                     // Invalidate path so we re-plan next tick.
                     self.path.clear();
@@ -146,15 +203,25 @@ impl AStarAgent {
     }
 }
 
-impl super::Agent for AStarAgent {
-    fn update(&mut self, grid: &Grid) {
-        self.update(grid);
+impl super::LegacyAgent for AStarAgent {
+    fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.update(grid, rng);
     }
 
     fn position(&self) -> Position {
         self.pos
     }
 
+    fn set_position(&mut self, pos: Position) {
+        if pos != self.pos {
+            self.pos = pos;
+            // The cached plan started somewhere else.
+            self.path.clear();
+        }
+    }
+
+    fn set_energy(&mut self, _energy: u32) {}
+
     fn name(&self) -> &'static str {
         "AStar"
     }
@@ -167,6 +234,10 @@ impl super::Agent for AStarAgent {
         self.stuck
     }
 
+    fn replans(&self) -> Option<u32> {
+        Some(self.replans())
+    }
+
     fn debug_state(&self) -> String {
         if self.stuck {
             "Stuck".to_string()
@@ -1,8 +1,23 @@
 use rand::Rng;
+use rand::seq::SliceRandom;
 
+use crate::engine::action::{MAX_ENERGY, REST_RECOVERY};
+use crate::engine::rng::SimRng;
 use crate::engine::world::{Grid, Position};
+use super::blackboard::{Blackboard, Key};
+use super::bt_format::Registry;
 use super::memory::SpatialMemory;
 
+/// Cell a `MoveToTarget` node heads for; written by other nodes.
+pub const TARGET: Key<Position> = Key::new("target");
+/// Where the goal was last seen. Kept current by the agent.
+pub const LAST_SEEN_GOAL: Key<Position> = Key::new("last_seen_goal");
+/// The agent's position at its previous update. Kept current by the agent.
+pub const PREVIOUS_POSITION: Key<Position> = Key::new("previous_position");
+/// Consecutive updates that started where the previous one did. Kept
+/// current by the agent.
+pub const STUCK_TICKS: Key<u32> = Key::new("stuck_ticks");
+
 /// Status returned by behavior tree nodes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Status {
@@ -11,47 +26,468 @@ pub enum Status {
     Running,
 }
 
+/// How many children of a `Parallel` node must finish one way for the
+/// node to finish that way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    RequireOne,
+    RequireAll,
+}
+
+impl Policy {
+    fn met(self, count: usize, total: usize) -> bool {
+        match self {
+            Policy::RequireOne => count >= 1,
+            Policy::RequireAll => count == total,
+        }
+    }
+}
+
+/// A leaf node with its own parameters or state, such as
+/// `IsEnergyBelow(30)` or a closure (see `Node::action_fn`).
+pub trait Leaf: Send + Sync {
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status;
+    /// Forget per-run state; see `Node::reset`.
+    fn reset(&mut self) {}
+    /// Copy this leaf, so that trees containing it can be cloned.
+    fn clone_box(&self) -> Box<dyn Leaf>;
+    /// How the leaf is shown in traces and the viewer.
+    fn name(&self) -> String {
+        "custom".to_string()
+    }
+}
+
+impl Clone for Box<dyn Leaf> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// A node's place in a tree, see `Node::outline`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// 0 for the root, 1 for its children, and so on.
+    pub depth: usize,
+    pub label: String,
+}
+
+/// A node visited during one tick and what it returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The node's index in `Node::outline`.
+    pub node: usize,
+    pub depth: usize,
+    pub label: String,
+    pub status: Status,
+}
+
+/// How often a node has returned each status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HitCounts {
+    pub success: u32,
+    pub failure: u32,
+    pub running: u32,
+}
+
+impl HitCounts {
+    pub fn record(&mut self, status: Status) {
+        match status {
+            Status::Success => self.success += 1,
+            Status::Failure => self.failure += 1,
+            Status::Running => self.running += 1,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.success + self.failure + self.running
+    }
+}
+
 /// Behavior Tree node.
 ///
 /// This keeps things simple by using function pointers for
-/// conditions and actions, and recursive enums for Sequence
-/// and Selector.
+/// conditions and actions, and recursive enums for composites
+/// and decorators. Nodes that need to remember something between
+/// ticks carry it in their variant; build those with the
+/// constructors below. Leaves with parameters or state go in
+/// `Custom`, and nodes share data through the agent's `Blackboard`.
+/// Trees can also be loaded from text, see `bt_format`.
+#[derive(Clone)]
 pub enum Node {
     /// Runs children in order; fails on first Failure,
-    /// succeeds only if all children succeed.
+    /// succeeds only if all children succeed. Children after the one
+    /// that decided the tick are reset, halting any left Running.
     Sequence(Vec<Node>),
     /// Runs children in order; succeeds on first Success,
-    /// fails only if all children fail.
+    /// fails only if all children fail. Children after the one that
+    /// decided the tick are reset, halting any left Running.
     Selector(Vec<Node>),
+    /// Like `Sequence`, but resumes from the child that was Running
+    /// instead of starting over every tick.
+    SequenceWithMemory { children: Vec<Node>, current: usize },
+    /// Like `Selector`, but resumes from the child that was Running
+    /// instead of starting over every tick.
+    SelectorWithMemory { children: Vec<Node>, current: usize },
+    /// Ticks every unfinished child each tick. Fails once `failure` is
+    /// met by failed children, else succeeds once `success` is met by
+    /// succeeded ones (and fails if every child finishes without either).
+    /// Children that finish early keep their result until the node as a
+    /// whole finishes.
+    Parallel {
+        success: Policy,
+        failure: Policy,
+        children: Vec<Node>,
+        results: Vec<Option<Status>>,
+    },
+    /// Swaps Success and Failure.
+    Inverter(Box<Node>),
+    /// Turns Failure into Success.
+    Succeeder(Box<Node>),
+    /// Runs its child until it has succeeded `times` times, one child
+    /// tick per tick; fails as soon as the child fails.
+    Repeat { times: u32, count: u32, child: Box<Node> },
+    /// Runs its child again (on the next tick) every time it fails,
+    /// until it succeeds.
+    RetryUntilSuccess(Box<Node>),
+    /// Fails, and resets its child, once the child has been Running for
+    /// `ticks` consecutive ticks of this node.
+    Timeout { ticks: u32, elapsed: u32, child: Box<Node> },
+    /// After its child finishes, fails without ticking it until `ticks`
+    /// agent ticks have passed.
+    Cooldown { ticks: u64, ready_at: u64, child: Box<Node> },
     /// Condition that checks read-only agent/world state.
     Condition(fn(&BehaviorTreeAgent, &Grid) -> bool),
-    /// Action that can modify agent state.
-    Action(fn(&mut BehaviorTreeAgent, &Grid) -> Status),
+    /// Action that can modify agent state. Any randomness must come
+    /// from the supplied RNG.
+    Action(fn(&mut BehaviorTreeAgent, &Grid, &mut SimRng) -> Status),
+    /// A leaf carrying its own parameters or state.
+    Custom(Box<dyn Leaf>),
 }
 
 impl Node {
-    pub fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid) -> Status {
+    pub fn sequence_with_memory(children: Vec<Node>) -> Self {
+        Node::SequenceWithMemory { children, current: 0 }
+    }
+
+    pub fn selector_with_memory(children: Vec<Node>) -> Self {
+        Node::SelectorWithMemory { children, current: 0 }
+    }
+
+    pub fn parallel(success: Policy, failure: Policy, children: Vec<Node>) -> Self {
+        let results = vec![None; children.len()];
+        Node::Parallel { success, failure, children, results }
+    }
+
+    pub fn inverter(child: Node) -> Self {
+        Node::Inverter(Box::new(child))
+    }
+
+    pub fn succeeder(child: Node) -> Self {
+        Node::Succeeder(Box::new(child))
+    }
+
+    pub fn repeat(times: u32, child: Node) -> Self {
+        Node::Repeat { times, count: 0, child: Box::new(child) }
+    }
+
+    pub fn retry_until_success(child: Node) -> Self {
+        Node::RetryUntilSuccess(Box::new(child))
+    }
+
+    pub fn timeout(ticks: u32, child: Node) -> Self {
+        Node::Timeout { ticks, elapsed: 0, child: Box::new(child) }
+    }
+
+    pub fn cooldown(ticks: u64, child: Node) -> Self {
+        Node::Cooldown { ticks, ready_at: 0, child: Box::new(child) }
+    }
+
+    pub fn leaf(leaf: impl Leaf + 'static) -> Self {
+        Node::Custom(Box::new(leaf))
+    }
+
+    /// An action from a closure, which may capture parameters and state.
+    pub fn action_fn<F>(f: F) -> Self
+    where
+        F: FnMut(&mut BehaviorTreeAgent, &Grid, &mut SimRng) -> Status + Clone + Send + Sync + 'static,
+    {
+        Node::leaf(ClosureAction(f))
+    }
+
+    /// A condition from a closure, which may capture parameters.
+    pub fn condition_fn<F>(f: F) -> Self
+    where
+        F: Fn(&BehaviorTreeAgent, &Grid) -> bool + Clone + Send + Sync + 'static,
+    {
+        Node::leaf(ClosureCondition(f))
+    }
+
+    /// Show this leaf under `name` in outlines and traces, e.g. the name
+    /// it was loaded under from a tree file. Other nodes are returned
+    /// unchanged.
+    pub fn named(self, name: impl Into<String>) -> Self {
+        let leaf: Box<dyn Leaf> = match self {
+            Node::Condition(condition) => Box::new(ClosureCondition(condition)),
+            Node::Action(action) => Box::new(ClosureAction(action)),
+            Node::Custom(leaf) => leaf,
+            other => return other,
+        };
+        Node::leaf(Named { name: name.into(), leaf })
+    }
+
+    /// Forget any progress in this subtree (running children, counters),
+    /// as if it had never been ticked. Cooldowns keep counting down.
+    pub fn reset(&mut self) {
+        match self {
+            Node::Sequence(children) | Node::Selector(children) => children.iter_mut().for_each(Node::reset),
+            Node::SequenceWithMemory { children, current } | Node::SelectorWithMemory { children, current } => {
+                *current = 0;
+                children.iter_mut().for_each(Node::reset);
+            }
+            Node::Parallel { children, results, .. } => {
+                results.iter_mut().for_each(|result| *result = None);
+                children.iter_mut().for_each(Node::reset);
+            }
+            Node::Repeat { count, child, .. } => {
+                *count = 0;
+                child.reset();
+            }
+            Node::Timeout { elapsed, child, .. } => {
+                *elapsed = 0;
+                child.reset();
+            }
+            Node::Inverter(child)
+            | Node::Succeeder(child)
+            | Node::RetryUntilSuccess(child)
+            | Node::Cooldown { child, .. } => child.reset(),
+            Node::Custom(leaf) => leaf.reset(),
+            Node::Condition(_) | Node::Action(_) => {}
+        }
+    }
+
+    /// The node's children, in order.
+    pub fn children(&self) -> &[Node] {
+        match self {
+            Node::Sequence(children)
+            | Node::Selector(children)
+            | Node::SequenceWithMemory { children, .. }
+            | Node::SelectorWithMemory { children, .. }
+            | Node::Parallel { children, .. } => children,
+            Node::Inverter(child)
+            | Node::Succeeder(child)
+            | Node::RetryUntilSuccess(child)
+            | Node::Repeat { child, .. }
+            | Node::Timeout { child, .. }
+            | Node::Cooldown { child, .. } => std::slice::from_ref(child.as_ref()),
+            Node::Condition(_) | Node::Action(_) | Node::Custom(_) => &[],
+        }
+    }
+
+    /// Number of nodes in this subtree.
+    pub fn size(&self) -> usize {
+        1 + self.children().iter().map(Node::size).sum::<usize>()
+    }
+
+    /// Every node of the tree in pre-order, which is the order of the
+    /// node ids used in traces: the root is 0 and a node's children
+    /// follow it.
+    pub fn outline(&self) -> Vec<NodeInfo> {
+        fn walk(node: &Node, depth: usize, registry: &Registry, out: &mut Vec<NodeInfo>) {
+            out.push(NodeInfo { depth, label: node.label(registry) });
+            for child in node.children() {
+                walk(child, depth + 1, registry, out);
+            }
+        }
+        let mut out = Vec::new();
+        walk(self, 0, &Registry::standard(), &mut out);
+        out
+    }
+
+    /// Short description of this node; function leaves are named after
+    /// their entry in `registry` (leaves loaded from a file carry their
+    /// own name, see `Node::named`).
+    fn label(&self, registry: &Registry) -> String {
+        match self {
+            Node::Sequence(_) => "Sequence".to_string(),
+            Node::Selector(_) => "Selector".to_string(),
+            Node::SequenceWithMemory { .. } => "SequenceWithMemory".to_string(),
+            Node::SelectorWithMemory { .. } => "SelectorWithMemory".to_string(),
+            Node::Parallel { success, failure, .. } => format!("Parallel({:?}, {:?})", success, failure),
+            Node::Inverter(_) => "Inverter".to_string(),
+            Node::Succeeder(_) => "Succeeder".to_string(),
+            Node::Repeat { times, .. } => format!("Repeat({})", times),
+            Node::RetryUntilSuccess(_) => "RetryUntilSuccess".to_string(),
+            Node::Timeout { ticks, .. } => format!("Timeout({})", ticks),
+            Node::Cooldown { ticks, .. } => format!("Cooldown({})", ticks),
+            Node::Condition(pred) => registry.condition_name(*pred).unwrap_or("condition").to_string(),
+            Node::Action(act) => registry.action_name(*act).unwrap_or("action").to_string(),
+            Node::Custom(leaf) => leaf.name(),
+        }
+    }
+
+    /// Tick this node as the root of a tree. Every node visited is
+    /// recorded in the agent's trace (see `BehaviorTreeAgent::trace`).
+    pub fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
+        self.tick_at(0, agent, grid, rng)
+    }
+
+    /// Tick this node, whose pre-order id is `id`, recording the visit.
+    fn tick_at(&mut self, id: usize, agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
+        let entry = agent.trace.len();
+        agent.trace.push((id, Status::Running));
+        let status = self.run(id, agent, grid, rng);
+        agent.trace[entry].1 = status;
+        status
+    }
+
+    /// Ids of the children of node `id`.
+    fn child_ids(id: usize, children: &[Node]) -> Vec<usize> {
+        children
+            .iter()
+            .scan(id + 1, |next, child| {
+                let child_id = *next;
+                *next += child.size();
+                Some(child_id)
+            })
+            .collect()
+    }
+
+    fn run(&mut self, id: usize, agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
         match self {
             Node::Sequence(children) => {
-                for child in children.iter_mut() {
-                    match child.tick(agent, grid) {
-                        Status::Success => continue,
-                        Status::Failure => return Status::Failure,
-                        Status::Running => return Status::Running,
+                let ids = Node::child_ids(id, children);
+                for i in 0..children.len() {
+                    let status = children[i].tick_at(ids[i], agent, grid, rng);
+                    if status != Status::Success {
+                        // Halt later children a previous tick left running.
+                        children[i + 1..].iter_mut().for_each(Node::reset);
+                        return status;
                     }
                 }
                 Status::Success
             }
             Node::Selector(children) => {
-                for child in children.iter_mut() {
-                    match child.tick(agent, grid) {
-                        Status::Success => return Status::Success,
+                let ids = Node::child_ids(id, children);
+                for i in 0..children.len() {
+                    let status = children[i].tick_at(ids[i], agent, grid, rng);
+                    if status != Status::Failure {
+                        // A higher-priority child took over: halt the rest.
+                        children[i + 1..].iter_mut().for_each(Node::reset);
+                        return status;
+                    }
+                }
+                Status::Failure
+            }
+            Node::SequenceWithMemory { children, current } => {
+                let ids = Node::child_ids(id, children);
+                while let Some(child) = children.get_mut(*current) {
+                    match child.tick_at(ids[*current], agent, grid, rng) {
+                        Status::Success => *current += 1,
+                        Status::Failure => {
+                            *current = 0;
+                            return Status::Failure;
+                        }
+                        Status::Running => return Status::Running,
+                    }
+                }
+                *current = 0;
+                Status::Success
+            }
+            Node::SelectorWithMemory { children, current } => {
+                let ids = Node::child_ids(id, children);
+                while let Some(child) = children.get_mut(*current) {
+                    match child.tick_at(ids[*current], agent, grid, rng) {
+                        Status::Success => {
+                            *current = 0;
+                            return Status::Success;
+                        }
+                        Status::Failure => *current += 1,
                         Status::Running => return Status::Running,
-                        Status::Failure => continue,
                     }
                 }
+                *current = 0;
                 Status::Failure
             }
+            Node::Parallel { success, failure, children, results } => {
+                let ids = Node::child_ids(id, children);
+                for ((child, result), child_id) in children.iter_mut().zip(results.iter_mut()).zip(ids) {
+                    if result.is_none() {
+                        match child.tick_at(child_id, agent, grid, rng) {
+                            Status::Running => {}
+                            done => *result = Some(done),
+                        }
+                    }
+                }
+                let count = |status| results.iter().filter(|&&result| result == Some(status)).count();
+                let status = if failure.met(count(Status::Failure), children.len()) {
+                    Status::Failure
+                } else if success.met(count(Status::Success), children.len()) {
+                    Status::Success
+                } else if results.iter().all(Option::is_some) {
+                    // Everything finished without meeting the success policy.
+                    Status::Failure
+                } else {
+                    return Status::Running;
+                };
+                self.reset();
+                status
+            }
+            Node::Inverter(child) => match child.tick_at(id + 1, agent, grid, rng) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Node::Succeeder(child) => match child.tick_at(id + 1, agent, grid, rng) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            Node::Repeat { times, count, child } => match child.tick_at(id + 1, agent, grid, rng) {
+                Status::Success => {
+                    *count += 1;
+                    if *count >= *times {
+                        *count = 0;
+                        Status::Success
+                    } else {
+                        Status::Running
+                    }
+                }
+                Status::Failure => {
+                    *count = 0;
+                    Status::Failure
+                }
+                Status::Running => Status::Running,
+            },
+            Node::RetryUntilSuccess(child) => match child.tick_at(id + 1, agent, grid, rng) {
+                Status::Success => Status::Success,
+                _ => Status::Running,
+            },
+            Node::Timeout { ticks, elapsed, child } => {
+                if *elapsed >= *ticks {
+                    *elapsed = 0;
+                    child.reset();
+                    return Status::Failure;
+                }
+                match child.tick_at(id + 1, agent, grid, rng) {
+                    Status::Running => {
+                        *elapsed += 1;
+                        Status::Running
+                    }
+                    done => {
+                        *elapsed = 0;
+                        done
+                    }
+                }
+            }
+            Node::Cooldown { ticks, ready_at, child } => {
+                if agent.ticks < *ready_at {
+                    return Status::Failure;
+                }
+                let status = child.tick_at(id + 1, agent, grid, rng);
+                if status != Status::Running {
+                    *ready_at = agent.ticks + *ticks;
+                }
+                status
+            }
             Node::Condition(pred) => {
                 if pred(agent, grid) {
                     Status::Success
@@ -59,17 +495,19 @@ impl Node {
                     Status::Failure
                 }
             }
-            Node::Action(act) => act(agent, grid),
+            Node::Action(act) => act(agent, grid, rng),
+            Node::Custom(leaf) => leaf.tick(agent, grid, rng),
         }
     }
 }
 
 /// Simple behavior-tree driven agent.
 ///
-/// For now it has:
+/// It has:
 /// - Position on the grid
 /// - Energy level
-/// - A hard-coded tree:
+/// - A tree, by default the built-in one below; `with_tree` replaces it
+///   (e.g. with one loaded by `bt_format::load_tree`):
 ///     Selector(
 ///       Sequence(IsHungry, MoveTowardsGoal),
 ///       Wander
@@ -78,11 +516,20 @@ pub struct BehaviorTreeAgent {
     pos: Position,
     energy: u32,
     root: Node,
+    /// `root.outline()`, built once per tree for traces and debug text.
+    outline: Vec<NodeInfo>,
     noise: f32,
     exploration_rate: f32,
     decay_rate: f32,
     memory: SpatialMemory,
     noise_triggered: bool,
+    /// Updates so far; the clock `Cooldown` nodes count against.
+    ticks: u64,
+    blackboard: Blackboard,
+    /// (node id, status) of the nodes visited in the last tick.
+    trace: Vec<(usize, Status)>,
+    /// Per node id, results over all ticks.
+    hits: Vec<HitCounts>,
 }
 
 impl BehaviorTreeAgent {
@@ -102,12 +549,17 @@ impl BehaviorTreeAgent {
                 y: start_y,
             },
             energy: 100,
+            outline: root.outline(),
             root,
             noise: 0.0,
             exploration_rate: 1.0,
             decay_rate: 1.0,
             memory: SpatialMemory::new(0),
             noise_triggered: false,
+            ticks: 0,
+            blackboard: Blackboard::new(),
+            trace: Vec::new(),
+            hits: Vec::new(),
         }
     }
 
@@ -144,9 +596,65 @@ impl BehaviorTreeAgent {
         self.energy
     }
 
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.blackboard
+    }
+
+    /// Step to the neighboring cell `to`, paying its terrain's energy
+    /// cost. Returns false (and stays put) if `to` can't be entered.
+    pub fn move_to(&mut self, grid: &Grid, to: Position) -> bool {
+        if !grid.walkable_neighbors(self.pos.x, self.pos.y).contains(&(to.x, to.y)) {
+            return false;
+        }
+        self.pos = to;
+        self.energy = self.energy.saturating_sub(grid.terrain(to.x, to.y).energy_cost());
+        true
+    }
+
+    /// Run `root` instead of the built-in tree, e.g. one loaded with
+    /// `bt_format::load_tree`.
+    pub fn with_tree(self, root: Node) -> Self {
+        Self { outline: root.outline(), root, hits: Vec::new(), ..self }
+    }
+
+    /// The agent's tree in pre-order; see `Node::outline`.
+    pub fn tree_outline(&self) -> &[NodeInfo] {
+        &self.outline
+    }
+
+    /// (node id, status) of every node visited in the last tick, in the
+    /// order they were entered. Empty if the tick was a noise move.
+    pub fn trace(&self) -> &[(usize, Status)] {
+        &self.trace
+    }
+
+    /// Results per node id, over all ticks so far. Nodes that were never
+    /// visited may be missing from the end.
+    pub fn hit_counts(&self) -> &[HitCounts] {
+        &self.hits
+    }
+
     /// Advance the behavior tree by one tick.
-    pub fn update(&mut self, grid: &Grid) {
+    pub fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.ticks += 1;
         self.noise_triggered = false;
+        self.trace.clear();
+        // Keep the agent-maintained blackboard entries current.
+        let stuck = match self.blackboard.get(PREVIOUS_POSITION) {
+            Some(&previous) if previous == self.pos => self.blackboard.get(STUCK_TICKS).map_or(1, |n| n + 1),
+            _ => 0,
+        };
+        self.blackboard.set(STUCK_TICKS, stuck);
+        self.blackboard.set(PREVIOUS_POSITION, self.pos);
+        self.blackboard.set(LAST_SEEN_GOAL, grid.goal);
         // Record current position in memory.
         self.memory.record(self.pos);
 
@@ -155,11 +663,10 @@ impl BehaviorTreeAgent {
 
         // Decision noise (modulated by exploration rate).
         let effective_noise = self.noise * self.exploration_rate;
-        let mut rng = rand::thread_rng();
         if effective_noise > 0.0 && rng.r#gen::<f32>() < effective_noise {
-            if let Some((nx, ny)) = grid.random_walkable_neighbor(self.pos.x, self.pos.y) {
+            if let Some((nx, ny)) = grid.random_walkable_neighbor(self.pos.x, self.pos.y, rng) {
                 self.pos = Position { x: nx, y: ny };
-                self.energy = self.energy.saturating_sub(1);
+                self.energy = self.energy.saturating_sub(grid.terrain(nx, ny).energy_cost());
                 self.noise_triggered = true;
                 println!("BT: Noise! Random move to ({}, {})", nx, ny);
                 return;
@@ -169,8 +676,14 @@ impl BehaviorTreeAgent {
         // Work around Rust's borrow checker by temporarily taking ownership
         // of the root node while ticking.
         let mut root = std::mem::replace(&mut self.root, Node::Action(noop_action));
-        let status = root.tick(self, grid);
+        let status = root.tick(self, grid, rng);
         self.root = root;
+        for &(id, status) in &self.trace {
+            if self.hits.len() <= id {
+                self.hits.resize(id + 1, HitCounts::default());
+            }
+            self.hits[id].record(status);
+        }
 
         println!(
             "BT tick -> {:?} | pos=({}, {}) | energy={}",
@@ -179,15 +692,23 @@ impl BehaviorTreeAgent {
     }
 }
 
-impl super::Agent for BehaviorTreeAgent {
-    fn update(&mut self, grid: &Grid) {
-        self.update(grid);
+impl super::LegacyAgent for BehaviorTreeAgent {
+    fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.update(grid, rng);
     }
 
     fn position(&self) -> Position {
         self.pos
     }
 
+    fn set_position(&mut self, pos: Position) {
+        self.pos = pos;
+    }
+
+    fn set_energy(&mut self, energy: u32) {
+        self.energy = energy;
+    }
+
     fn name(&self) -> &'static str {
         "BehaviorTree"
     }
@@ -200,8 +721,33 @@ impl super::Agent for BehaviorTreeAgent {
         Some(self.energy)
     }
 
+    /// The leaves visited in the last tick and their results, e.g.
+    /// `is_hungry: Failure, wander: Success`.
     fn debug_state(&self) -> String {
-        "Running".to_string() // BT doesn't have a single state enum like FSM
+        if self.noise_triggered {
+            return "Noise (random move)".to_string();
+        }
+        let outline = &self.outline;
+        let leaves: Vec<String> = self
+            .trace
+            .iter()
+            .filter(|&&(id, _)| id + 1 == outline.len() || outline[id + 1].depth <= outline[id].depth)
+            .map(|&(id, status)| format!("{}: {:?}", outline[id].label, status))
+            .collect();
+        leaves.join(", ")
+    }
+
+    fn decision_trace(&self) -> Vec<TraceStep> {
+        let outline = &self.outline;
+        self.trace
+            .iter()
+            .map(|&(node, status)| TraceStep {
+                node,
+                depth: outline[node].depth,
+                label: outline[node].label.clone(),
+                status,
+            })
+            .collect()
     }
 
     fn did_noise_trigger(&self) -> bool {
@@ -210,19 +756,14 @@ impl super::Agent for BehaviorTreeAgent {
 }
 
 // === Conditions and Actions used in the default tree ===
+// They are also registered by name in `bt_format::Registry::standard`.
 
-fn is_hungry(agent: &BehaviorTreeAgent, _grid: &Grid) -> bool {
+pub(crate) fn is_hungry(agent: &BehaviorTreeAgent, _grid: &Grid) -> bool {
     agent.energy < 50
 }
 
-fn manhattan(a: Position, b: Position) -> u32 {
-    let dx = a.x.abs_diff(b.x);
-    let dy = a.y.abs_diff(b.y);
-    (dx + dy) as u32
-}
-
-/// Move greedily toward the goal, consuming a small amount of energy.
-fn move_towards_goal(agent: &mut BehaviorTreeAgent, grid: &Grid) -> Status {
+/// Move greedily toward the goal, paying the terrain's energy cost.
+pub(crate) fn move_towards_goal(agent: &mut BehaviorTreeAgent, grid: &Grid, _rng: &mut SimRng) -> Status {
     if agent.pos == grid.goal {
         // "Eat": recover energy when at the goal cell.
         let before = agent.energy;
@@ -236,34 +777,13 @@ fn move_towards_goal(agent: &mut BehaviorTreeAgent, grid: &Grid) -> Status {
 
     let current = agent.pos;
     let goal = grid.goal;
-    let current_h = manhattan(current, goal);
-
-    let candidates = [
-        Position {
-            x: current.x.wrapping_sub(1),
-            y: current.y,
-        },
-        Position {
-            x: current.x + 1,
-            y: current.y,
-        },
-        Position {
-            x: current.x,
-            y: current.y.wrapping_sub(1),
-        },
-        Position {
-            x: current.x,
-            y: current.y + 1,
-        },
-    ];
-
-    let mut best: Option<(Position, u32)> = None;
-
-    for cand in candidates {
-        if !grid.is_walkable(cand.x, cand.y) {
-            continue;
-        }
-        let h = manhattan(cand, goal);
+    let current_h = grid.distance(current, goal);
+
+    let mut best: Option<(Position, f32)> = None;
+
+    for (x, y) in grid.walkable_neighbors(current.x, current.y) {
+        let cand = Position { x, y };
+        let h = grid.distance(cand, goal);
         if h < current_h {
             match best {
                 Some((_, best_h)) if h >= best_h => {}
@@ -274,7 +794,7 @@ fn move_towards_goal(agent: &mut BehaviorTreeAgent, grid: &Grid) -> Status {
 
     if let Some((next, _)) = best {
         agent.pos = next;
-        agent.energy = agent.energy.saturating_sub(1);
+        agent.energy = agent.energy.saturating_sub(grid.terrain(next.x, next.y).energy_cost());
         println!(
             "BT: Moving toward goal -> ({}, {}), energy={}",
             next.x, next.y, agent.energy
@@ -286,37 +806,211 @@ fn move_towards_goal(agent: &mut BehaviorTreeAgent, grid: &Grid) -> Status {
     }
 }
 
-/// Wander randomly, consuming a bit of energy.
-fn wander(agent: &mut BehaviorTreeAgent, grid: &Grid) -> Status {
-    let mut rng = rand::thread_rng();
+/// Wander to a random neighbor, paying the terrain's energy cost.
+pub(crate) fn wander(agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
+    match grid.random_walkable_neighbor(agent.pos.x, agent.pos.y, rng) {
+        Some((x, y)) => {
+            agent.pos = Position { x, y };
+            agent.energy = agent.energy.saturating_sub(grid.terrain(x, y).energy_cost());
+            println!(
+                "BT: Wandering to ({}, {}), energy={}",
+                x, y, agent.energy
+            );
+            Status::Success
+        }
+        None => Status::Failure,
+    }
+}
 
-    for _ in 0..8 {
-        let dir = rng.gen_range(0..4);
-        let mut next = agent.pos;
+// === Further leaves for trees loaded from files ===
+
+pub(crate) fn is_at_goal(agent: &BehaviorTreeAgent, grid: &Grid) -> bool {
+    agent.pos == grid.goal
+}
 
-        match dir {
-            0 if next.x > 0 => next.x -= 1,
-            1 => next.x = next.x.saturating_add(1),
-            2 if next.y > 0 => next.y -= 1,
-            3 => next.y = next.y.saturating_add(1),
-            _ => continue,
+/// Too tired to go on.
+pub(crate) fn is_exhausted(agent: &BehaviorTreeAgent, _grid: &Grid) -> bool {
+    agent.energy < 10
+}
+
+/// Stay put and recover energy; Running until energy is full.
+pub(crate) fn rest(agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+    agent.energy = (agent.energy + REST_RECOVERY).min(MAX_ENERGY);
+    if agent.energy >= MAX_ENERGY {
+        Status::Success
+    } else {
+        Status::Running
+    }
+}
+
+/// Move to a random neighbor that isn't in memory; fails if there is none.
+pub(crate) fn explore(agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
+    let unvisited: Vec<(usize, usize)> = grid
+        .walkable_neighbors(agent.pos.x, agent.pos.y)
+        .into_iter()
+        .filter(|&(x, y)| !agent.memory.contains(&Position { x, y }))
+        .collect();
+    match unvisited.choose(rng) {
+        Some(&(x, y)) => {
+            agent.pos = Position { x, y };
+            agent.energy = agent.energy.saturating_sub(grid.terrain(x, y).energy_cost());
+            println!("BT: Exploring to ({}, {}), energy={}", x, y, agent.energy);
+            Status::Success
         }
+        None => Status::Failure,
+    }
+}
 
-        if grid.is_walkable(next.x, next.y) {
-            agent.pos = next;
-            agent.energy = agent.energy.saturating_sub(1);
-            println!(
-                "BT: Wandering to ({}, {}), energy={}",
-                next.x, next.y, agent.energy
-            );
+// === Leaves with parameters ===
+
+/// Succeeds while energy is below the threshold.
+#[derive(Debug, Clone, Copy)]
+pub struct IsEnergyBelow(pub u32);
+
+impl Leaf for IsEnergyBelow {
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+        if agent.energy < self.0 { Status::Success } else { Status::Failure }
+    }
+
+    fn clone_box(&self) -> Box<dyn Leaf> {
+        Box::new(*self)
+    }
+
+    fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Succeeds once the agent has stayed put for at least this many updates
+/// (see `STUCK_TICKS`).
+#[derive(Debug, Clone, Copy)]
+pub struct IsStuckFor(pub u32);
+
+impl Leaf for IsStuckFor {
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+        let stuck = agent.blackboard.get(STUCK_TICKS).copied().unwrap_or(0);
+        if stuck >= self.0 { Status::Success } else { Status::Failure }
+    }
+
+    fn clone_box(&self) -> Box<dyn Leaf> {
+        Box::new(*self)
+    }
+
+    fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Steps greedily towards the blackboard's `TARGET`: Running while on
+/// the way, Success (clearing the target) on arrival, Failure with no
+/// target or no step that gets closer.
+#[derive(Debug, Clone, Copy)]
+pub struct MoveToTarget;
+
+impl Leaf for MoveToTarget {
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid, _rng: &mut SimRng) -> Status {
+        let Some(&target) = agent.blackboard.get(TARGET) else {
+            return Status::Failure;
+        };
+        if agent.pos == target {
+            agent.blackboard.remove(TARGET);
             return Status::Success;
         }
+        let here = grid.distance(agent.pos, target);
+        let next = grid
+            .walkable_neighbors(agent.pos.x, agent.pos.y)
+            .into_iter()
+            .map(|(x, y)| Position { x, y })
+            .filter(|&n| grid.distance(n, target) < here)
+            .min_by(|&a, &b| grid.distance(a, target).total_cmp(&grid.distance(b, target)));
+        match next {
+            Some(next) if agent.move_to(grid, next) => {
+                if next == target {
+                    agent.blackboard.remove(TARGET);
+                    Status::Success
+                } else {
+                    Status::Running
+                }
+            }
+            _ => Status::Failure,
+        }
     }
 
-    Status::Failure
+    fn clone_box(&self) -> Box<dyn Leaf> {
+        Box::new(*self)
+    }
+
+    fn name(&self) -> String {
+        format!("{:?}", self)
+    }
 }
 
-fn noop_action(_agent: &mut BehaviorTreeAgent, _grid: &Grid) -> Status {
+#[derive(Clone)]
+struct ClosureAction<F>(F);
+
+impl<F> Leaf for ClosureAction<F>
+where
+    F: FnMut(&mut BehaviorTreeAgent, &Grid, &mut SimRng) -> Status + Clone + Send + Sync + 'static,
+{
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
+        (self.0)(agent, grid, rng)
+    }
+
+    fn clone_box(&self) -> Box<dyn Leaf> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> String {
+        "action_fn".to_string()
+    }
+}
+
+#[derive(Clone)]
+struct ClosureCondition<F>(F);
+
+impl<F> Leaf for ClosureCondition<F>
+where
+    F: Fn(&BehaviorTreeAgent, &Grid) -> bool + Clone + Send + Sync + 'static,
+{
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid, _rng: &mut SimRng) -> Status {
+        if (self.0)(agent, grid) { Status::Success } else { Status::Failure }
+    }
+
+    fn clone_box(&self) -> Box<dyn Leaf> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> String {
+        "condition_fn".to_string()
+    }
+}
+
+/// A leaf shown under a fixed name; see `Node::named`.
+#[derive(Clone)]
+struct Named {
+    name: String,
+    leaf: Box<dyn Leaf>,
+}
+
+impl Leaf for Named {
+    fn tick(&mut self, agent: &mut BehaviorTreeAgent, grid: &Grid, rng: &mut SimRng) -> Status {
+        self.leaf.tick(agent, grid, rng)
+    }
+
+    fn reset(&mut self) {
+        self.leaf.reset();
+    }
+
+    fn clone_box(&self) -> Box<dyn Leaf> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+fn noop_action(_agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
     Status::Running
 }
 
@@ -326,6 +1020,7 @@ fn noop_action(_agent: &mut BehaviorTreeAgent, _grid: &Grid) -> Status {
 mod tests {
     use super::*;
 
+    use crate::engine::rng::seeded;
     use crate::engine::world::Grid;
 
     fn dummy_grid() -> Grid {
@@ -344,14 +1039,43 @@ mod tests {
         false
     }
 
-    fn action_success(_agent: &mut BehaviorTreeAgent, _grid: &Grid) -> Status {
+    fn action_success(_agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
         Status::Success
     }
 
-    fn action_failure(_agent: &mut BehaviorTreeAgent, _grid: &Grid) -> Status {
+    fn action_failure(_agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
         Status::Failure
     }
 
+    fn action_running(_agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+        Status::Running
+    }
+
+    /// Counts its ticks in the agent's energy and succeeds.
+    fn action_count(agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+        agent.energy += 1;
+        Status::Success
+    }
+
+    /// Counts its ticks in the agent's energy; fails the first two.
+    fn action_third_time_lucky(agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+        agent.energy += 1;
+        if agent.energy < 3 { Status::Failure } else { Status::Success }
+    }
+
+    /// Running and Success on alternate ticks, using the noise flag as
+    /// scratch state.
+    fn action_two_ticks(agent: &mut BehaviorTreeAgent, _grid: &Grid, _rng: &mut SimRng) -> Status {
+        agent.noise_triggered = !agent.noise_triggered;
+        if agent.noise_triggered { Status::Running } else { Status::Success }
+    }
+
+    fn counting_agent() -> BehaviorTreeAgent {
+        let mut agent = dummy_agent();
+        agent.energy = 0;
+        agent
+    }
+
     #[test]
     fn sequence_fails_on_first_failure() {
         let mut root = Node::Sequence(vec![
@@ -361,8 +1085,9 @@ mod tests {
 
         let mut agent = dummy_agent();
         let grid = dummy_grid();
+        let mut rng = seeded(0);
 
-        let status = root.tick(&mut agent, &grid);
+        let status = root.tick(&mut agent, &grid, &mut rng);
         assert_eq!(status, Status::Failure);
     }
 
@@ -375,8 +1100,9 @@ mod tests {
 
         let mut agent = dummy_agent();
         let grid = dummy_grid();
+        let mut rng = seeded(0);
 
-        let status = root.tick(&mut agent, &grid);
+        let status = root.tick(&mut agent, &grid, &mut rng);
         assert_eq!(status, Status::Success);
     }
 
@@ -387,9 +1113,249 @@ mod tests {
 
         let mut agent = dummy_agent();
         let grid = dummy_grid();
+        let mut rng = seeded(0);
 
-        assert_eq!(node_true.tick(&mut agent, &grid), Status::Success);
-        assert_eq!(node_false.tick(&mut agent, &grid), Status::Failure);
+        assert_eq!(node_true.tick(&mut agent, &grid, &mut rng), Status::Success);
+        assert_eq!(node_false.tick(&mut agent, &grid, &mut rng), Status::Failure);
     }
-}
 
+    #[test]
+    fn inverter_and_succeeder_map_results() {
+        let mut agent = dummy_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let mut inverted = Node::inverter(Node::Action(action_success));
+        let mut inverted_running = Node::inverter(Node::Action(action_running));
+        let mut succeeder = Node::succeeder(Node::Action(action_failure));
+        assert_eq!(inverted.tick(&mut agent, &grid, &mut rng), Status::Failure);
+        assert_eq!(inverted_running.tick(&mut agent, &grid, &mut rng), Status::Running);
+        assert_eq!(succeeder.tick(&mut agent, &grid, &mut rng), Status::Success);
+    }
+
+    #[test]
+    fn selector_halts_a_running_branch_when_a_higher_one_takes_over() {
+        // The noise flag stands in for an interrupt.
+        let mut root = Node::Selector(vec![
+            Node::condition_fn(|agent: &BehaviorTreeAgent, _: &Grid| agent.noise_triggered),
+            Node::repeat(3, Node::Action(action_count)),
+        ]);
+        let mut agent = counting_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let mut statuses: Vec<Status> = (0..2).map(|_| root.tick(&mut agent, &grid, &mut rng)).collect();
+        agent.noise_triggered = true;
+        statuses.push(root.tick(&mut agent, &grid, &mut rng));
+        agent.noise_triggered = false;
+        // The repeat starts its three runs over rather than resuming.
+        statuses.extend((0..3).map(|_| root.tick(&mut agent, &grid, &mut rng)));
+        assert_eq!(
+            statuses,
+            [Status::Running, Status::Running, Status::Success, Status::Running, Status::Running, Status::Success]
+        );
+    }
+
+    #[test]
+    fn repeat_runs_child_n_times_then_starts_over() {
+        let mut root = Node::repeat(3, Node::Action(action_count));
+        let mut agent = counting_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let statuses: Vec<Status> = (0..4).map(|_| root.tick(&mut agent, &grid, &mut rng)).collect();
+        assert_eq!(statuses, [Status::Running, Status::Running, Status::Success, Status::Running]);
+        assert_eq!(agent.energy, 4);
+
+        let mut failing = Node::repeat(3, Node::Action(action_failure));
+        assert_eq!(failing.tick(&mut agent, &grid, &mut rng), Status::Failure);
+    }
+
+    #[test]
+    fn retry_until_success_keeps_running_through_failures() {
+        let mut root = Node::retry_until_success(Node::Action(action_third_time_lucky));
+        let mut agent = counting_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let statuses: Vec<Status> = (0..3).map(|_| root.tick(&mut agent, &grid, &mut rng)).collect();
+        assert_eq!(statuses, [Status::Running, Status::Running, Status::Success]);
+    }
+
+    #[test]
+    fn timeout_fails_a_child_that_runs_too_long() {
+        let mut root = Node::timeout(2, Node::Action(action_running));
+        let mut agent = dummy_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let statuses: Vec<Status> = (0..4).map(|_| root.tick(&mut agent, &grid, &mut rng)).collect();
+        assert_eq!(statuses, [Status::Running, Status::Running, Status::Failure, Status::Running]);
+
+        // A child finishing in time passes its result through.
+        let mut quick = Node::timeout(2, Node::Action(action_two_ticks));
+        let mut agent = counting_agent();
+        assert_eq!(quick.tick(&mut agent, &grid, &mut rng), Status::Running);
+        assert_eq!(quick.tick(&mut agent, &grid, &mut rng), Status::Success);
+    }
+
+    #[test]
+    fn cooldown_blocks_its_child_until_enough_ticks_pass() {
+        let mut root = Node::cooldown(3, Node::Action(action_count));
+        let mut agent = counting_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let mut statuses = Vec::new();
+        for tick in 0..5 {
+            agent.ticks = tick;
+            statuses.push(root.tick(&mut agent, &grid, &mut rng));
+        }
+        assert_eq!(
+            statuses,
+            [Status::Success, Status::Failure, Status::Failure, Status::Success, Status::Failure]
+        );
+        assert_eq!(agent.energy, 2);
+    }
+
+    #[test]
+    fn parallel_applies_success_and_failure_policies() {
+        let mut agent = counting_agent();
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        let mut any = Node::parallel(
+            Policy::RequireOne,
+            Policy::RequireAll,
+            vec![Node::Action(action_running), Node::Action(action_success)],
+        );
+        assert_eq!(any.tick(&mut agent, &grid, &mut rng), Status::Success);
+
+        let mut all = Node::parallel(
+            Policy::RequireAll,
+            Policy::RequireOne,
+            vec![Node::Action(action_two_ticks), Node::Action(action_count)],
+        );
+        // The counting child finishes first and isn't ticked again.
+        assert_eq!(all.tick(&mut agent, &grid, &mut rng), Status::Running);
+        assert_eq!(all.tick(&mut agent, &grid, &mut rng), Status::Success);
+        assert_eq!(agent.energy, 1);
+
+        let mut strict = Node::parallel(
+            Policy::RequireAll,
+            Policy::RequireOne,
+            vec![Node::Action(action_running), Node::Action(action_failure)],
+        );
+        assert_eq!(strict.tick(&mut agent, &grid, &mut rng), Status::Failure);
+    }
+
+    #[test]
+    fn memory_composites_resume_from_the_running_child() {
+        let grid = dummy_grid();
+        let mut rng = seeded(0);
+
+        // Without memory the first child is re-run every tick.
+        let mut agent = counting_agent();
+        let mut plain = Node::Sequence(vec![Node::Action(action_count), Node::Action(action_two_ticks)]);
+        plain.tick(&mut agent, &grid, &mut rng);
+        plain.tick(&mut agent, &grid, &mut rng);
+        assert_eq!(agent.energy, 2);
+
+        let mut agent = counting_agent();
+        let mut sequence =
+            Node::sequence_with_memory(vec![Node::Action(action_count), Node::Action(action_two_ticks)]);
+        assert_eq!(sequence.tick(&mut agent, &grid, &mut rng), Status::Running);
+        assert_eq!(sequence.tick(&mut agent, &grid, &mut rng), Status::Success);
+        assert_eq!(agent.energy, 1);
+
+        let mut agent = counting_agent();
+        let mut selector = Node::selector_with_memory(vec![
+            Node::inverter(Node::Action(action_count)),
+            Node::Action(action_two_ticks),
+        ]);
+        assert_eq!(selector.tick(&mut agent, &grid, &mut rng), Status::Running);
+        assert_eq!(selector.tick(&mut agent, &grid, &mut rng), Status::Success);
+        assert_eq!(agent.energy, 1);
+    }
+
+    #[test]
+    fn parameterised_leaves_share_state_through_the_blackboard() {
+        const VISITS: Key<u32> = Key::new("visits");
+        let mut board = Blackboard::new();
+        board.set(VISITS, 2);
+        assert_eq!(board.get(VISITS), Some(&2));
+        assert_eq!(board.get(Key::<Position>::new("visits")), None);
+
+        // Pick a target when tired, then walk to it; count visits in a
+        // captured counter as well as on the blackboard.
+        let corner = Position { x: 3, y: 0 };
+        let mut runs = 0;
+        let mut root = Node::Sequence(vec![
+            Node::leaf(IsEnergyBelow(50)),
+            Node::action_fn(move |agent: &mut BehaviorTreeAgent, _: &Grid, _: &mut SimRng| {
+                runs += 1;
+                agent.blackboard_mut().set(VISITS, runs);
+                agent.blackboard_mut().set(TARGET, corner);
+                Status::Success
+            }),
+            Node::leaf(MoveToTarget),
+        ]);
+        let grid = Grid::new(4, 2, Position { x: 3, y: 1 });
+        let mut agent = counting_agent();
+        let mut rng = seeded(0);
+        let statuses: Vec<Status> = (0..3).map(|_| root.tick(&mut agent, &grid, &mut rng)).collect();
+        assert_eq!(statuses, [Status::Running, Status::Running, Status::Success]);
+        assert_eq!((agent.position(), agent.blackboard().get(VISITS)), (corner, Some(&3)));
+        assert!(!agent.blackboard().contains(TARGET));
+
+        agent.energy = 80;
+        assert_eq!(root.clone().tick(&mut agent, &grid, &mut rng), Status::Failure);
+
+        // The agent keeps its own entries current.
+        let mut idle = BehaviorTreeAgent::new(0, 0).with_tree(Node::condition_fn(|_: &BehaviorTreeAgent, _: &Grid| false));
+        for _ in 0..3 {
+            idle.update(&grid, &mut rng);
+        }
+        assert_eq!(idle.blackboard().get(STUCK_TICKS), Some(&2));
+        assert_eq!(idle.blackboard().get(LAST_SEEN_GOAL), Some(&grid.goal));
+        assert_eq!(Node::leaf(IsStuckFor(2)).tick(&mut idle, &grid, &mut rng), Status::Success);
+    }
+
+    #[test]
+    fn ticks_are_traced_with_per_node_hit_counts() {
+        use crate::agents::LegacyAgent;
+
+        let grid = Grid::new(5, 5, Position { x: 4, y: 4 });
+        let mut rng = seeded(0);
+        let mut agent = BehaviorTreeAgent::new(0, 0);
+        let labels: Vec<String> = agent.tree_outline().iter().map(|node| node.label.clone()).collect();
+        assert_eq!(labels, ["Selector", "Sequence", "is_hungry", "move_towards_goal", "wander"]);
+
+        // Not hungry, so the sequence fails and the agent wanders.
+        agent.update(&grid, &mut rng);
+        agent.update(&grid, &mut rng);
+        assert_eq!(
+            agent.trace(),
+            [(0, Status::Success), (1, Status::Failure), (2, Status::Failure), (4, Status::Success)]
+        );
+        assert_eq!(LegacyAgent::debug_state(&agent), "is_hungry: Failure, wander: Success");
+        let hits = agent.hit_counts();
+        assert_eq!((hits[2].failure, hits[3].total(), hits[4].success), (2, 0, 2));
+
+        agent.energy = 20;
+        agent.update(&grid, &mut rng);
+        let trace = LegacyAgent::decision_trace(&agent);
+        let visited: Vec<(&str, usize, Status)> =
+            trace.iter().map(|step| (step.label.as_str(), step.depth, step.status)).collect();
+        assert_eq!(
+            visited,
+            [
+                ("Selector", 0, Status::Success),
+                ("Sequence", 1, Status::Success),
+                ("is_hungry", 2, Status::Success),
+                ("move_towards_goal", 2, Status::Success),
+            ]
+        );
+        assert_eq!(Node::leaf(IsEnergyBelow(30)).outline()[0].label, "IsEnergyBelow(30)");
+    }
+}
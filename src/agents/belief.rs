@@ -0,0 +1,204 @@
+use crate::engine::terrain::Terrain;
+use crate::engine::world::{Grid, Position};
+
+/// What an agent can perceive each tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sensor {
+    /// Euclidean sensing radius in cells (at least 1). All eight
+    /// neighbors are visible whatever the radius.
+    pub radius: usize,
+    /// If true, walls hide the cells behind them.
+    pub line_of_sight: bool,
+}
+
+impl Sensor {
+    pub fn new(radius: usize, line_of_sight: bool) -> Self {
+        Self {
+            radius: radius.max(1),
+            line_of_sight,
+        }
+    }
+
+    /// Cells visible from `pos` on the true `grid`, including `pos` itself.
+    /// A wall is visible but blocks sight of anything behind it.
+    pub fn visible_cells(&self, grid: &Grid, pos: Position) -> Vec<Position> {
+        let r = self.radius.max(1);
+        let mut out = Vec::new();
+        for y in pos.y.saturating_sub(r)..=(pos.y + r).min(grid.height.saturating_sub(1)) {
+            for x in pos.x.saturating_sub(r)..=(pos.x + r).min(grid.width.saturating_sub(1)) {
+                let dx = x.abs_diff(pos.x);
+                let dy = y.abs_diff(pos.y);
+                if (dx > 1 || dy > 1) && dx * dx + dy * dy > r * r {
+                    continue;
+                }
+                let cell = Position { x, y };
+                if !self.line_of_sight || line_is_clear(grid, pos, cell) {
+                    out.push(cell);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Whether every cell strictly between `from` and `to` (Bresenham line)
+/// is see-through.
+fn line_is_clear(grid: &Grid, from: Position, to: Position) -> bool {
+    let (mut x, mut y) = (from.x as i64, from.y as i64);
+    let (tx, ty) = (to.x as i64, to.y as i64);
+    let dx = (tx - x).abs();
+    let dy = -(ty - y).abs();
+    let sx = if x < tx { 1 } else { -1 };
+    let sy = if y < ty { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        if (x, y) == (tx, ty) {
+            return true;
+        }
+        if (x, y) != (from.x as i64, from.y as i64) && !grid.is_walkable(x as usize, y as usize) {
+            return false;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+/// An agent's private picture of the grid.
+///
+/// Cells it has never seen are assumed to be open floor (optimistic), so
+/// a planner working on the belief heads straight for the goal and
+/// replans as walls come into view.
+pub struct BeliefMap {
+    grid: Grid,
+    known: Vec<Vec<bool>>,
+}
+
+impl BeliefMap {
+    /// An empty belief with the same size, start, goal and movement rules
+    /// as `truth`, but none of its walls or terrain.
+    pub fn new(truth: &Grid) -> Self {
+        let mut grid = Grid::new(truth.width, truth.height, truth.goal);
+        grid.start = truth.start;
+        grid.connectivity = truth.connectivity;
+        Self {
+            grid,
+            known: vec![vec![false; truth.width]; truth.height],
+        }
+    }
+
+    /// Copy what `sensor` sees from `pos` into the belief.
+    ///
+    /// Returns how many believed cells changed (newly discovered walls or
+    /// terrain, or changes since the cell was last seen).
+    pub fn observe(&mut self, truth: &Grid, pos: Position, sensor: &Sensor) -> usize {
+        // Goals are always known, and may be retargeted between ticks.
+        self.grid.goal = truth.goal;
+        self.grid.extra_goals.clone_from(&truth.extra_goals);
+        let mut changed = 0;
+        for cell in sensor.visible_cells(truth, pos) {
+            let terrain = truth.terrain(cell.x, cell.y);
+            if self.grid.terrain(cell.x, cell.y) != terrain {
+                self.grid.set_terrain(cell.x, cell.y, terrain);
+                changed += 1;
+            }
+            self.known[cell.y][cell.x] = true;
+        }
+        changed
+    }
+
+    /// The believed grid, suitable for planning.
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Whether a cell has ever been observed.
+    pub fn is_known(&self, x: usize, y: usize) -> bool {
+        self.known.get(y).and_then(|row| row.get(x)).copied().unwrap_or(false)
+    }
+
+    /// Number of cells observed so far.
+    pub fn known_count(&self) -> usize {
+        self.known.iter().flatten().filter(|&&k| k).count()
+    }
+
+    /// Believed terrain of a cell (`Floor` if never seen).
+    pub fn terrain(&self, x: usize, y: usize) -> Terrain {
+        self.grid.terrain(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::adapter::legacy;
+    use crate::agents::astar::AStarAgent;
+    use crate::agents::dstar_lite::DStarLiteAgent;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn walls_block_line_of_sight() {
+        // Wall column at x=2 with the sensor at (0,1).
+        let wall: Vec<_> = (0..3).map(|y| (2, y)).collect();
+        let grid = Grid::with_obstacles(5, 3, Position { x: 4, y: 2 }, &wall);
+        let pos = Position { x: 0, y: 1 };
+
+        let open = Sensor::new(4, false).visible_cells(&grid, pos);
+        assert!(open.contains(&Position { x: 3, y: 1 }));
+
+        let sighted = Sensor::new(4, true).visible_cells(&grid, pos);
+        assert!(sighted.contains(&Position { x: 2, y: 1 }));
+        assert!(!sighted.contains(&Position { x: 3, y: 1 }));
+    }
+
+    #[test]
+    fn planners_with_a_radius_one_sensor_see_diagonal_walls() {
+        let grid: Grid = "\
+connectivity 8
+corners allow
+map
+S.....
+.#....
+..#...
+...#..
+......
+.....G"
+            .parse()
+            .unwrap();
+        let around = Sensor::new(1, true).visible_cells(&grid, Position { x: 2, y: 2 });
+        assert_eq!(around.len(), 9);
+
+        let agents = [
+            legacy(AStarAgent::new(0, 0).with_sensor(Sensor::new(1, true))),
+            legacy(DStarLiteAgent::new(0, 0).with_sensor(Sensor::new(1, true))),
+        ];
+        for agent in agents {
+            let name = agent.name();
+            assert!(World::with_rng(grid.clone(), agent, 0, seeded(0)).run_until_done(100), "{}", name);
+        }
+    }
+
+    #[test]
+    fn belief_starts_optimistic_and_learns_walls() {
+        let grid = Grid::with_obstacles(6, 1, Position { x: 5, y: 0 }, &[(4, 0)]);
+        let mut belief = BeliefMap::new(&grid);
+        assert!(belief.grid().is_walkable(4, 0));
+
+        let sensor = Sensor::new(2, true);
+        assert_eq!(belief.observe(&grid, Position { x: 0, y: 0 }, &sensor), 0);
+        assert_eq!(belief.known_count(), 3);
+        assert!(!belief.is_known(4, 0));
+
+        assert_eq!(belief.observe(&grid, Position { x: 2, y: 0 }, &sensor), 1);
+        assert!(!belief.grid().is_walkable(4, 0));
+        assert_eq!(belief.terrain(4, 0), Terrain::Wall);
+    }
+}
@@ -0,0 +1,93 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A typed blackboard key: the name an entry is stored under plus the
+/// type of its value.
+pub struct Key<T> {
+    name: &'static str,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Key<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _value: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
+impl<T> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Key({})", self.name)
+    }
+}
+
+/// Shared state that behavior tree nodes read and write.
+///
+/// Entries are looked up by `Key`, so reads come back with the type they
+/// were written with. Setting a name again replaces its entry, whatever
+/// its type; reading it with a key of another type gives `None`.
+#[derive(Default)]
+pub struct Blackboard {
+    entries: HashMap<&'static str, Box<dyn Any + Send + Sync>>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get<T: Any>(&self, key: Key<T>) -> Option<&T> {
+        self.entries.get(key.name)?.downcast_ref()
+    }
+
+    pub fn get_mut<T: Any>(&mut self, key: Key<T>) -> Option<&mut T> {
+        self.entries.get_mut(key.name)?.downcast_mut()
+    }
+
+    pub fn set<T: Any + Send + Sync>(&mut self, key: Key<T>, value: T) {
+        self.entries.insert(key.name, Box::new(value));
+    }
+
+    /// Remove an entry, returning it if it had the key's type.
+    pub fn remove<T: Any>(&mut self, key: Key<T>) -> Option<T> {
+        let value = self.entries.remove(key.name)?;
+        value.downcast().ok().map(|value| *value)
+    }
+
+    pub fn contains<T: Any>(&self, key: Key<T>) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Names of the entries, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.keys().copied()
+    }
+}
@@ -0,0 +1,495 @@
+//! Plain-text format for behavior trees, so tree variants can be defined
+//! per experiment without recompiling.
+//!
+//! Each line is one node: a keyword followed by its arguments. A node's
+//! children are the lines below it indented further (all by the same
+//! amount); blank lines and `#` comments are skipped.
+//!
+//! ```text
+//! # The built-in tree: head for the goal when hungry, else wander.
+//! selector
+//!   sequence
+//!     condition is_hungry
+//!     action move_towards_goal
+//!   action wander
+//! ```
+//!
+//! Composites take one or more children: `sequence`, `selector`,
+//! `sequence_with_memory`, `selector_with_memory` and
+//! `parallel <success> <failure>` with policies `one` or `all`.
+//! Decorators take exactly one: `inverter`, `succeeder`,
+//! `retry_until_success`, `repeat <n>`, `timeout <ticks>` and
+//! `cooldown <ticks>`. Leaves are `condition <name> [args]` and
+//! `action <name> [args]`, looked up in a `Registry`; leaves with
+//! arguments, such as `condition energy_below 30`, are built by a factory.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::engine::rng::SimRng;
+use crate::engine::world::Grid;
+use super::behavior_tree::{
+    self as bt, BehaviorTreeAgent, IsEnergyBelow, IsStuckFor, MoveToTarget, Node, Policy, Status,
+};
+
+pub type ConditionFn = fn(&BehaviorTreeAgent, &Grid) -> bool;
+pub type ActionFn = fn(&mut BehaviorTreeAgent, &Grid, &mut SimRng) -> Status;
+/// Builds a leaf from the arguments after its name in a tree file, e.g.
+/// `["30"]` for `condition energy_below 30`, typically with `Node::leaf`
+/// or `Node::condition_fn`/`Node::action_fn`.
+pub type LeafFactory = Arc<dyn Fn(&[&str]) -> Result<Node, TreeErrorKind> + Send + Sync>;
+
+/// Named conditions and actions that tree files can refer to.
+#[derive(Clone, Default)]
+pub struct Registry {
+    conditions: BTreeMap<String, ConditionFn>,
+    actions: BTreeMap<String, ActionFn>,
+    condition_factories: BTreeMap<String, LeafFactory>,
+    action_factories: BTreeMap<String, LeafFactory>,
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in leaves: conditions `is_hungry`, `is_exhausted`,
+    /// `is_at_goal`, `energy_below <n>` and `stuck_for <ticks>`; actions
+    /// `move_towards_goal`, `wander`, `explore`, `rest` and
+    /// `move_to_target`.
+    pub fn standard() -> Self {
+        Self::new()
+            .with_condition("is_hungry", bt::is_hungry)
+            .with_condition("is_exhausted", bt::is_exhausted)
+            .with_condition("is_at_goal", bt::is_at_goal)
+            .with_condition_factory("energy_below", |args| {
+                Ok(Node::leaf(IsEnergyBelow(number_arg("energy_below", args)?)))
+            })
+            .with_condition_factory("stuck_for", |args| Ok(Node::leaf(IsStuckFor(number_arg("stuck_for", args)?))))
+            .with_action("move_towards_goal", bt::move_towards_goal)
+            .with_action("wander", bt::wander)
+            .with_action("explore", bt::explore)
+            .with_action("rest", bt::rest)
+            .with_action_factory("move_to_target", |args| {
+                expect_args("move_to_target", args, 0)?;
+                Ok(Node::leaf(MoveToTarget))
+            })
+    }
+
+    /// Register (or replace) a condition under `name`.
+    pub fn with_condition(mut self, name: &str, condition: ConditionFn) -> Self {
+        self.conditions.insert(name.to_string(), condition);
+        self
+    }
+
+    /// Register (or replace) an action under `name`.
+    pub fn with_action(mut self, name: &str, action: ActionFn) -> Self {
+        self.actions.insert(name.to_string(), action);
+        self
+    }
+
+    /// Register (or replace) a condition built by `factory` from its
+    /// arguments in the tree file.
+    pub fn with_condition_factory<F>(mut self, name: &str, factory: F) -> Self
+    where
+        F: Fn(&[&str]) -> Result<Node, TreeErrorKind> + Send + Sync + 'static,
+    {
+        self.condition_factories.insert(name.to_string(), Arc::new(factory));
+        self
+    }
+
+    /// Register (or replace) an action built by `factory` from its
+    /// arguments in the tree file.
+    pub fn with_action_factory<F>(mut self, name: &str, factory: F) -> Self
+    where
+        F: Fn(&[&str]) -> Result<Node, TreeErrorKind> + Send + Sync + 'static,
+    {
+        self.action_factories.insert(name.to_string(), Arc::new(factory));
+        self
+    }
+
+    /// The name `condition` is registered under, if any.
+    pub fn condition_name(&self, condition: ConditionFn) -> Option<&str> {
+        self.conditions
+            .iter()
+            .find(|&(_, &registered)| std::ptr::fn_addr_eq(registered, condition))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The name `action` is registered under, if any.
+    pub fn action_name(&self, action: ActionFn) -> Option<&str> {
+        self.actions
+            .iter()
+            .find(|&(_, &registered)| std::ptr::fn_addr_eq(registered, action))
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn condition_names(&self) -> impl Iterator<Item = &str> {
+        self.conditions.keys().chain(self.condition_factories.keys()).map(String::as_str)
+    }
+
+    pub fn action_names(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().chain(self.action_factories.keys()).map(String::as_str)
+    }
+}
+
+/// Check that leaf `name` was given `expected` arguments; for use in
+/// leaf factories.
+pub fn expect_args(name: &str, args: &[&str], expected: usize) -> Result<(), TreeErrorKind> {
+    if args.len() == expected {
+        Ok(())
+    } else {
+        Err(TreeErrorKind::WrongArgumentCount { node: name.to_string(), expected, found: args.len() })
+    }
+}
+
+/// The single whole-number argument of leaf `name`; for use in leaf
+/// factories.
+pub fn number_arg(name: &str, args: &[&str]) -> Result<u32, TreeErrorKind> {
+    expect_args(name, args, 1)?;
+    args[0]
+        .parse()
+        .map_err(|_| TreeErrorKind::InvalidArgument { node: name.to_string(), value: args[0].to_string() })
+}
+
+/// What went wrong while parsing a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeErrorKind {
+    /// The text contains no nodes.
+    Empty,
+    /// A second node at the root's level (or shallower).
+    MultipleRoots,
+    /// Children indented differently from their first sibling.
+    InconsistentIndent,
+    UnknownNode(String),
+    UnknownCondition(String),
+    UnknownAction(String),
+    WrongArgumentCount { node: String, expected: usize, found: usize },
+    /// A number or policy that doesn't parse.
+    InvalidArgument { node: String, value: String },
+    /// A composite or decorator with no children.
+    MissingChild(String),
+    /// A decorator with more than one child, or a leaf with any.
+    UnexpectedChildren(String),
+}
+
+/// A tree parse error with a 1-based line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeParseError {
+    pub line: usize,
+    pub kind: TreeErrorKind,
+}
+
+impl fmt::Display for TreeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeErrorKind::Empty => write!(f, "tree has no nodes"),
+            TreeErrorKind::MultipleRoots => write!(f, "a tree has exactly one root node"),
+            TreeErrorKind::InconsistentIndent => write!(f, "indentation doesn't match the previous sibling"),
+            TreeErrorKind::UnknownNode(name) => write!(f, "unknown node type `{}`", name),
+            TreeErrorKind::UnknownCondition(name) => write!(f, "unknown condition `{}`", name),
+            TreeErrorKind::UnknownAction(name) => write!(f, "unknown action `{}`", name),
+            TreeErrorKind::WrongArgumentCount { node, expected, found } => {
+                write!(f, "`{}` takes {} argument(s), found {}", node, expected, found)
+            }
+            TreeErrorKind::InvalidArgument { node, value } => {
+                write!(f, "`{}` is not a valid argument for `{}`", value, node)
+            }
+            TreeErrorKind::MissingChild(node) => write!(f, "`{}` needs a child node", node),
+            TreeErrorKind::UnexpectedChildren(node) => write!(f, "`{}` has too many child nodes", node),
+        }
+    }
+}
+
+impl fmt::Display for TreeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl Error for TreeParseError {}
+
+/// One non-blank, non-comment line.
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    keyword: &'a str,
+    args: Vec<&'a str>,
+}
+
+/// Parse a tree, resolving leaf names in `registry`.
+pub fn parse_tree(source: &str, registry: &Registry) -> Result<Node, TreeParseError> {
+    let lines: Vec<Line> = source
+        .lines()
+        .enumerate()
+        .filter_map(|(i, text)| {
+            let text = text.split('#').next().unwrap_or("").trim_end();
+            let mut words = text.split_whitespace();
+            let keyword = words.next()?;
+            Some(Line {
+                number: i + 1,
+                indent: text.len() - text.trim_start().len(),
+                keyword,
+                args: words.collect(),
+            })
+        })
+        .collect();
+
+    if lines.is_empty() {
+        return Err(TreeParseError { line: 1, kind: TreeErrorKind::Empty });
+    }
+    let mut next = 0;
+    let tree = parse_node(&lines, &mut next, registry)?;
+    // Everything indented under the root has been consumed.
+    if let Some(extra) = lines.get(next) {
+        return Err(TreeParseError { line: extra.number, kind: TreeErrorKind::MultipleRoots });
+    }
+    Ok(tree)
+}
+
+/// Load a tree file written in the format above.
+pub fn load_tree<P: AsRef<Path>>(path: P, registry: &Registry) -> Result<Node, Box<dyn Error>> {
+    let text = fs::read_to_string(&path)?;
+    let tree = parse_tree(&text, registry).map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+    Ok(tree)
+}
+
+/// Parse `lines[*next]` and its children, leaving `next` after them.
+fn parse_node(lines: &[Line], next: &mut usize, registry: &Registry) -> Result<Node, TreeParseError> {
+    let line = &lines[*next];
+    *next += 1;
+
+    let mut children = Vec::new();
+    if let Some(first) = lines.get(*next).filter(|child| child.indent > line.indent) {
+        let indent = first.indent;
+        while let Some(child) = lines.get(*next).filter(|child| child.indent > line.indent) {
+            if child.indent != indent {
+                return Err(TreeParseError { line: child.number, kind: TreeErrorKind::InconsistentIndent });
+            }
+            children.push(parse_node(lines, next, registry)?);
+        }
+    }
+    build(line, children, registry).map_err(|kind| TreeParseError { line: line.number, kind })
+}
+
+fn build(line: &Line, children: Vec<Node>, registry: &Registry) -> Result<Node, TreeErrorKind> {
+    let node = line.keyword;
+    let args = |expected: usize| {
+        if line.args.len() == expected {
+            Ok(&line.args)
+        } else {
+            Err(TreeErrorKind::WrongArgumentCount { node: node.to_string(), expected, found: line.args.len() })
+        }
+    };
+    let invalid = |value: &str| TreeErrorKind::InvalidArgument { node: node.to_string(), value: value.to_string() };
+    let number = |value: &str| value.parse::<u32>().map_err(|_| invalid(value));
+    let policy = |value: &str| match value {
+        "one" => Ok(Policy::RequireOne),
+        "all" => Ok(Policy::RequireAll),
+        _ => Err(invalid(value)),
+    };
+
+    Ok(match node {
+        "sequence" => {
+            args(0)?;
+            Node::Sequence(some_children(node, children)?)
+        }
+        "selector" => {
+            args(0)?;
+            Node::Selector(some_children(node, children)?)
+        }
+        "sequence_with_memory" => {
+            args(0)?;
+            Node::sequence_with_memory(some_children(node, children)?)
+        }
+        "selector_with_memory" => {
+            args(0)?;
+            Node::selector_with_memory(some_children(node, children)?)
+        }
+        "parallel" => {
+            let args = args(2)?;
+            Node::parallel(policy(args[0])?, policy(args[1])?, some_children(node, children)?)
+        }
+        "inverter" => {
+            args(0)?;
+            Node::inverter(only_child(node, children)?)
+        }
+        "succeeder" => {
+            args(0)?;
+            Node::succeeder(only_child(node, children)?)
+        }
+        "retry_until_success" => {
+            args(0)?;
+            Node::retry_until_success(only_child(node, children)?)
+        }
+        "repeat" => {
+            let times = number(args(1)?[0])?;
+            Node::repeat(times, only_child(node, children)?)
+        }
+        "timeout" => {
+            let ticks = number(args(1)?[0])?;
+            Node::timeout(ticks, only_child(node, children)?)
+        }
+        "cooldown" => {
+            let ticks = number(args(1)?[0])?;
+            Node::cooldown(ticks as u64, only_child(node, children)?)
+        }
+        "condition" | "action" => {
+            let Some((&name, leaf_args)) = line.args.split_first() else {
+                return Err(TreeErrorKind::WrongArgumentCount { node: node.to_string(), expected: 1, found: 0 });
+            };
+            no_children(node, &children)?;
+            let (plain, factory) = if node == "condition" {
+                (registry.conditions.get(name).map(|&c| Node::Condition(c)), registry.condition_factories.get(name))
+            } else {
+                (registry.actions.get(name).map(|&a| Node::Action(a)), registry.action_factories.get(name))
+            };
+            let leaf = match (plain, factory) {
+                (_, Some(factory)) => factory(leaf_args)?,
+                (Some(leaf), None) => {
+                    expect_args(name, leaf_args, 0)?;
+                    leaf
+                }
+                (None, None) if node == "condition" => return Err(TreeErrorKind::UnknownCondition(name.to_string())),
+                (None, None) => return Err(TreeErrorKind::UnknownAction(name.to_string())),
+            };
+            // Traces show the leaf as written, whatever registry it came from.
+            leaf.named(line.args.join(" "))
+        }
+        other => return Err(TreeErrorKind::UnknownNode(other.to_string())),
+    })
+}
+
+fn some_children(node: &str, children: Vec<Node>) -> Result<Vec<Node>, TreeErrorKind> {
+    if children.is_empty() {
+        return Err(TreeErrorKind::MissingChild(node.to_string()));
+    }
+    Ok(children)
+}
+
+fn only_child(node: &str, mut children: Vec<Node>) -> Result<Node, TreeErrorKind> {
+    match children.len() {
+        0 => Err(TreeErrorKind::MissingChild(node.to_string())),
+        1 => Ok(children.remove(0)),
+        _ => Err(TreeErrorKind::UnexpectedChildren(node.to_string())),
+    }
+}
+
+fn no_children(node: &str, children: &[Node]) -> Result<(), TreeErrorKind> {
+    if children.is_empty() {
+        Ok(())
+    } else {
+        Err(TreeErrorKind::UnexpectedChildren(node.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::LegacyAgent;
+    use crate::agents::adapter::legacy;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::{Position, World};
+
+    #[test]
+    fn tree_files_match_the_built_in_tree_and_report_bad_names() {
+        let registry = Registry::standard();
+        let default = load_tree("trees/default.bt", &registry).unwrap();
+        let grid = Grid::with_obstacles(10, 5, Position { x: 9, y: 4 }, &[(3, 3), (4, 1)]);
+        let trace = |agent: BehaviorTreeAgent| {
+            let mut world = World::with_rng(grid.clone(), legacy(agent), 0, seeded(7));
+            (0..60)
+                .map(|_| {
+                    world.update();
+                    world.body.position
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            trace(BehaviorTreeAgent::new(0, 0).with_tree(default)),
+            trace(BehaviorTreeAgent::new(0, 0))
+        );
+        for path in ["trees/explorer.bt", "trees/cautious.bt"] {
+            load_tree(path, &registry).unwrap();
+        }
+
+        let error = |source: &str| parse_tree(source, &registry).err().map(|e| (e.line, e.kind));
+        let tree = "selector\n  # try the goal first\n  action move_towards_goal\n  action fly\n";
+        assert_eq!(error(tree), Some((4, TreeErrorKind::UnknownAction("fly".to_string()))));
+        assert_eq!(
+            error("inverter\n  condition is_sleepy"),
+            Some((2, TreeErrorKind::UnknownCondition("is_sleepy".to_string())))
+        );
+        assert_eq!(error("sequence\n  shuffle"), Some((2, TreeErrorKind::UnknownNode("shuffle".to_string()))));
+        assert_eq!(
+            error("repeat many\n  action wander"),
+            Some((1, TreeErrorKind::InvalidArgument { node: "repeat".to_string(), value: "many".to_string() }))
+        );
+        assert_eq!(
+            error("selector\n    action wander\n  action rest"),
+            Some((3, TreeErrorKind::InconsistentIndent))
+        );
+        assert_eq!(error("action wander\naction rest"), Some((2, TreeErrorKind::MultipleRoots)));
+        assert_eq!(error("# nothing here\n"), Some((1, TreeErrorKind::Empty)));
+    }
+
+    #[test]
+    fn traces_name_leaves_from_a_custom_registry() {
+        fn is_sleepy(agent: &BehaviorTreeAgent, _grid: &Grid) -> bool {
+            agent.energy() < 50
+        }
+        let registry = Registry::standard().with_condition("is_sleepy", is_sleepy).with_action("nap", bt::rest);
+        let tree = parse_tree("selector\n  sequence\n    condition is_sleepy\n    action nap\n  action wander\n", &registry).unwrap();
+        let mut agent = BehaviorTreeAgent::new(0, 0).with_tree(tree);
+        let grid = Grid::new(5, 1, Position { x: 4, y: 0 });
+        agent.update(&grid, &mut seeded(0));
+
+        let labels: Vec<String> = agent.tree_outline().iter().map(|node| node.label.clone()).collect();
+        assert_eq!(labels, ["Selector", "Sequence", "is_sleepy", "nap", "wander"]);
+        assert_eq!(LegacyAgent::debug_state(&agent), "is_sleepy: Failure, wander: Success");
+        let trace = LegacyAgent::decision_trace(&agent);
+        assert_eq!(trace[2].label, "is_sleepy");
+    }
+
+    #[test]
+    fn leaf_factories_take_arguments_from_tree_files() {
+        // A closure leaf, registered with the value it should compare to.
+        let limit = 3;
+        let registry = Registry::standard().with_condition_factory("column_below", move |args| {
+            expect_args("column_below", args, 0)?;
+            Ok(Node::condition_fn(move |agent: &BehaviorTreeAgent, _: &Grid| agent.position().x < limit))
+        });
+        let source = "sequence\n  condition column_below\n  condition energy_below 150\n  inverter\n    condition stuck_for 2\n  action move_to_target\n";
+        let mut tree = parse_tree(source, &registry).unwrap();
+        let labels: Vec<String> = tree.outline().into_iter().map(|node| node.label).collect();
+        assert_eq!(
+            &labels[1..],
+            ["column_below", "energy_below 150", "Inverter", "stuck_for 2", "move_to_target"]
+        );
+
+        // No target on the blackboard, so the tree gets as far as the move.
+        let grid = Grid::new(5, 1, Position { x: 4, y: 0 });
+        let mut agent = BehaviorTreeAgent::new(0, 0);
+        assert_eq!(tree.tick(&mut agent, &grid, &mut seeded(0)), Status::Failure);
+        assert_eq!(agent.trace().len(), 6);
+
+        let error = |source: &str| parse_tree(source, &registry).err().map(|e| (e.line, e.kind));
+        assert_eq!(
+            error("condition energy_below lots"),
+            Some((1, TreeErrorKind::InvalidArgument { node: "energy_below".to_string(), value: "lots".to_string() }))
+        );
+        assert_eq!(
+            error("condition stuck_for"),
+            Some((1, TreeErrorKind::WrongArgumentCount { node: "stuck_for".to_string(), expected: 1, found: 0 }))
+        );
+        assert_eq!(
+            error("action wander fast"),
+            Some((1, TreeErrorKind::WrongArgumentCount { node: "wander".to_string(), expected: 0, found: 1 }))
+        );
+    }
+}
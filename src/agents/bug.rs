@@ -0,0 +1,287 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::engine::action::{Action, Direction, Observation};
+use crate::engine::rng::SimRng;
+use crate::engine::world::{Grid, Position};
+use super::wall_follower::{is_open, Hand, Tracer};
+use super::Agent;
+
+/// Which Bug algorithm decides when to leave an obstacle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BugVariant {
+    /// Circle the whole obstacle, return to the point on it closest to
+    /// the goal, and head for the goal from there.
+    Bug1,
+    /// Follow the obstacle until back on the start–goal line (the
+    /// m-line) closer to the goal than where it was hit.
+    Bug2,
+}
+
+/// The cells of a 4-connected line from `from` to `to`, both included.
+pub fn grid_line(from: Position, to: Position) -> Vec<Position> {
+    let (nx, ny) = (from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+    let mut cells = Vec::with_capacity(nx + ny + 1);
+    let mut pos = from;
+    cells.push(pos);
+    let (mut ix, mut iy) = (0, 0);
+    while ix < nx || iy < ny {
+        // Step along whichever axis keeps closer to the true line.
+        if (1 + 2 * ix) * ny < (1 + 2 * iy) * nx {
+            pos.x = if to.x > from.x { pos.x + 1 } else { pos.x - 1 };
+            ix += 1;
+        } else {
+            pos.y = if to.y > from.y { pos.y + 1 } else { pos.y - 1 };
+            iy += 1;
+        }
+        cells.push(pos);
+    }
+    cells
+}
+
+enum Mode {
+    /// Walking `line` towards the goal.
+    ToGoal,
+    /// Following the boundary of the obstacle hit at `line[hit]`.
+    Follow {
+        tracer: Tracer,
+        hit: usize,
+        /// Closest cell to the goal seen on the boundary (Bug1).
+        closest: Position,
+        /// Bug1 has been all the way round and is heading for `closest`.
+        returning: bool,
+    },
+    /// The goal can't be reached.
+    Stuck,
+}
+
+/// Bug1/Bug2 navigation.
+///
+/// Walks a straight (4-connected) line towards the goal and, on hitting
+/// an obstacle, follows its boundary with one hand until the variant's
+/// leave condition holds. Like the wall follower it only senses
+/// neighboring cells and knows where the goal is. Circling an obstacle
+/// without meeting the leave condition means the goal is unreachable,
+/// and the agent reports stuck.
+pub struct BugAgent {
+    variant: BugVariant,
+    hand: Hand,
+    /// Bug2's m-line, or Bug1's line from its last leave point.
+    line: Vec<Position>,
+    /// Index of the agent's cell on `line`.
+    index: usize,
+    mode: Mode,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+}
+
+impl BugAgent {
+    pub fn new(variant: BugVariant) -> Self {
+        Self {
+            variant,
+            hand: Hand::Right,
+            line: Vec::new(),
+            index: 0,
+            mode: Mode::ToGoal,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+        }
+    }
+
+    /// Create a Bug agent with the shared noise/decay cognitive parameters.
+    pub fn with_config(variant: BugVariant, noise: f32, decay_rate: f32) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            ..Self::new(variant)
+        }
+    }
+
+    /// Follow obstacles with `hand` instead of the right hand.
+    pub fn with_hand(self, hand: Hand) -> Self {
+        Self { hand, ..self }
+    }
+
+    pub fn variant(&self) -> BugVariant {
+        self.variant
+    }
+
+    /// Walk a new line from `pos` to `goal`.
+    fn head_for(&mut self, pos: Position, goal: Position) {
+        self.line = grid_line(pos, goal);
+        self.index = 0;
+        self.mode = Mode::ToGoal;
+    }
+
+    /// Next move while walking the line; starts following on a hit.
+    fn walk_line(&mut self, grid: &Grid, pos: Position, goal: Position) -> Option<Direction> {
+        // Knocked off the line (noise, or a refused move): Bug2 keeps its
+        // m-line if we are still on it, otherwise start a new line.
+        match self.line.iter().position(|&cell| cell == pos) {
+            Some(i) if self.line.last() == Some(&goal) => self.index = i,
+            _ => self.head_for(pos, goal),
+        }
+        let next = *self.line.get(self.index + 1)?;
+        let dir = Direction::between(pos, next)?;
+        if is_open(grid, pos, dir) {
+            return Some(dir);
+        }
+        self.mode = Mode::Follow {
+            tracer: Tracer::begin(self.hand, dir),
+            hit: self.index,
+            closest: pos,
+            returning: false,
+        };
+        self.follow(grid, pos, goal)
+    }
+
+    /// Next move while following an obstacle; leaves it when allowed.
+    fn follow(&mut self, grid: &Grid, pos: Position, goal: Position) -> Option<Direction> {
+        let Mode::Follow { tracer, hit, closest, returning } = &mut self.mode else {
+            return None;
+        };
+        match self.variant {
+            BugVariant::Bug2 => {
+                // Back on the m-line, nearer the goal, with the way ahead clear.
+                let ahead = self.line[*hit + 1..]
+                    .iter()
+                    .position(|&cell| cell == pos)
+                    .map(|i| *hit + 1 + i);
+                if let Some(i) = ahead
+                    && let Some(&next) = self.line.get(i + 1)
+                    && let Some(dir) = Direction::between(pos, next)
+                    && is_open(grid, pos, dir)
+                {
+                    self.index = i;
+                    self.mode = Mode::ToGoal;
+                    return Some(dir);
+                }
+            }
+            BugVariant::Bug1 => {
+                if *returning && pos == *closest {
+                    self.head_for(pos, goal);
+                    let dir = Direction::between(pos, *self.line.get(1)?)?;
+                    if is_open(grid, pos, dir) {
+                        return Some(dir);
+                    }
+                    // The best point on the obstacle still faces a wall.
+                    self.mode = Mode::Stuck;
+                    return None;
+                }
+                if grid.distance(pos, goal) < grid.distance(*closest, goal) {
+                    *closest = pos;
+                }
+            }
+        }
+
+        let dir = tracer.step(grid, pos)?;
+        if tracer.looped() {
+            match self.variant {
+                BugVariant::Bug1 if !*returning => {
+                    // Keep going round to the closest point; restart the
+                    // trace so a second lap is detected too.
+                    *returning = true;
+                    tracer.restart();
+                }
+                _ => {
+                    self.mode = Mode::Stuck;
+                    return None;
+                }
+            }
+        }
+        Some(dir)
+    }
+}
+
+impl Agent for BugAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+        let (grid, pos) = (obs.grid, obs.position);
+
+        if pos == obs.goal {
+            return Action::Interact;
+        }
+        if matches!(self.mode, Mode::Stuck) {
+            return Action::Wait;
+        }
+
+        let effective_noise = self.noise * self.exploration_rate;
+        if effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some(&(x, y)) = grid.walkable_neighbors(pos.x, pos.y).choose(rng)
+        {
+            // A random step abandons any obstacle being followed.
+            self.noise_triggered = true;
+            self.mode = Mode::ToGoal;
+            return Direction::between(pos, Position { x, y }).map_or(Action::Wait, Action::Move);
+        }
+
+        let dir = match self.mode {
+            Mode::ToGoal => self.walk_line(grid, pos, obs.goal),
+            _ => self.follow(grid, pos, obs.goal),
+        };
+        dir.map_or(Action::Wait, Action::Move)
+    }
+
+    fn name(&self) -> &'static str {
+        match self.variant {
+            BugVariant::Bug1 => "Bug1",
+            BugVariant::Bug2 => "Bug2",
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_stuck(&self) -> bool {
+        matches!(self.mode, Mode::Stuck)
+    }
+
+    fn debug_state(&self) -> String {
+        match &self.mode {
+            Mode::ToGoal => format!("Heading for goal ({}/{})", self.index, self.line.len().saturating_sub(1)),
+            Mode::Follow { returning: true, closest, .. } => {
+                format!("Returning to ({}, {})", closest.x, closest.y)
+            }
+            Mode::Follow { tracer, .. } => format!("Following obstacle, heading {:?}", tracer.heading),
+            Mode::Stuck => "Stuck (goal unreachable)".to_string(),
+        }
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    fn run(grid: &Grid, variant: BugVariant) -> World {
+        let mut world = World::with_rng(grid.clone(), Box::new(BugAgent::new(variant)), 0, seeded(0));
+        world.run_until_done(300);
+        world
+    }
+
+    #[test]
+    fn both_variants_escape_a_cup_and_detect_unreachable_goals() {
+        let cup = Grid::load("maps/u_trap.map").unwrap();
+        for variant in [BugVariant::Bug1, BugVariant::Bug2] {
+            let world = run(&cup, variant);
+            assert!(world.has_reached_goal(), "{:?} took {} steps", variant, world.step);
+        }
+
+        let island = Grid::load("tests/fixtures/walled_goal.map").unwrap();
+        for variant in [BugVariant::Bug1, BugVariant::Bug2] {
+            assert!(run(&island, variant).is_agent_stuck(), "{:?}", variant);
+        }
+    }
+}
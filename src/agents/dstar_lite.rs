@@ -0,0 +1,186 @@
+use rand::Rng;
+use crate::algorithms::dstar_lite::DStarLite;
+use crate::engine::rng::SimRng;
+use crate::engine::world::{Grid, Position};
+use super::belief::{BeliefMap, Sensor};
+use super::memory::SpatialMemory;
+
+/// Agent that follows a D* Lite plan and repairs it incrementally
+/// whenever cells change, instead of re-running A* from scratch.
+///
+/// When the goal is cut off it waits in place, since a dynamic grid may
+/// open a route again later.
+pub struct DStarLiteAgent {
+    pos: Position,
+    planner: Option<DStarLite>,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    memory: SpatialMemory,
+    noise_triggered: bool,
+    /// Number of ticks on which the plan was repaired.
+    replans: u32,
+    /// Limited perception; `None` plans on the full grid.
+    sensor: Option<Sensor>,
+    belief: Option<BeliefMap>,
+}
+
+impl DStarLiteAgent {
+    pub fn new(start_x: usize, start_y: usize) -> Self {
+        Self {
+            pos: Position {
+                x: start_x,
+                y: start_y,
+            },
+            planner: None,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            memory: SpatialMemory::new(0),
+            noise_triggered: false,
+            replans: 0,
+            sensor: None,
+            belief: None,
+        }
+    }
+
+    /// Create a D* Lite agent with full cognitive parameters.
+    pub fn with_config(
+        start_x: usize,
+        start_y: usize,
+        noise: f32,
+        memory_capacity: usize,
+        decay_rate: f32,
+    ) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            memory: SpatialMemory::new(memory_capacity),
+            ..Self::new(start_x, start_y)
+        }
+    }
+
+    /// Restrict perception to `sensor`; newly seen cells are fed to the
+    /// planner as changes, with unseen cells treated as open.
+    pub fn with_sensor(self, sensor: Sensor) -> Self {
+        Self {
+            sensor: Some(sensor),
+            ..self
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
+    /// What the agent believes about the grid (only with a sensor).
+    pub fn belief(&self) -> Option<&BeliefMap> {
+        self.belief.as_ref()
+    }
+
+    /// Whether the goal is currently unreachable.
+    pub fn is_stuck(&self) -> bool {
+        self.planner.as_ref().is_some_and(|planner| !planner.has_path())
+    }
+
+    /// Number of ticks on which the plan was repaired.
+    pub fn replans(&self) -> u32 {
+        self.replans
+    }
+
+    /// Current route to the goal (empty before the first tick or when cut off).
+    pub fn path(&self) -> Vec<(usize, usize)> {
+        self.planner.as_ref().map(DStarLite::path).unwrap_or_default()
+    }
+
+    /// Sync the plan with the grid, then take one step along it.
+    pub fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.noise_triggered = false;
+        self.memory.record(self.pos);
+        self.exploration_rate *= self.decay_rate;
+
+        if self.pos == grid.goal {
+            return;
+        }
+
+        if let Some(sensor) = self.sensor {
+            self.belief
+                .get_or_insert_with(|| BeliefMap::new(grid))
+                .observe(grid, self.pos, &sensor);
+        }
+        let plan_grid = self.belief.as_ref().map_or(grid, BeliefMap::grid);
+
+        match &mut self.planner {
+            Some(planner) => {
+                if planner.update(self.pos, plan_grid) {
+                    self.replans += 1;
+                    println!("D* Lite: Grid changed, plan repaired ({} replans)", self.replans);
+                }
+            }
+            None => self.planner = Some(DStarLite::new(self.pos, plan_grid)),
+        }
+
+        // Decision noise (modulated by exploration rate).
+        let effective_noise = self.noise * self.exploration_rate;
+        if effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some((nx, ny)) = plan_grid.random_walkable_neighbor(self.pos.x, self.pos.y, rng)
+        {
+            self.pos = Position { x: nx, y: ny };
+            self.noise_triggered = true;
+            println!("D* Lite: Noise! Random move to ({}, {})", nx, ny);
+            return;
+        }
+
+        match self.planner.as_ref().and_then(|planner| planner.next_step(self.pos)) {
+            Some(next) => {
+                self.pos = next;
+                println!("D* Lite: Moving to ({}, {})", next.x, next.y);
+            }
+            None => println!("D* Lite: No route to goal, waiting"),
+        }
+    }
+}
+
+impl super::LegacyAgent for DStarLiteAgent {
+    fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.update(grid, rng);
+    }
+
+    fn position(&self) -> Position {
+        self.pos
+    }
+
+    fn set_position(&mut self, pos: Position) {
+        self.pos = pos;
+    }
+
+    fn set_energy(&mut self, _energy: u32) {}
+
+    fn name(&self) -> &'static str {
+        "DStarLite"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_stuck(&self) -> bool {
+        self.is_stuck()
+    }
+
+    fn debug_state(&self) -> String {
+        match self.planner.as_ref().and_then(DStarLite::cost_to_goal) {
+            Some(cost) => format!("Cost to goal: {:.1}", cost),
+            None => "No route".to_string(),
+        }
+    }
+
+    fn replans(&self) -> Option<u32> {
+        Some(self.replans)
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
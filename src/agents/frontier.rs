@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::algorithms::astar::find_path;
+use crate::engine::action::{Action, Direction, Observation, Outcome};
+use crate::engine::grid::Grid;
+use crate::engine::rng::SimRng;
+use crate::engine::world::Position;
+use super::belief::{BeliefMap, Sensor};
+use super::Agent;
+
+/// What the agent is currently travelling to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// A known open cell next to unexplored ones.
+    Frontier(Position),
+    /// The goal, once it has been seen.
+    Goal(Position),
+}
+
+/// Frontier-based explorer.
+///
+/// Keeps a `BeliefMap` of what its sensor has shown it. Until the goal
+/// has been seen it repeatedly travels to the nearest frontier (a known
+/// open cell bordering unknown ones), planning with `find_path` through
+/// known cells only. Once the goal is in view it plans straight for it,
+/// optimistically through unknown cells, and replans as walls appear.
+/// Without a sensor it sees the whole grid and behaves like A*.
+pub struct FrontierAgent {
+    sensor: Option<Sensor>,
+    belief: Option<BeliefMap>,
+    path: Vec<(usize, usize)>,
+    path_index: usize,
+    target: Option<Target>,
+    /// Set when there is neither a route to the goal nor a frontier left.
+    stuck: bool,
+    plans: u32,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+}
+
+impl FrontierAgent {
+    pub fn new() -> Self {
+        Self {
+            sensor: None,
+            belief: None,
+            path: Vec::new(),
+            path_index: 0,
+            target: None,
+            stuck: false,
+            plans: 0,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+        }
+    }
+
+    /// Create a frontier explorer with the shared noise/decay cognitive
+    /// parameters.
+    pub fn with_config(noise: f32, decay_rate: f32) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            ..Self::new()
+        }
+    }
+
+    /// Restrict perception to `sensor`.
+    pub fn with_sensor(self, sensor: Sensor) -> Self {
+        Self {
+            sensor: Some(sensor),
+            ..self
+        }
+    }
+
+    /// What the agent has seen so far.
+    pub fn belief(&self) -> Option<&BeliefMap> {
+        self.belief.as_ref()
+    }
+
+    /// Plans made after the first one.
+    pub fn replans(&self) -> u32 {
+        self.plans.saturating_sub(1)
+    }
+
+    /// Whether the current target and path still hold on `belief`.
+    /// Sighting the goal invalidates any exploration target.
+    fn plan_is_valid(&self, belief: &BeliefMap, pos: Position, goal: Position, goal_seen: bool) -> bool {
+        let Some(target) = self.target else {
+            return false;
+        };
+        let remaining = &self.path[(self.path_index + 1).min(self.path.len())..];
+        if remaining.is_empty() || remaining.iter().any(|&(x, y)| !belief.grid().is_walkable(x, y)) {
+            return false;
+        }
+        match target {
+            Target::Frontier(cell) => !goal_seen && cell != pos && is_frontier(belief, cell),
+            Target::Goal(target) => target == goal,
+        }
+    }
+
+    /// Choose a target and plan a path to it on `belief`.
+    fn plan(&mut self, belief: &BeliefMap, pos: Position, goal: Position, goal_seen: bool) {
+        self.plans += 1;
+        self.path.clear();
+        self.path_index = 0;
+        self.target = None;
+
+        let start = (pos.x, pos.y);
+        if goal_seen && let Some(path) = find_path(start, (goal.x, goal.y), belief.grid(), None) {
+            self.path = path;
+            self.target = Some(Target::Goal(goal));
+            return;
+        }
+        // The goal is unseen or walled off as far as we know: explore.
+        let known = known_grid(belief);
+        if let Some(frontier) = nearest_frontier(belief, &known, pos)
+            && let Some(path) = find_path(start, (frontier.x, frontier.y), &known, None)
+        {
+            self.path = path;
+            self.target = Some(Target::Frontier(frontier));
+        }
+    }
+}
+
+impl Default for FrontierAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `cell` is known, open, and has an unknown 4-neighbor.
+fn is_frontier(belief: &BeliefMap, cell: Position) -> bool {
+    let grid = belief.grid();
+    if !belief.is_known(cell.x, cell.y) || !grid.is_walkable(cell.x, cell.y) {
+        return false;
+    }
+    Direction::CARDINAL.iter().any(|dir| {
+        dir.apply(cell)
+            .is_some_and(|n| n.x < grid.width && n.y < grid.height && !belief.is_known(n.x, n.y))
+    })
+}
+
+/// The believed grid with every unknown cell treated as a wall, so plans
+/// stay inside explored space.
+pub(crate) fn known_grid(belief: &BeliefMap) -> Grid {
+    let mut grid = belief.grid().clone();
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if !belief.is_known(x, y) {
+                grid.set_walkable(x, y, false);
+            }
+        }
+    }
+    grid
+}
+
+/// Closest frontier to `from` by moves through known cells.
+pub(crate) fn nearest_frontier(belief: &BeliefMap, known: &Grid, from: Position) -> Option<Position> {
+    let mut seen = vec![vec![false; known.width]; known.height];
+    let mut queue = VecDeque::from([from]);
+    seen[from.y][from.x] = true;
+    while let Some(pos) = queue.pop_front() {
+        if pos != from && is_frontier(belief, pos) {
+            return Some(pos);
+        }
+        for (x, y) in known.walkable_neighbors(pos.x, pos.y) {
+            if !seen[y][x] {
+                seen[y][x] = true;
+                queue.push_back(Position { x, y });
+            }
+        }
+    }
+    None
+}
+
+impl Agent for FrontierAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+
+        if obs.position == obs.goal {
+            return Action::Interact;
+        }
+
+        // Look around. Without a sensor, every cell is in view.
+        let grid = obs.grid;
+        let sensor = self.sensor.unwrap_or(Sensor::new(grid.width + grid.height, false));
+        let mut belief = self.belief.take().unwrap_or_else(|| BeliefMap::new(grid));
+        belief.observe(grid, obs.position, &sensor);
+        let goal_seen = belief.is_known(obs.goal.x, obs.goal.y);
+
+        let effective_noise = self.noise * self.exploration_rate;
+        if effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some(&(x, y)) = grid.walkable_neighbors(obs.position.x, obs.position.y).choose(rng)
+        {
+            self.belief = Some(belief);
+            self.noise_triggered = true;
+            self.path.clear();
+            self.target = None;
+            return Direction::between(obs.position, Position { x, y }).map_or(Action::Wait, Action::Move);
+        }
+
+        if !self.plan_is_valid(&belief, obs.position, obs.goal, goal_seen) {
+            self.plan(&belief, obs.position, obs.goal, goal_seen);
+        }
+        self.stuck = self.target.is_none();
+        self.belief = Some(belief);
+
+        if self.stuck || self.path_index + 1 >= self.path.len() {
+            return Action::Wait;
+        }
+        let (x, y) = self.path[self.path_index + 1];
+        match Direction::between(obs.position, Position { x, y }) {
+            Some(dir) => Action::Move(dir),
+            None => {
+                // The world put us somewhere else; replan next tick.
+                self.target = None;
+                Action::Wait
+            }
+        }
+    }
+
+    fn on_outcome(&mut self, outcome: &Outcome) {
+        match outcome {
+            Outcome::Moved { .. } if self.target.is_some() && !self.noise_triggered => self.path_index += 1,
+            Outcome::Blocked | Outcome::Collided => self.target = None,
+            _ => {}
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "Frontier"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_stuck(&self) -> bool {
+        self.stuck
+    }
+
+    fn debug_state(&self) -> String {
+        match self.target {
+            None if self.stuck => "Stuck".to_string(),
+            None => "Idle".to_string(),
+            Some(Target::Frontier(p)) => format!("Exploring frontier ({}, {})", p.x, p.y),
+            Some(Target::Goal(p)) => format!("Heading for goal ({}, {})", p.x, p.y),
+        }
+    }
+
+    fn replans(&self) -> Option<u32> {
+        Some(self.replans())
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+
+    fn planning_radius(&self) -> Option<f32> {
+        self.sensor.map(|sensor| sensor.radius as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn explores_until_the_goal_is_seen_then_heads_for_it() {
+        // The goal sits in a pocket behind a wall with its opening at the
+        // far (right) end, out of sight from the start.
+        let grid = Grid::load("tests/fixtures/hidden_pocket.map").unwrap();
+        let agent = FrontierAgent::new().with_sensor(Sensor::new(2, true));
+
+        let mut world = World::with_rng(grid, Box::new(agent), 0, seeded(0));
+        assert!(world.run_until_done(200));
+        let agent = world.agent.as_any().downcast_ref::<FrontierAgent>().unwrap();
+        assert!(agent.replans() > 0);
+        let belief = agent.belief().unwrap();
+        assert!(belief.known_count() < 70, "explored everything: {}", belief.known_count());
+    }
+}
@@ -0,0 +1,344 @@
+//! Table-driven hierarchical state machines.
+//!
+//! A `StateMachine` is declared as data: one `StateSpec` per state, each
+//! with its guarded transitions, entry/exit actions and a per-tick
+//! behavior. States can be nested by giving them a parent; a state with
+//! children names the child it starts in, and the machine is always in a
+//! leaf state plus all of that state's ancestors.
+//!
+//! Hooks are plain function pointers over the agent type `A`, like
+//! behavior tree conditions and actions.
+
+use crate::engine::rng::SimRng;
+use crate::engine::world::Grid;
+
+/// Whether a transition should fire.
+pub type Guard<A> = fn(&A, &Grid) -> bool;
+/// An entry/exit action or per-tick behavior. Any randomness must come
+/// from the supplied RNG.
+pub type Effect<A> = fn(&mut A, &Grid, &mut SimRng);
+
+/// Go to `target` when `guard` holds.
+pub struct Transition<S, A> {
+    pub guard: Guard<A>,
+    pub target: S,
+}
+
+impl<S: Clone, A> Clone for Transition<S, A> {
+    fn clone(&self) -> Self {
+        Self {
+            guard: self.guard,
+            target: self.target.clone(),
+        }
+    }
+}
+
+/// One row of a state machine's table.
+pub struct StateSpec<S, A> {
+    pub id: S,
+    /// The enclosing state; `None` at the top level.
+    pub parent: Option<S>,
+    /// For a state with children, the child it starts in.
+    pub initial: Option<S>,
+    pub on_enter: Option<Effect<A>>,
+    pub on_exit: Option<Effect<A>>,
+    /// Run every tick while the state is active.
+    pub behavior: Option<Effect<A>>,
+    /// Checked in order; the first whose guard holds fires.
+    pub transitions: Vec<Transition<S, A>>,
+}
+
+impl<S: Clone, A> Clone for StateSpec<S, A> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            parent: self.parent.clone(),
+            initial: self.initial.clone(),
+            on_enter: self.on_enter,
+            on_exit: self.on_exit,
+            behavior: self.behavior,
+            transitions: self.transitions.clone(),
+        }
+    }
+}
+
+impl<S, A> StateSpec<S, A> {
+    /// A top-level state with no hooks or transitions.
+    pub fn new(id: S) -> Self {
+        Self {
+            id,
+            parent: None,
+            initial: None,
+            on_enter: None,
+            on_exit: None,
+            behavior: None,
+            transitions: Vec::new(),
+        }
+    }
+
+    pub fn child_of(mut self, parent: S) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn starts_in(mut self, child: S) -> Self {
+        self.initial = Some(child);
+        self
+    }
+
+    pub fn on_enter(mut self, effect: Effect<A>) -> Self {
+        self.on_enter = Some(effect);
+        self
+    }
+
+    pub fn on_exit(mut self, effect: Effect<A>) -> Self {
+        self.on_exit = Some(effect);
+        self
+    }
+
+    pub fn behavior(mut self, effect: Effect<A>) -> Self {
+        self.behavior = Some(effect);
+        self
+    }
+
+    pub fn transition(mut self, guard: Guard<A>, target: S) -> Self {
+        self.transitions.push(Transition { guard, target });
+        self
+    }
+}
+
+/// A hierarchical state machine over agent type `A`.
+///
+/// Each tick, `transition` checks the transitions of the active states
+/// from the outermost inwards, so a parent's transitions take priority
+/// over its children's, and fires the first whose guard holds: states
+/// are exited innermost first up to the nearest state shared with the
+/// target, then entered down to the target and on into its initial
+/// children. `behave` then runs the active states' behaviors, outermost
+/// first. `step` does both.
+///
+/// The machine starts in its initial state without running entry
+/// actions. States missing from the table act as top-level states with
+/// no hooks or transitions.
+#[derive(Clone)]
+pub struct StateMachine<S, A> {
+    states: Vec<StateSpec<S, A>>,
+    current: S,
+}
+
+impl<S: Copy + PartialEq, A> StateMachine<S, A> {
+    /// An empty machine starting in `initial` (or its initial child,
+    /// once that is declared).
+    pub fn new(initial: S) -> Self {
+        Self {
+            states: Vec::new(),
+            current: initial,
+        }
+    }
+
+    /// Add (or replace) a state's row.
+    pub fn with_state(mut self, spec: StateSpec<S, A>) -> Self {
+        match self.states.iter_mut().find(|state| state.id == spec.id) {
+            Some(state) => *state = spec,
+            None => self.states.push(spec),
+        }
+        self.current = self.innermost(self.current);
+        self
+    }
+
+    /// The active leaf state.
+    pub fn current(&self) -> S {
+        self.current
+    }
+
+    /// The active states, outermost first, ending with `current`.
+    pub fn active(&self) -> Vec<S> {
+        self.path(self.current)
+    }
+
+    /// Whether `state` is the current state or one of its ancestors.
+    pub fn is_in(&self, state: S) -> bool {
+        self.active().contains(&state)
+    }
+
+    pub fn spec(&self, state: S) -> Option<&StateSpec<S, A>> {
+        self.states.iter().find(|spec| spec.id == state)
+    }
+
+    pub fn states(&self) -> &[StateSpec<S, A>] {
+        &self.states
+    }
+
+    /// Fire the first transition whose guard holds, returning the new
+    /// current state.
+    pub fn transition(&mut self, agent: &mut A, grid: &Grid, rng: &mut SimRng) -> Option<S> {
+        let target = self.active().into_iter().find_map(|state| {
+            self.spec(state)?
+                .transitions
+                .iter()
+                .find(|transition| (transition.guard)(agent, grid))
+                .map(|transition| transition.target)
+        })?;
+        self.go_to(target, agent, grid, rng);
+        Some(self.current)
+    }
+
+    /// Run the behaviors of the active states, outermost first.
+    pub fn behave(&self, agent: &mut A, grid: &Grid, rng: &mut SimRng) {
+        for state in self.active() {
+            if let Some(behavior) = self.spec(state).and_then(|spec| spec.behavior) {
+                behavior(agent, grid, rng);
+            }
+        }
+    }
+
+    /// One tick: `transition`, then `behave`.
+    pub fn step(&mut self, agent: &mut A, grid: &Grid, rng: &mut SimRng) {
+        self.transition(agent, grid, rng);
+        self.behave(agent, grid, rng);
+    }
+
+    /// Move to `target` (and its initial children), running exit and
+    /// entry actions as a transition would. Going to an active state
+    /// exits and re-enters it.
+    pub fn go_to(&mut self, target: S, agent: &mut A, grid: &Grid, rng: &mut SimRng) {
+        let from = self.active();
+        let to = self.path(target);
+        let shared = from
+            .iter()
+            .zip(&to)
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(to.len() - 1);
+
+        for &state in from[shared..].iter().rev() {
+            if let Some(on_exit) = self.spec(state).and_then(|spec| spec.on_exit) {
+                on_exit(agent, grid, rng);
+            }
+        }
+        let mut entered = to[shared..].to_vec();
+        let mut state = target;
+        let innermost = self.innermost(target);
+        while state != innermost
+            && let Some(child) = self.spec(state).and_then(|spec| spec.initial)
+        {
+            entered.push(child);
+            state = child;
+        }
+        for &state in &entered {
+            if let Some(on_enter) = self.spec(state).and_then(|spec| spec.on_enter) {
+                on_enter(agent, grid, rng);
+            }
+        }
+        self.current = state;
+    }
+
+    /// `state` and its ancestors, outermost first.
+    fn path(&self, state: S) -> Vec<S> {
+        let mut path = vec![state];
+        while let Some(parent) = self.spec(path[0]).and_then(|spec| spec.parent) {
+            // A cycle in the table would otherwise never end.
+            if path.contains(&parent) {
+                break;
+            }
+            path.insert(0, parent);
+        }
+        path
+    }
+
+    /// Follow initial children down from `state`.
+    fn innermost(&self, mut state: S) -> S {
+        let mut seen = vec![state];
+        while let Some(child) = self.spec(state).and_then(|spec| spec.initial) {
+            if seen.contains(&child) {
+                break;
+            }
+            seen.push(child);
+            state = child;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::Position;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Light {
+        On,
+        Red,
+        Green,
+        Off,
+    }
+
+    #[derive(Default)]
+    struct Lamp {
+        ticks: u32,
+        power: bool,
+        log: Vec<&'static str>,
+    }
+
+    type Spec = StateSpec<Light, Lamp>;
+
+    fn lamp() -> StateMachine<Light, Lamp> {
+        StateMachine::new(Light::On)
+            .with_state(
+                Spec::new(Light::On)
+                    .starts_in(Light::Red)
+                    .on_enter(|lamp, _, _| lamp.log.push("enter on"))
+                    .on_exit(|lamp, _, _| lamp.log.push("exit on"))
+                    .behavior(|lamp, _, _| lamp.ticks += 1)
+                    .transition(|lamp, _| !lamp.power, Light::Off),
+            )
+            .with_state(
+                Spec::new(Light::Red)
+                    .child_of(Light::On)
+                    .on_enter(|lamp, _, _| lamp.log.push("enter red"))
+                    .on_exit(|lamp, _, _| lamp.log.push("exit red"))
+                    .behavior(|lamp, _, _| lamp.log.push("red"))
+                    .transition(|lamp, _| lamp.ticks >= 2, Light::Green),
+            )
+            .with_state(
+                Spec::new(Light::Green)
+                    .child_of(Light::On)
+                    .on_enter(|lamp, _, _| lamp.log.push("enter green"))
+                    .behavior(|lamp, _, _| lamp.log.push("green")),
+            )
+            .with_state(
+                Spec::new(Light::Off)
+                    .on_enter(|lamp, _, _| lamp.log.push("enter off"))
+                    .transition(|lamp, _| lamp.power, Light::On),
+            )
+    }
+
+    #[test]
+    fn nested_states_enter_exit_and_defer_to_their_parents() {
+        let grid = Grid::new(1, 1, Position { x: 0, y: 0 });
+        let mut rng = seeded(0);
+        let mut machine = lamp();
+        let mut state = Lamp { power: true, ..Lamp::default() };
+        assert_eq!(machine.active(), [Light::On, Light::Red]);
+
+        for _ in 0..3 {
+            machine.step(&mut state, &grid, &mut rng);
+        }
+        assert_eq!(state.log, ["red", "red", "exit red", "enter green", "green"]);
+        assert!(machine.is_in(Light::On) && machine.current() == Light::Green);
+
+        // The parent's transition wins, leaving both states.
+        state.log.clear();
+        state.power = false;
+        assert_eq!(machine.transition(&mut state, &grid, &mut rng), Some(Light::Off));
+        assert_eq!(state.log, ["exit on", "enter off"]);
+
+        // Re-entering the parent starts it in its initial child again.
+        state.log.clear();
+        state.power = true;
+        assert_eq!(machine.transition(&mut state, &grid, &mut rng), Some(Light::Red));
+        assert_eq!(state.log, ["enter on", "enter red"]);
+        assert_eq!(machine.transition(&mut state, &grid, &mut rng), Some(Light::Green));
+    }
+}
@@ -0,0 +1,381 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::engine::action::{MAX_ENERGY, REST_RECOVERY};
+use crate::engine::rng::SimRng;
+use crate::engine::world::{Grid, Position};
+use super::memory::SpatialMemory;
+use self::machine::{StateMachine, StateSpec};
+
+pub mod machine;
+
+/// FSM states for the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FSMState {
+    /// Parent of `Exploring` and `Resting`: still looking for the goal.
+    /// Never the current state itself.
+    Active,
+    Exploring,
+    Resting,
+    FoundGoal,
+}
+
+/// The agent's state table:
+///
+/// ```text
+/// Active            --at goal-->           FoundGoal
+///   Exploring       --energy low-->        Resting
+///   Resting         --energy full-->       Exploring
+/// FoundGoal
+/// ```
+///
+/// Exploring moves to a random (preferably unvisited) neighbor and
+/// Resting recovers energy; FoundGoal does nothing.
+pub fn default_machine() -> StateMachine<FSMState, FSMAgent> {
+    StateMachine::new(FSMState::Active)
+        .with_state(
+            StateSpec::new(FSMState::Active)
+                .starts_in(FSMState::Exploring)
+                .transition(at_goal, FSMState::FoundGoal),
+        )
+        .with_state(
+            StateSpec::new(FSMState::Exploring)
+                .child_of(FSMState::Active)
+                .on_enter(announce_rested)
+                .behavior(explore)
+                .transition(is_tired, FSMState::Resting),
+        )
+        .with_state(
+            StateSpec::new(FSMState::Resting)
+                .child_of(FSMState::Active)
+                .on_enter(announce_tired)
+                .behavior(rest)
+                .transition(is_rested, FSMState::Exploring),
+        )
+        .with_state(StateSpec::new(FSMState::FoundGoal).on_enter(announce_goal))
+}
+
+/// FSM-based agent with optional cognitive limitations.
+///
+/// Its states and transitions are declared as data in a `StateMachine`
+/// (see `default_machine`), which `with_machine` can replace.
+pub struct FSMAgent {
+    pos: Position,
+    machine: StateMachine<FSMState, FSMAgent>,
+    energy: u32,
+    /// Energy below which the agent stops exploring to rest.
+    pub low_energy: u32,
+    /// Energy at which a resting agent explores again.
+    pub full_energy: u32,
+    /// Base noise probability (0.0–1.0).
+    noise: f32,
+    /// Current exploration multiplier (starts at 1.0, decays each tick).
+    exploration_rate: f32,
+    /// Per-tick multiplicative decay for exploration_rate (e.g. 0.995).
+    decay_rate: f32,
+    /// Visited-cell memory with bounded capacity.
+    memory: SpatialMemory,
+    /// Did the last update trigger a random noise move?
+    noise_triggered: bool,
+}
+
+impl FSMAgent {
+    /// Create a new FSM agent at the given starting coordinates.
+    pub fn new(start_x: usize, start_y: usize) -> Self {
+        Self {
+            pos: Position {
+                x: start_x,
+                y: start_y,
+            },
+            machine: default_machine(),
+            energy: 100,
+            low_energy: 10,
+            full_energy: MAX_ENERGY,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            memory: SpatialMemory::new(0),
+            noise_triggered: false,
+        }
+    }
+
+    /// Create an FSM agent with decision noise.
+    pub fn with_noise(start_x: usize, start_y: usize, noise: f32) -> Self {
+        Self {
+            noise,
+            ..Self::new(start_x, start_y)
+        }
+    }
+
+    /// Create an FSM agent with full cognitive config.
+    pub fn with_config(
+        start_x: usize,
+        start_y: usize,
+        noise: f32,
+        memory_capacity: usize,
+        decay_rate: f32,
+    ) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            memory: SpatialMemory::new(memory_capacity),
+            ..Self::new(start_x, start_y)
+        }
+    }
+
+    /// Run `machine` instead of `default_machine`.
+    pub fn with_machine(self, machine: StateMachine<FSMState, FSMAgent>) -> Self {
+        Self { machine, ..self }
+    }
+
+    /// Expose read-only state for the world/printing.
+    pub fn state(&self) -> FSMState {
+        self.machine.current()
+    }
+
+    pub fn machine(&self) -> &StateMachine<FSMState, FSMAgent> {
+        &self.machine
+    }
+
+    pub fn energy(&self) -> u32 {
+        self.energy
+    }
+
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
+    /// Update the FSM: fire a transition, then run the state's behavior
+    /// unless decision noise moves the agent instead.
+    pub fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.noise_triggered = false;
+        // Record current position in memory.
+        self.memory.record(self.pos);
+
+        // Decay exploration rate.
+        self.exploration_rate *= self.decay_rate;
+
+        // Take the machine out while it runs so its hooks can borrow the
+        // agent mutably.
+        let placeholder = StateMachine::new(self.machine.current());
+        let mut machine = std::mem::replace(&mut self.machine, placeholder);
+        let entered = machine.transition(self, grid, rng);
+        // Reaching the goal takes the whole tick.
+        if entered != Some(FSMState::FoundGoal) && !self.noise_move(grid, rng) {
+            machine.behave(self, grid, rng);
+        }
+        self.machine = machine;
+    }
+
+    /// Decision noise (modulated by exploration rate): maybe move to a
+    /// random neighbor instead of acting.
+    fn noise_move(&mut self, grid: &Grid, rng: &mut SimRng) -> bool {
+        let effective_noise = self.noise * self.exploration_rate;
+        if effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some((nx, ny)) = grid.random_walkable_neighbor(self.pos.x, self.pos.y, rng)
+        {
+            self.pos = Position { x: nx, y: ny };
+            self.energy = self.energy.saturating_sub(grid.terrain(nx, ny).energy_cost());
+            self.noise_triggered = true;
+            println!("FSM: Noise! Random move to ({}, {})", nx, ny);
+            return true;
+        }
+        false
+    }
+
+    fn move_randomly(&mut self, grid: &Grid, rng: &mut SimRng) {
+        // Collect all valid neighbors (respecting the grid's connectivity).
+        let candidates: Vec<Position> = grid
+            .walkable_neighbors(self.pos.x, self.pos.y)
+            .into_iter()
+            .map(|(x, y)| Position { x, y })
+            .collect();
+
+        if candidates.is_empty() {
+            return;
+        }
+
+        // Prefer unvisited cells if memory is active.
+        let unvisited: Vec<_> = candidates.iter().filter(|p| !self.memory.contains(p)).copied().collect();
+        let pool = if unvisited.is_empty() { &candidates } else { &unvisited };
+
+        if let Some(&next) = pool.choose(rng) {
+            self.pos = next;
+        }
+    }
+}
+
+// === Guards and hooks used by `default_machine` ===
+
+fn at_goal(agent: &FSMAgent, grid: &Grid) -> bool {
+    agent.pos == grid.goal
+}
+
+fn is_tired(agent: &FSMAgent, _grid: &Grid) -> bool {
+    agent.energy < agent.low_energy
+}
+
+fn is_rested(agent: &FSMAgent, _grid: &Grid) -> bool {
+    agent.energy >= agent.full_energy
+}
+
+fn announce_goal(agent: &mut FSMAgent, _grid: &Grid, _rng: &mut SimRng) {
+    println!(
+        "FSM: Reached goal at ({}, {}) -> state = FoundGoal",
+        agent.pos.x, agent.pos.y
+    );
+}
+
+fn announce_tired(agent: &mut FSMAgent, _grid: &Grid, _rng: &mut SimRng) {
+    println!(
+        "FSM: Energy low ({}). Transition Exploring -> Resting",
+        agent.energy
+    );
+}
+
+fn announce_rested(agent: &mut FSMAgent, _grid: &Grid, _rng: &mut SimRng) {
+    println!(
+        "FSM: Energy full ({}). Transition Resting -> Exploring",
+        agent.energy
+    );
+}
+
+/// Move to a random neighbor, paying the terrain's energy cost.
+fn explore(agent: &mut FSMAgent, grid: &Grid, rng: &mut SimRng) {
+    println!(
+        "FSM: Exploring at ({}, {}), energy = {}. Moving...",
+        agent.pos.x, agent.pos.y, agent.energy
+    );
+    agent.move_randomly(grid, rng);
+    // Exploring costs energy according to the terrain stepped onto.
+    let cost = grid.terrain(agent.pos.x, agent.pos.y).energy_cost();
+    agent.energy = agent.energy.saturating_sub(cost);
+}
+
+/// Recover energy in place.
+fn rest(agent: &mut FSMAgent, _grid: &Grid, _rng: &mut SimRng) {
+    let before = agent.energy;
+    agent.energy = (agent.energy + REST_RECOVERY).min(MAX_ENERGY);
+    println!(
+        "FSM: Resting at ({}, {}), energy {} -> {}",
+        agent.pos.x, agent.pos.y, before, agent.energy
+    );
+}
+
+impl super::LegacyAgent for FSMAgent {
+    fn update(&mut self, grid: &Grid, rng: &mut SimRng) {
+        self.update(grid, rng);
+    }
+
+    fn position(&self) -> Position {
+        self.pos
+    }
+
+    fn set_position(&mut self, pos: Position) {
+        self.pos = pos;
+    }
+
+    fn set_energy(&mut self, energy: u32) {
+        self.energy = energy;
+    }
+
+    fn name(&self) -> &'static str {
+        "FSM"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn energy(&self) -> Option<u32> {
+        Some(self.energy)
+    }
+
+    fn debug_state(&self) -> String {
+        format!("{:?}", self.state())
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::LegacyAgent;
+    use crate::engine::rng::seeded;
+    use crate::engine::terrain::Terrain;
+
+    #[test]
+    fn trajectory_matches_the_hand_written_fsm() {
+        use FSMState::{Exploring, FoundGoal, Resting};
+
+        // Water everywhere but the goal, so energy runs low quickly.
+        let mut grid = Grid::new(6, 4, Position { x: 5, y: 3 });
+        for y in 0..4 {
+            for x in 0..6 {
+                if (x, y) != (5, 3) {
+                    grid.set_terrain(x, y, Terrain::Water);
+                }
+            }
+        }
+        let mut rng = seeded(3);
+        let mut agent = FSMAgent::with_config(0, 0, 0.2, 5, 1.0);
+        let mut noise_moves = 0;
+        let mut trajectory = Vec::new();
+        while agent.state() != FoundGoal && trajectory.len() < 200 {
+            agent.update(&grid, &mut rng);
+            noise_moves += usize::from(agent.did_noise_trigger());
+            trajectory.push((agent.pos.x, agent.pos.y, agent.state(), agent.energy()));
+        }
+
+        // Recorded from the FSM before it moved onto `StateMachine`.
+        let expected = [
+            (1, 0, Exploring, 96),
+            (2, 0, Exploring, 92),
+            (2, 1, Exploring, 88),
+            (1, 1, Exploring, 84),
+            (2, 1, Exploring, 80),
+            (2, 2, Exploring, 76),
+            (3, 2, Exploring, 72),
+            (3, 1, Exploring, 68),
+            (3, 0, Exploring, 64),
+            (2, 0, Exploring, 60),
+            (1, 0, Exploring, 56),
+            (1, 1, Exploring, 52),
+            (1, 2, Exploring, 48),
+            (1, 3, Exploring, 44),
+            (2, 3, Exploring, 40),
+            (3, 3, Exploring, 36),
+            (2, 3, Exploring, 32),
+            (2, 2, Exploring, 28),
+            (2, 1, Exploring, 24),
+            (2, 0, Exploring, 20),
+            (3, 0, Exploring, 16),
+            (4, 0, Exploring, 12),
+            (5, 0, Exploring, 8),
+            (5, 0, Resting, 18),
+            (5, 0, Resting, 28),
+            (5, 0, Resting, 38),
+            (5, 0, Resting, 48),
+            (5, 0, Resting, 58),
+            (5, 0, Resting, 68),
+            (5, 0, Resting, 78),
+            (5, 0, Resting, 88),
+            (5, 0, Resting, 98),
+            (5, 0, Resting, 100),
+            (4, 0, Exploring, 96),
+            (4, 1, Exploring, 92),
+            (3, 1, Exploring, 88),
+            (4, 1, Exploring, 84),
+            (4, 2, Exploring, 80),
+            (5, 2, Exploring, 76),
+            (5, 3, Exploring, 75),
+            (5, 3, FoundGoal, 75),
+        ];
+        assert_eq!(trajectory, expected);
+        assert!(noise_moves > 0);
+    }
+}
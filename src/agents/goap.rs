@@ -0,0 +1,408 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::algorithms::astar::find_path;
+use crate::engine::action::{Action, Direction, Observation, MAX_ENERGY};
+use crate::engine::grid::Grid;
+use crate::engine::rng::SimRng;
+use crate::engine::world::Position;
+use super::belief::{BeliefMap, Sensor};
+use super::frontier::{known_grid, nearest_frontier};
+use super::Agent;
+
+/// A symbolic fact about the world, true or false in a `WorldState`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fact {
+    /// Energy is below the agent's `low_energy` threshold.
+    EnergyLow,
+    /// The goal cell has been seen.
+    GoalKnown,
+    /// The agent stands on the goal.
+    AtGoal,
+}
+
+impl Fact {
+    fn bit(self) -> u8 {
+        match self {
+            Fact::EnergyLow => 1,
+            Fact::GoalKnown => 2,
+            Fact::AtGoal => 4,
+        }
+    }
+}
+
+/// Which facts hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WorldState(u8);
+
+impl WorldState {
+    pub fn get(self, fact: Fact) -> bool {
+        self.0 & fact.bit() != 0
+    }
+
+    pub fn with(self, fact: Fact, value: bool) -> Self {
+        if value {
+            Self(self.0 | fact.bit())
+        } else {
+            Self(self.0 & !fact.bit())
+        }
+    }
+
+    /// Whether every `(fact, value)` pair holds.
+    pub fn satisfies(self, conditions: &[(Fact, bool)]) -> bool {
+        conditions.iter().all(|&(fact, value)| self.get(fact) == value)
+    }
+
+    /// Apply `effects`.
+    pub fn after(self, effects: &[(Fact, bool)]) -> Self {
+        effects.iter().fold(self, |state, &(fact, value)| state.with(fact, value))
+    }
+}
+
+/// The symbolic actions a GOAP agent plans with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Task {
+    /// Rest until energy is full.
+    Rest,
+    /// Travel to the nearest frontier until the goal is seen.
+    Explore,
+    /// Follow a path to the goal.
+    GoTo,
+}
+
+/// A task with its planning cost, preconditions and effects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskSpec {
+    pub task: Task,
+    pub cost: f32,
+    pub preconditions: Vec<(Fact, bool)>,
+    pub effects: Vec<(Fact, bool)>,
+}
+
+/// The default domain: moving needs energy, and the goal has to be seen
+/// before it can be walked to.
+pub fn default_tasks() -> Vec<TaskSpec> {
+    vec![
+        TaskSpec {
+            task: Task::Rest,
+            cost: 1.0,
+            preconditions: vec![(Fact::EnergyLow, true)],
+            effects: vec![(Fact::EnergyLow, false)],
+        },
+        TaskSpec {
+            task: Task::Explore,
+            cost: 5.0,
+            preconditions: vec![(Fact::EnergyLow, false), (Fact::GoalKnown, false)],
+            effects: vec![(Fact::GoalKnown, true)],
+        },
+        TaskSpec {
+            task: Task::GoTo,
+            cost: 2.0,
+            preconditions: vec![(Fact::EnergyLow, false), (Fact::GoalKnown, true)],
+            effects: vec![(Fact::AtGoal, true)],
+        },
+    ]
+}
+
+/// Cheapest sequence of `tasks` taking `start` to a state satisfying
+/// `goal`, found by A* over world states. The heuristic counts unmet goal
+/// facts times the cheapest task cost, which is admissible as long as no
+/// task sets more than one goal fact.
+pub fn plan(start: WorldState, goal: &[(Fact, bool)], tasks: &[TaskSpec]) -> Option<Vec<Task>> {
+    let cheapest = tasks.iter().map(|spec| spec.cost).fold(f32::INFINITY, f32::min);
+    let h = |state: WorldState| {
+        goal.iter().filter(|&&(fact, value)| state.get(fact) != value).count() as f32 * cheapest
+    };
+
+    // World states are few, so a plain list does as the open set.
+    let mut open = vec![(h(start), 0.0, start, Vec::new())];
+    let mut closed = Vec::new();
+    while !open.is_empty() {
+        let best = (0..open.len())
+            .min_by(|&a, &b| open[a].0.total_cmp(&open[b].0))
+            .unwrap_or(0);
+        let (_, g, state, steps) = open.swap_remove(best);
+        if state.satisfies(goal) {
+            return Some(steps);
+        }
+        if closed.contains(&state) {
+            continue;
+        }
+        closed.push(state);
+        for spec in tasks.iter().filter(|spec| state.satisfies(&spec.preconditions)) {
+            let next = state.after(&spec.effects);
+            if !closed.contains(&next) {
+                let mut steps = steps.clone();
+                steps.push(spec.task);
+                let g = g + spec.cost;
+                open.push((g + h(next), g, next, steps));
+            }
+        }
+    }
+    None
+}
+
+/// Goal-Oriented Action Planning agent.
+///
+/// Reads a `WorldState` from its observation and plans a sequence of
+/// `Task`s reaching `AtGoal` with `plan`, then carries the tasks out one
+/// by one, moving along paths found with `find_path`. A running task is
+/// abandoned, and the agent replans, when one of its preconditions stops
+/// holding (other than facts the task itself changes), e.g. when energy
+/// runs low on the way. With a sensor it explores frontiers until it has
+/// seen the goal; without one the goal is always known.
+pub struct GoapAgent {
+    pub tasks: Vec<TaskSpec>,
+    /// Energy below which `EnergyLow` holds.
+    pub low_energy: u32,
+    sensor: Option<Sensor>,
+    belief: Option<BeliefMap>,
+    plan: Vec<Task>,
+    /// Index of the running task in `plan`.
+    step: usize,
+    path: Vec<(usize, usize)>,
+    plans: u32,
+    /// Set when no plan reaches the goal.
+    stuck: bool,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+}
+
+impl GoapAgent {
+    pub fn new() -> Self {
+        Self {
+            tasks: default_tasks(),
+            low_energy: 10,
+            sensor: None,
+            belief: None,
+            plan: Vec::new(),
+            step: 0,
+            path: Vec::new(),
+            plans: 0,
+            stuck: false,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+        }
+    }
+
+    /// Create a GOAP agent with the shared noise/decay cognitive
+    /// parameters.
+    pub fn with_config(noise: f32, decay_rate: f32) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            ..Self::new()
+        }
+    }
+
+    /// Restrict perception to `sensor`.
+    pub fn with_sensor(self, sensor: Sensor) -> Self {
+        Self {
+            sensor: Some(sensor),
+            ..self
+        }
+    }
+
+    /// The current plan.
+    pub fn plan(&self) -> &[Task] {
+        &self.plan
+    }
+
+    /// The task being carried out.
+    pub fn current_task(&self) -> Option<Task> {
+        self.plan.get(self.step).copied()
+    }
+
+    /// Plans made after the first one.
+    pub fn replans(&self) -> u32 {
+        self.plans.saturating_sub(1)
+    }
+
+    fn world_state(&self, obs: &Observation, belief: &BeliefMap) -> WorldState {
+        WorldState::default()
+            .with(Fact::EnergyLow, obs.energy < self.low_energy)
+            .with(Fact::GoalKnown, belief.is_known(obs.goal.x, obs.goal.y))
+            .with(Fact::AtGoal, obs.position == obs.goal)
+    }
+
+    fn spec(&self, task: Task) -> Option<&TaskSpec> {
+        self.tasks.iter().find(|spec| spec.task == task)
+    }
+
+    /// Whether `task` has achieved what it is for.
+    fn finished(&self, task: Task, obs: &Observation, state: WorldState) -> bool {
+        match task {
+            // Resting only until `EnergyLow` clears would stop one tick later.
+            Task::Rest => obs.energy >= MAX_ENERGY,
+            _ => self.spec(task).is_some_and(|spec| state.satisfies(&spec.effects)),
+        }
+    }
+
+    /// Whether `task` may keep running: its preconditions hold, ignoring
+    /// the facts it changes itself.
+    fn still_applicable(&self, task: Task, state: WorldState) -> bool {
+        self.spec(task).is_some_and(|spec| {
+            spec.preconditions
+                .iter()
+                .filter(|&&(fact, _)| !spec.effects.iter().any(|&(changed, _)| changed == fact))
+                .all(|&(fact, value)| state.get(fact) == value)
+        })
+    }
+
+    /// Next step along a path to `target` on `grid`, reusing the current
+    /// path while it is still walkable.
+    fn step_towards(&mut self, grid: &Grid, pos: Position, target: Position) -> Option<Direction> {
+        let here = self.path.iter().position(|&cell| cell == (pos.x, pos.y));
+        let valid = here.is_some_and(|i| {
+            self.path.last() == Some(&(target.x, target.y))
+                && self.path[i + 1..].iter().all(|&(x, y)| grid.is_walkable(x, y))
+        });
+        if !valid {
+            self.path = find_path((pos.x, pos.y), (target.x, target.y), grid, None)?;
+        }
+        let i = self.path.iter().position(|&cell| cell == (pos.x, pos.y))?;
+        let &(x, y) = self.path.get(i + 1)?;
+        Direction::between(pos, Position { x, y })
+    }
+
+    /// One tick of `task`.
+    fn execute(&mut self, task: Task, obs: &Observation, belief: &BeliefMap) -> Option<Action> {
+        match task {
+            Task::Rest => Some(Action::Rest),
+            Task::Explore => {
+                let known = known_grid(belief);
+                let frontier = nearest_frontier(belief, &known, obs.position)?;
+                self.step_towards(&known, obs.position, frontier).map(Action::Move)
+            }
+            Task::GoTo => self.step_towards(belief.grid(), obs.position, obs.goal).map(Action::Move),
+        }
+    }
+}
+
+impl Default for GoapAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for GoapAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+
+        if obs.position == obs.goal {
+            return Action::Interact;
+        }
+
+        // Without a sensor, every cell is in view.
+        let grid = obs.grid;
+        let sensor = self.sensor.unwrap_or(Sensor::new(grid.width + grid.height, false));
+        let mut belief = self.belief.take().unwrap_or_else(|| BeliefMap::new(grid));
+        belief.observe(grid, obs.position, &sensor);
+        let state = self.world_state(obs, &belief);
+
+        let effective_noise = self.noise * self.exploration_rate;
+        if effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some(&(x, y)) = grid.walkable_neighbors(obs.position.x, obs.position.y).choose(rng)
+        {
+            self.belief = Some(belief);
+            self.noise_triggered = true;
+            return Direction::between(obs.position, Position { x, y }).map_or(Action::Wait, Action::Move);
+        }
+
+        // Move past finished tasks; replan if the next one can't run.
+        while let Some(task) = self.current_task()
+            && self.finished(task, obs, state)
+        {
+            self.step += 1;
+        }
+        let mut action = None;
+        if let Some(task) = self.current_task()
+            && self.still_applicable(task, state)
+        {
+            action = self.execute(task, obs, &belief);
+        }
+        if action.is_none() {
+            self.plans += 1;
+            self.plan = plan(state, &[(Fact::AtGoal, true)], &self.tasks).unwrap_or_default();
+            self.step = 0;
+            self.path.clear();
+            action = self.current_task().and_then(|task| self.execute(task, obs, &belief));
+        }
+        self.stuck = action.is_none();
+        self.belief = Some(belief);
+        action.unwrap_or(Action::Wait)
+    }
+
+    fn name(&self) -> &'static str {
+        "GOAP"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_stuck(&self) -> bool {
+        self.stuck
+    }
+
+    fn debug_state(&self) -> String {
+        if self.stuck {
+            return "Stuck (no plan)".to_string();
+        }
+        let plan: Vec<String> = self.plan[self.step.min(self.plan.len())..]
+            .iter()
+            .map(|task| format!("{:?}", task))
+            .collect();
+        format!("Plan: {}", plan.join(" → "))
+    }
+
+    fn replans(&self) -> Option<u32> {
+        Some(self.replans())
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+
+    fn planning_radius(&self) -> Option<f32> {
+        self.sensor.map(|sensor| sensor.radius as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn plans_rest_and_exploration_before_heading_for_the_goal() {
+        let tired = WorldState::default().with(Fact::EnergyLow, true);
+        let goal = [(Fact::AtGoal, true)];
+        assert_eq!(plan(tired, &goal, &default_tasks()), Some(vec![Task::Rest, Task::Explore, Task::GoTo]));
+        let seen = WorldState::default().with(Fact::GoalKnown, true);
+        assert_eq!(plan(seen, &goal, &default_tasks()), Some(vec![Task::GoTo]));
+        assert_eq!(plan(tired, &goal, &default_tasks()[1..]), None);
+
+        // The goal is hidden behind a wall until the agent explores round it.
+        let grid = Grid::load("tests/fixtures/hidden_pocket.map").unwrap();
+        let agent = GoapAgent::new().with_sensor(Sensor::new(2, true));
+        let mut world = World::with_rng(grid, Box::new(agent), 0, seeded(0));
+        world.body.energy = 5;
+        let mut rested = false;
+        while !world.has_reached_goal() && world.step < 200 {
+            world.update();
+            let agent = world.agent.as_any().downcast_ref::<GoapAgent>().unwrap();
+            rested |= agent.current_task() == Some(Task::Rest);
+        }
+        assert!(rested && world.has_reached_goal());
+        let agent = world.agent.as_any().downcast_ref::<GoapAgent>().unwrap();
+        assert_eq!(agent.plan(), [Task::Rest, Task::Explore, Task::GoTo]);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::action::{Action, Direction, Observation};
+use crate::engine::rng::SimRng;
+use crate::engine::world::{Grid, Position};
+use super::Agent;
+
+/// Learned cost-to-goal estimates for (cell, goal) pairs.
+///
+/// Cells that were never updated fall back to `Grid::distance`, which
+/// never overestimates, so learning only ever raises values. Tables are
+/// tied to the map they were learned on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeuristicTable {
+    values: HashMap<(Position, Position), f32>,
+}
+
+/// One CSV row of a saved table.
+#[derive(Serialize, Deserialize)]
+struct HeuristicRow {
+    x: usize,
+    y: usize,
+    goal_x: usize,
+    goal_y: usize,
+    h: f32,
+}
+
+impl HeuristicTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Estimated cost from `pos` to `goal`.
+    pub fn value(&self, grid: &Grid, pos: Position, goal: Position) -> f32 {
+        self.values
+            .get(&(pos, goal))
+            .copied()
+            .unwrap_or_else(|| grid.distance(pos, goal))
+    }
+
+    /// Number of cells with learned values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Save the table as CSV (one row per learned cell), creating/overwriting
+    /// `path`. Rows are sorted so equal tables produce equal files.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut cells: Vec<_> = self.values.iter().collect();
+        cells.sort_by_key(|&(&(pos, goal), _)| (goal.y, goal.x, pos.y, pos.x));
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for (&(pos, goal), &h) in cells {
+            wtr.serialize(HeuristicRow {
+                x: pos.x,
+                y: pos.y,
+                goal_x: goal.x,
+                goal_y: goal.y,
+                h,
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Load a table written by [`HeuristicTable::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let mut rdr = csv::Reader::from_path(&path)
+            .map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        let mut table = Self::new();
+        for row in rdr.deserialize() {
+            let row: HeuristicRow = row.map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+            table.values.insert(
+                (Position { x: row.x, y: row.y }, Position { x: row.goal_x, y: row.goal_y }),
+                row.h,
+            );
+        }
+        Ok(table)
+    }
+}
+
+/// Learning Real-Time A* (LRTA*).
+///
+/// Each tick it looks `lookahead` moves ahead, scoring every neighbor by
+/// the cheapest step cost plus learned heuristic at the edge of that
+/// search, moves to the best one and raises its current cell's heuristic
+/// to that score. Raising dead ends' estimates is what stops the
+/// oscillation a depth-limited planner falls into, and because the table
+/// outlives an episode, repeated runs on one map converge to an optimal
+/// route. Lookahead depth is its bounded-rationality knob.
+pub struct LrtaAgent {
+    table: HeuristicTable,
+    lookahead: usize,
+    /// If false the table is only read.
+    pub learning: bool,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+    /// Sum of all heuristic increases made so far.
+    learned: f32,
+}
+
+impl LrtaAgent {
+    pub fn new(lookahead: usize) -> Self {
+        Self {
+            table: HeuristicTable::new(),
+            lookahead: lookahead.max(1),
+            learning: true,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+            learned: 0.0,
+        }
+    }
+
+    /// Create an LRTA* agent with the shared noise/decay cognitive
+    /// parameters.
+    pub fn with_config(lookahead: usize, noise: f32, decay_rate: f32) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            ..Self::new(lookahead)
+        }
+    }
+
+    /// Start from an existing (e.g. loaded) table.
+    pub fn with_table(mut self, table: HeuristicTable) -> Self {
+        self.table = table;
+        self
+    }
+
+    pub fn table(&self) -> &HeuristicTable {
+        &self.table
+    }
+
+    pub fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    /// Cheapest estimated cost to the goal from `pos`, searching `depth`
+    /// moves ahead before falling back on the table.
+    fn estimate(&self, grid: &Grid, pos: Position, goal: Position, depth: usize) -> f32 {
+        if pos == goal {
+            return 0.0;
+        }
+        if depth == 0 {
+            return self.table.value(grid, pos, goal);
+        }
+        grid.walkable_neighbors(pos.x, pos.y)
+            .into_iter()
+            .map(|(x, y)| {
+                let next = Position { x, y };
+                grid.step_cost((pos.x, pos.y), (x, y)).unwrap_or(f32::INFINITY)
+                    + self.estimate(grid, next, goal, depth - 1)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+}
+
+impl Agent for LrtaAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+        let (grid, pos, goal) = (obs.grid, obs.position, obs.goal);
+
+        if pos == goal {
+            return Action::Interact;
+        }
+
+        let neighbors: Vec<Position> = grid
+            .walkable_neighbors(pos.x, pos.y)
+            .into_iter()
+            .map(|(x, y)| Position { x, y })
+            .collect();
+        let scores: Vec<f32> = neighbors
+            .iter()
+            .map(|&n| {
+                grid.step_cost((pos.x, pos.y), (n.x, n.y)).unwrap_or(f32::INFINITY)
+                    + self.estimate(grid, n, goal, self.lookahead - 1)
+            })
+            .collect();
+        let best = scores.iter().copied().fold(f32::INFINITY, f32::min);
+
+        // Learning step: this cell is at least as far as its best neighbor.
+        if self.learning && best.is_finite() {
+            let h = self.table.value(grid, pos, goal);
+            if best > h {
+                self.table.values.insert((pos, goal), best);
+                self.learned += best - h;
+            }
+        }
+
+        let effective_noise = self.noise * self.exploration_rate;
+        let next = if effective_noise > 0.0 && rng.r#gen::<f32>() < effective_noise {
+            self.noise_triggered = true;
+            neighbors.choose(rng).copied()
+        } else {
+            let ties: Vec<Position> = neighbors
+                .iter()
+                .zip(&scores)
+                .filter(|&(_, &score)| score <= best)
+                .map(|(&n, _)| n)
+                .collect();
+            ties.choose(rng).copied()
+        };
+        next.and_then(|to| Direction::between(pos, to))
+            .map_or(Action::Wait, Action::Move)
+    }
+
+    fn name(&self) -> &'static str {
+        "LRTA*"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn debug_state(&self) -> String {
+        format!("lookahead {}, {} cells learned", self.lookahead, self.table.len())
+    }
+
+    fn learning_progress(&self) -> Option<f32> {
+        Some(self.learned)
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+
+    fn planning_radius(&self) -> Option<f32> {
+        Some(self.lookahead as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn repeated_trials_converge_and_the_table_round_trips() {
+        let cup = Grid::load("maps/u_trap.map").unwrap();
+        let optimal = cup.optimal_path_len().unwrap();
+        let run = |agent: LrtaAgent, seed: u64| {
+            let mut world = World::with_rng(cup.clone(), Box::new(agent), seed, seeded(seed));
+            world.run_until_done(500);
+            let agent = world.agent.as_any().downcast_ref::<LrtaAgent>().unwrap();
+            (world.step, agent.learned, agent.table().clone())
+        };
+
+        let mut table = HeuristicTable::new();
+        let mut steps = Vec::new();
+        for trial in 0..30 {
+            let (taken, _, learned) = run(LrtaAgent::new(1).with_table(table), trial);
+            steps.push(taken);
+            table = learned;
+        }
+        assert!(steps[0] > optimal);
+        assert_eq!(*steps.last().unwrap(), optimal);
+
+        let path = std::env::temp_dir().join(format!("lrta_test_{}.csv", std::process::id()));
+        table.save(&path).unwrap();
+        let loaded = HeuristicTable::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded, table);
+
+        // A converged table has nothing left to learn.
+        let (taken, learned, _) = run(LrtaAgent::new(1).with_table(loaded), 99);
+        assert_eq!((taken, learned), (optimal, 0.0));
+    }
+}
@@ -0,0 +1,273 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::engine::action::{Action, Direction, Observation};
+use crate::engine::grid::Grid;
+use crate::engine::rng::SimRng;
+use crate::engine::world::Position;
+use super::Agent;
+
+/// Search iterations per move when no budget is given.
+pub const DEFAULT_ITERATIONS: usize = 200;
+
+/// One node of the search tree: the agent standing on `pos` after the
+/// moves leading here from the root.
+struct Node {
+    pos: Position,
+    parent: Option<usize>,
+    /// Move from the parent that led here.
+    dir: Option<Direction>,
+    children: Vec<usize>,
+    untried: Vec<Direction>,
+    visits: u32,
+    total: f32,
+    /// Terrain cost of the moves from the root.
+    cost: f32,
+    depth: usize,
+}
+
+impl Node {
+    fn mean(&self) -> f32 {
+        if self.visits == 0 { 0.0 } else { self.total / self.visits as f32 }
+    }
+}
+
+/// Monte Carlo Tree Search planner.
+///
+/// Every tick it grows a fresh UCT tree from its position for
+/// `iterations` iterations, using the grid as a forward model: select
+/// down the tree, expand one untried move, play a random rollout of up to
+/// `rollout_depth` moves and back up its score. A rollout scores
+/// `1 / (1 + cost + h)`, where `cost` is the terrain cost spent and `h`
+/// the remaining `Grid::distance` to the goal, so cheap routes that end
+/// near (or on) the goal score highest. The agent then takes the most
+/// visited first move. The iteration budget is its bounded-rationality
+/// knob, like `AStarAgent`'s planning limit.
+pub struct MctsAgent {
+    iterations: usize,
+    /// Maximum moves in one random rollout.
+    pub rollout_depth: usize,
+    /// UCT exploration constant.
+    pub exploration: f32,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+    /// Depth of the deepest node in the last search tree.
+    horizon: usize,
+    /// Iterations run this episode.
+    searched: u64,
+}
+
+impl MctsAgent {
+    pub fn new() -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+            rollout_depth: 30,
+            exploration: std::f32::consts::SQRT_2,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+            horizon: 0,
+            searched: 0,
+        }
+    }
+
+    /// Create an MCTS agent with `iterations` per move and the shared
+    /// noise/decay cognitive parameters.
+    pub fn with_config(iterations: usize, noise: f32, decay_rate: f32) -> Self {
+        Self {
+            iterations: iterations.max(1),
+            noise,
+            decay_rate,
+            ..Self::new()
+        }
+    }
+
+    /// Search iterations per move.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Run one search from `obs.position` and return the chosen move.
+    fn search(&mut self, obs: &Observation, rng: &mut SimRng) -> Option<Direction> {
+        let grid = obs.grid;
+        let mut nodes = vec![Node {
+            pos: obs.position,
+            parent: None,
+            dir: None,
+            children: Vec::new(),
+            untried: moves(grid, obs.position),
+            visits: 0,
+            total: 0.0,
+            cost: 0.0,
+            depth: 0,
+        }];
+
+        for _ in 0..self.iterations {
+            // Selection: descend through fully expanded nodes.
+            let mut current = 0;
+            while nodes[current].untried.is_empty()
+                && !nodes[current].children.is_empty()
+                && nodes[current].pos != obs.goal
+            {
+                current = self.select(&nodes, current);
+            }
+
+            // Expansion: add one untried move, unless this is the goal.
+            if nodes[current].pos != obs.goal && !nodes[current].untried.is_empty() {
+                let pick = rng.gen_range(0..nodes[current].untried.len());
+                let dir = nodes[current].untried.swap_remove(pick);
+                let from = nodes[current].pos;
+                if let Some(to) = dir.apply(from) {
+                    let step = grid.step_cost((from.x, from.y), (to.x, to.y)).unwrap_or(0.0);
+                    let child = Node {
+                        pos: to,
+                        parent: Some(current),
+                        dir: Some(dir),
+                        children: Vec::new(),
+                        untried: moves(grid, to),
+                        visits: 0,
+                        total: 0.0,
+                        cost: nodes[current].cost + step,
+                        depth: nodes[current].depth + 1,
+                    };
+                    nodes.push(child);
+                    let id = nodes.len() - 1;
+                    nodes[current].children.push(id);
+                    current = id;
+                }
+            }
+
+            // Simulation and backpropagation.
+            let score = self.rollout(grid, nodes[current].pos, nodes[current].cost, obs.goal, rng);
+            let mut node = Some(current);
+            while let Some(id) = node {
+                nodes[id].visits += 1;
+                nodes[id].total += score;
+                node = nodes[id].parent;
+            }
+        }
+
+        self.searched += self.iterations as u64;
+        self.horizon = nodes.iter().map(|n| n.depth).max().unwrap_or(0);
+        nodes[0]
+            .children
+            .iter()
+            .map(|&id| &nodes[id])
+            .max_by(|a, b| a.visits.cmp(&b.visits).then(a.mean().total_cmp(&b.mean())))
+            .and_then(|node| node.dir)
+    }
+
+    /// Child of `parent` with the highest UCT value.
+    fn select(&self, nodes: &[Node], parent: usize) -> usize {
+        let ln_n = (nodes[parent].visits.max(1) as f32).ln();
+        let uct = |id: usize| {
+            let node = &nodes[id];
+            node.mean() + self.exploration * (ln_n / node.visits.max(1) as f32).sqrt()
+        };
+        nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .unwrap_or(parent)
+    }
+
+    /// Random walk from `pos` that stops on the goal; returns its score.
+    fn rollout(&self, grid: &Grid, mut pos: Position, mut cost: f32, goal: Position, rng: &mut SimRng) -> f32 {
+        for _ in 0..self.rollout_depth {
+            if pos == goal {
+                break;
+            }
+            let Some((x, y)) = grid.random_walkable_neighbor(pos.x, pos.y, rng) else {
+                break;
+            };
+            cost += grid.step_cost((pos.x, pos.y), (x, y)).unwrap_or(0.0);
+            pos = Position { x, y };
+        }
+        1.0 / (1.0 + cost + grid.distance(pos, goal))
+    }
+}
+
+impl Default for MctsAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Legal moves from `pos` under the grid's movement rules.
+fn moves(grid: &Grid, pos: Position) -> Vec<Direction> {
+    grid.walkable_neighbors(pos.x, pos.y)
+        .into_iter()
+        .filter_map(|(x, y)| Direction::between(pos, Position { x, y }))
+        .collect()
+}
+
+impl Agent for MctsAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+
+        if obs.position == obs.goal {
+            return Action::Interact;
+        }
+
+        // Decision noise: occasionally take a random legal move instead.
+        if self.noise > 0.0
+            && rng.r#gen::<f32>() < self.noise * self.exploration_rate
+            && let Some(&dir) = moves(obs.grid, obs.position).choose(rng)
+        {
+            self.noise_triggered = true;
+            return Action::Move(dir);
+        }
+
+        match self.search(obs, rng) {
+            Some(dir) => Action::Move(dir),
+            None => Action::Wait,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "MCTS"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn debug_state(&self) -> String {
+        format!("{} iterations/move, horizon {}, {} run", self.iterations, self.horizon, self.searched)
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+
+    /// How far ahead the last search's tree reached within the budget.
+    fn planning_radius(&self) -> Option<f32> {
+        (self.horizon > 0).then_some(self.horizon as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn finds_the_gap_in_a_wall() {
+        // Wall down column 4 with a gap at the bottom.
+        let walls: Vec<(usize, usize)> = (0..4).map(|y| (4, y)).collect();
+        let grid = Grid::with_obstacles(9, 5, Position { x: 8, y: 0 }, &walls);
+        let optimal = grid.optimal_path_len().unwrap();
+
+        let mut world = World::with_rng(grid, Box::new(MctsAgent::new()), 3, seeded(3));
+        assert!(world.run_until_done(100));
+        assert!(world.step <= optimal + 4, "took {} steps, optimal {}", world.step, optimal);
+        let agent = world.agent.as_any().downcast_ref::<MctsAgent>().unwrap();
+        assert!(agent.planning_radius().is_some());
+    }
+}
@@ -41,6 +41,11 @@ impl SpatialMemory {
         self.entries.contains(pos)
     }
 
+    /// Remembered positions, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Position> {
+        self.entries.iter()
+    }
+
     /// Number of entries currently stored.
     pub fn len(&self) -> usize {
         self.entries.len()
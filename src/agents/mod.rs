@@ -1,21 +1,76 @@
+use crate::engine::action::{Action, Observation, Outcome};
+use crate::engine::rng::SimRng;
 use crate::engine::world::{Grid, Position};
 
+/// An agent decides; the world acts.
+///
+/// Each tick the world hands the agent an `Observation`, the agent returns
+/// an `Action`, and the world validates it, moves the agent's `Body` and
+/// charges energy. Agents never mutate their own position.
 pub trait Agent {
-    fn update(&mut self, grid: &Grid);
-    fn position(&self) -> Position;
+    /// Choose this tick's action. All randomness must be drawn from `rng`
+    /// so that episodes are reproducible from their seed.
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action;
+    /// Told what the world did with the last action.
+    fn on_outcome(&mut self, _outcome: &Outcome) {}
     fn name(&self) -> &'static str;
     fn as_any(&self) -> &dyn std::any::Any; // Helpful for downcasting if needed
     fn is_stuck(&self) -> bool { false }
-    fn energy(&self) -> Option<u32> { None }
     fn debug_state(&self) -> String { String::new() }
-    
+    /// Times a planning agent has re-planned; `None` for reactive agents.
+    fn replans(&self) -> Option<u32> { None }
+    /// Running total of how much a learning agent has changed what it
+    /// knows (e.g. heuristic increases); `None` for agents that don't learn.
+    fn learning_progress(&self) -> Option<f32> { None }
+    /// Nodes a tree-based agent visited while making its last decision,
+    /// in visit order; empty for other agents.
+    fn decision_trace(&self) -> Vec<behavior_tree::TraceStep> { Vec::new() }
+
     // Visual feedback hooks
     fn did_noise_trigger(&self) -> bool { false }
     fn planning_radius(&self) -> Option<f32> { None }
 }
 
+/// The original agent interface, where an agent moves itself and keeps
+/// its own energy. Wrap one in `adapter::LegacyAdapter` to run it in a
+/// `World`/`MultiWorld`.
+pub trait LegacyAgent {
+    /// Advance one tick. All randomness must be drawn from `rng`
+    /// so that episodes are reproducible from their seed.
+    fn update(&mut self, grid: &Grid, rng: &mut SimRng);
+    fn position(&self) -> Position;
+    /// Move the agent to where the world says it is (e.g. after a
+    /// rejected move).
+    fn set_position(&mut self, pos: Position);
+    /// Set the agent's energy to what the world's `Body` holds. Agents
+    /// that don't track energy ignore it.
+    fn set_energy(&mut self, energy: u32);
+    fn name(&self) -> &'static str;
+    fn as_any(&self) -> &dyn std::any::Any;
+    fn is_stuck(&self) -> bool { false }
+    fn energy(&self) -> Option<u32> { None }
+    fn debug_state(&self) -> String { String::new() }
+    fn replans(&self) -> Option<u32> { None }
+    fn decision_trace(&self) -> Vec<behavior_tree::TraceStep> { Vec::new() }
+    fn did_noise_trigger(&self) -> bool { false }
+    fn planning_radius(&self) -> Option<f32> { None }
+}
+
+pub mod adapter;
 pub mod fsm;
 pub mod astar;
+pub mod belief;
+pub mod bt_format;
+pub mod bug;
+pub mod dstar_lite;
+pub mod frontier;
+pub mod goap;
+pub mod lrta;
 pub mod behavior_tree;
+pub mod blackboard;
 pub mod memory;
-
+pub mod mcts;
+pub mod potential_field;
+pub mod qlearning;
+pub mod utility;
+pub mod wall_follower;
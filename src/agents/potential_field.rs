@@ -0,0 +1,211 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::engine::action::{Action, Direction, Observation};
+use crate::engine::rng::SimRng;
+use crate::engine::world::{Grid, Position};
+use super::memory::SpatialMemory;
+use super::Agent;
+
+/// Strengths and reach of the forces making up a potential field.
+///
+/// Repulsion from a source falls off linearly with Euclidean distance,
+/// from its full weight on the source cell to nothing at its range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldWeights {
+    /// Potential per unit of `Grid::distance` to the goal.
+    pub goal: f32,
+    /// Peak repulsion of a wall cell.
+    pub wall: f32,
+    /// Distance (in cells) at which wall repulsion reaches zero.
+    pub wall_range: f32,
+    /// Peak repulsion of a remembered cell.
+    pub memory: f32,
+    /// Distance (in cells) at which memory repulsion reaches zero.
+    pub memory_range: f32,
+}
+
+impl Default for FieldWeights {
+    fn default() -> Self {
+        Self {
+            goal: 1.0,
+            wall: 1.0,
+            wall_range: 1.5,
+            memory: 3.0,
+            memory_range: 1.5,
+        }
+    }
+}
+
+/// Repulsion at distance `d` from a source of `weight` reaching `range`.
+fn falloff(weight: f32, range: f32, d: f32) -> f32 {
+    if d >= range {
+        0.0
+    } else {
+        weight * (1.0 - d / range)
+    }
+}
+
+fn euclidean(a: Position, b: Position) -> f32 {
+    let dx = a.x.abs_diff(b.x) as f32;
+    let dy = a.y.abs_diff(b.y) as f32;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Gradient-descent agent on an artificial potential field.
+///
+/// The goal attracts, while walls and the cells in its `SpatialMemory`
+/// repel (see `FieldWeights`). Each tick it steps to the neighbor with
+/// the lowest potential, even if that is uphill: because the cells it has
+/// just left repel it, memory pushes it out of the local minima a plain
+/// field gets stuck in. With memory disabled it is pure gradient descent.
+pub struct PotentialFieldAgent {
+    pub weights: FieldWeights,
+    memory: SpatialMemory,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+}
+
+impl PotentialFieldAgent {
+    pub fn new() -> Self {
+        Self {
+            weights: FieldWeights::default(),
+            memory: SpatialMemory::new(0),
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+        }
+    }
+
+    /// Create a potential-field agent with full cognitive config.
+    pub fn with_config(noise: f32, memory_capacity: usize, decay_rate: f32) -> Self {
+        Self {
+            memory: SpatialMemory::new(memory_capacity),
+            noise,
+            decay_rate,
+            ..Self::new()
+        }
+    }
+
+    /// Use `weights` instead of the defaults.
+    pub fn with_weights(self, weights: FieldWeights) -> Self {
+        Self { weights, ..self }
+    }
+
+    /// Potential of `pos` when heading for `goal` (lower is better).
+    pub fn potential(&self, grid: &Grid, goal: Position, pos: Position) -> f32 {
+        let w = &self.weights;
+        let mut u = w.goal * grid.distance(pos, goal);
+
+        let reach = w.wall_range.ceil() as usize;
+        for y in pos.y.saturating_sub(reach)..=(pos.y + reach).min(grid.height - 1) {
+            for x in pos.x.saturating_sub(reach)..=(pos.x + reach).min(grid.width - 1) {
+                if !grid.is_walkable(x, y) {
+                    u += falloff(w.wall, w.wall_range, euclidean(pos, Position { x, y }));
+                }
+            }
+        }
+        for &cell in self.memory.iter() {
+            u += falloff(w.memory, w.memory_range, euclidean(pos, cell));
+        }
+        u
+    }
+
+    /// The potential of every cell, indexed `[y][x]`; `None` for cells
+    /// that can't be entered. Used by the viewer's field overlay.
+    pub fn field(&self, grid: &Grid, goal: Position) -> Vec<Vec<Option<f32>>> {
+        (0..grid.height)
+            .map(|y| {
+                (0..grid.width)
+                    .map(|x| grid.is_walkable(x, y).then(|| self.potential(grid, goal, Position { x, y })))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Default for PotentialFieldAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for PotentialFieldAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+        self.memory.record(obs.position);
+
+        if obs.position == obs.goal {
+            return Action::Interact;
+        }
+
+        let neighbors: Vec<Position> = obs
+            .grid
+            .walkable_neighbors(obs.position.x, obs.position.y)
+            .into_iter()
+            .map(|(x, y)| Position { x, y })
+            .collect();
+
+        let effective_noise = self.noise * self.exploration_rate;
+        let next = if effective_noise > 0.0 && rng.r#gen::<f32>() < effective_noise {
+            self.noise_triggered = true;
+            neighbors.choose(rng).copied()
+        } else {
+            // Steepest descent, breaking ties at random.
+            let potentials: Vec<f32> = neighbors
+                .iter()
+                .map(|&n| self.potential(obs.grid, obs.goal, n))
+                .collect();
+            let lowest = potentials.iter().copied().fold(f32::INFINITY, f32::min);
+            let best: Vec<Position> = neighbors
+                .iter()
+                .zip(&potentials)
+                .filter(|&(_, &u)| u <= lowest)
+                .map(|(&n, _)| n)
+                .collect();
+            best.choose(rng).copied()
+        };
+
+        next.and_then(|to| Direction::between(obs.position, to))
+            .map_or(Action::Wait, Action::Move)
+    }
+
+    fn name(&self) -> &'static str {
+        "PotentialField"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn debug_state(&self) -> String {
+        format!("Memory: {}/{}", self.memory.len(), self.memory.capacity())
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn memory_repulsion_escapes_a_local_minimum() {
+        // A cup open towards the start, directly between it and the goal.
+        let grid = Grid::load("tests/fixtures/cup.map").unwrap();
+        let run = |memory_capacity| {
+            let agent = PotentialFieldAgent::with_config(0.0, memory_capacity, 1.0);
+            World::with_rng(grid.clone(), Box::new(agent), 0, seeded(0)).run_until_done(200)
+        };
+        assert!(!run(0));
+        assert!(run(30));
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::action::{Action, Direction, Observation, Outcome};
+use crate::engine::grid::Connectivity;
+use crate::engine::rng::SimRng;
+use crate::engine::world::Position;
+use super::Agent;
+
+/// Reward for stepping onto the goal the agent was heading for.
+pub const GOAL_REWARD: f32 = 100.0;
+/// Penalty for a move the world refused (wall, edge or another agent).
+pub const BUMP_PENALTY: f32 = 5.0;
+/// Penalty for a tick spent not moving.
+pub const IDLE_PENALTY: f32 = 1.0;
+
+/// Learned action values for (cell, goal) states.
+///
+/// Each state holds one value per `Direction::ALL` entry; unseen states
+/// read as all zeros. Keying on the goal as well as the cell lets one
+/// table cover several goals (see `GoalMode::All`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QTable {
+    values: HashMap<(Position, Position), [f32; 8]>,
+}
+
+/// One CSV row of a saved table.
+#[derive(Serialize, Deserialize)]
+struct QRow {
+    x: usize,
+    y: usize,
+    goal_x: usize,
+    goal_y: usize,
+    n: f32,
+    e: f32,
+    s: f32,
+    w: f32,
+    ne: f32,
+    se: f32,
+    sw: f32,
+    nw: f32,
+}
+
+impl QTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Value of moving `dir` from `pos` while heading for `goal`.
+    pub fn value(&self, pos: Position, goal: Position, dir: Direction) -> f32 {
+        self.values.get(&(pos, goal)).map_or(0.0, |values| values[index(dir)])
+    }
+
+    fn value_mut(&mut self, pos: Position, goal: Position, dir: Direction) -> &mut f32 {
+        &mut self.values.entry((pos, goal)).or_insert([0.0; 8])[index(dir)]
+    }
+
+    /// Highest value among `dirs` from `pos`.
+    pub fn best_value(&self, pos: Position, goal: Position, dirs: &[Direction]) -> f32 {
+        dirs.iter()
+            .map(|&dir| self.value(pos, goal, dir))
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// Number of states with learned values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Save the table as CSV (one row per state), creating/overwriting
+    /// `path`. Rows are sorted so equal tables produce equal files.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut states: Vec<_> = self.values.iter().collect();
+        states.sort_by_key(|&(&(pos, goal), _)| (goal.y, goal.x, pos.y, pos.x));
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for (&(pos, goal), v) in states {
+            wtr.serialize(QRow {
+                x: pos.x,
+                y: pos.y,
+                goal_x: goal.x,
+                goal_y: goal.y,
+                n: v[0],
+                e: v[1],
+                s: v[2],
+                w: v[3],
+                ne: v[4],
+                se: v[5],
+                sw: v[6],
+                nw: v[7],
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Load a table written by [`QTable::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let mut rdr = csv::Reader::from_path(&path)
+            .map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        let mut table = Self::new();
+        for row in rdr.deserialize() {
+            let row: QRow = row.map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+            table.values.insert(
+                (Position { x: row.x, y: row.y }, Position { x: row.goal_x, y: row.goal_y }),
+                [row.n, row.e, row.s, row.w, row.ne, row.se, row.sw, row.nw],
+            );
+        }
+        Ok(table)
+    }
+}
+
+/// Position of `dir` in `Direction::ALL`.
+fn index(dir: Direction) -> usize {
+    Direction::ALL.iter().position(|&d| d == dir).unwrap_or(0)
+}
+
+/// Moves available under `connectivity`.
+fn directions(connectivity: Connectivity) -> &'static [Direction] {
+    match connectivity {
+        Connectivity::Four => &Direction::CARDINAL,
+        Connectivity::Eight(_) => &Direction::ALL,
+    }
+}
+
+/// The move chosen last tick, awaiting its outcome.
+struct Pending {
+    pos: Position,
+    goal: Position,
+    dir: Direction,
+    /// Terrain cost of the move if it succeeds.
+    cost: f32,
+    dirs: &'static [Direction],
+}
+
+/// Tabular Q-learning agent.
+///
+/// Picks moves epsilon-greedily from its `QTable`, with epsilon equal to
+/// `noise` times an exploration rate that decays by `decay_rate` every
+/// tick. After each move it updates the table from the reward: minus the
+/// terrain cost of a successful step, `GOAL_REWARD` on reaching the goal,
+/// `BUMP_PENALTY` for a refused move. The table outlives an episode, so
+/// keeping one agent across a batch trains it.
+pub struct QLearningAgent {
+    table: QTable,
+    /// Learning rate.
+    pub alpha: f32,
+    /// Discount factor for future rewards.
+    pub gamma: f32,
+    /// If false the table is only read, e.g. to evaluate a trained agent.
+    pub learning: bool,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+    pending: Option<Pending>,
+}
+
+impl QLearningAgent {
+    pub fn new() -> Self {
+        Self {
+            table: QTable::new(),
+            alpha: 0.5,
+            gamma: 0.95,
+            learning: true,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+            pending: None,
+        }
+    }
+
+    /// Create a Q-learning agent whose exploration is driven by the
+    /// shared cognitive parameters.
+    pub fn with_config(noise: f32, decay_rate: f32) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            ..Self::new()
+        }
+    }
+
+    /// Start from an existing (e.g. loaded) table.
+    pub fn with_table(mut self, table: QTable) -> Self {
+        self.table = table;
+        self
+    }
+
+    pub fn table(&self) -> &QTable {
+        &self.table
+    }
+
+    /// Current epsilon: the chance of a random move this tick.
+    pub fn epsilon(&self) -> f32 {
+        self.noise * self.exploration_rate
+    }
+}
+
+impl Default for QLearningAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for QLearningAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.pending = None;
+        self.exploration_rate *= self.decay_rate;
+
+        if obs.position == obs.goal {
+            return Action::Interact;
+        }
+
+        let dirs = directions(obs.grid.connectivity);
+        let dir = if self.epsilon() > 0.0 && rng.r#gen::<f32>() < self.epsilon() {
+            self.noise_triggered = true;
+            *dirs.choose(rng).unwrap_or(&Direction::North)
+        } else {
+            // Greedy, breaking ties at random so untried moves get tried.
+            let best = self.table.best_value(obs.position, obs.goal, dirs);
+            let ties: Vec<Direction> = dirs
+                .iter()
+                .copied()
+                .filter(|&d| self.table.value(obs.position, obs.goal, d) >= best)
+                .collect();
+            *ties.choose(rng).unwrap_or(&Direction::North)
+        };
+
+        let cost = dir
+            .apply(obs.position)
+            .and_then(|to| obs.grid.step_cost((obs.position.x, obs.position.y), (to.x, to.y)))
+            .unwrap_or(0.0);
+        self.pending = Some(Pending {
+            pos: obs.position,
+            goal: obs.goal,
+            dir,
+            cost,
+            dirs,
+        });
+        Action::Move(dir)
+    }
+
+    fn on_outcome(&mut self, outcome: &Outcome) {
+        let Some(p) = self.pending.take() else {
+            return;
+        };
+        if !self.learning {
+            return;
+        }
+
+        let (reward, next, terminal) = match *outcome {
+            Outcome::Moved { to, .. } if to == p.goal => (GOAL_REWARD - p.cost, to, true),
+            Outcome::Moved { to, .. } => (-p.cost, to, false),
+            Outcome::Blocked | Outcome::Collided => (-BUMP_PENALTY, p.pos, false),
+            _ => (-IDLE_PENALTY, p.pos, false),
+        };
+        let future = if terminal {
+            0.0
+        } else {
+            self.gamma * self.table.best_value(next, p.goal, p.dirs)
+        };
+        let q = self.table.value_mut(p.pos, p.goal, p.dir);
+        *q += self.alpha * (reward + future - *q);
+    }
+
+    fn name(&self) -> &'static str {
+        "QLearning"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn debug_state(&self) -> String {
+        format!("epsilon {:.3}, {} states learned", self.epsilon(), self.table.len())
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::{Grid, World};
+
+    #[test]
+    fn learns_a_corridor_and_round_trips_to_disk() {
+        let grid = Grid::new(6, 2, Position { x: 5, y: 0 });
+        let run = |agent: QLearningAgent, seed: u64| {
+            let mut world = World::with_rng(grid.clone(), Box::new(agent), seed, seeded(seed));
+            world.run_until_done(200);
+            let agent = world.agent.as_any().downcast_ref::<QLearningAgent>().unwrap();
+            (world.step, agent.table().clone())
+        };
+
+        let mut table = QTable::new();
+        let mut first = None;
+        for episode in 0..200 {
+            let (steps, learned) = run(QLearningAgent::with_config(0.2, 1.0).with_table(table), episode);
+            first.get_or_insert(steps);
+            table = learned;
+        }
+        assert!(first.unwrap() > 5);
+
+        let path = std::env::temp_dir().join(format!("qtable_test_{}.csv", std::process::id()));
+        table.save(&path).unwrap();
+        let loaded = QTable::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded, table);
+
+        // Greedy evaluation of the loaded table takes the shortest route.
+        let mut greedy = QLearningAgent::new().with_table(loaded);
+        greedy.learning = false;
+        assert_eq!(run(greedy, 99).0, 5);
+    }
+}
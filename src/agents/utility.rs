@@ -0,0 +1,372 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::engine::action::{Action, Direction, Observation, MAX_ENERGY};
+use crate::engine::rng::SimRng;
+use crate::engine::world::Position;
+use super::memory::SpatialMemory;
+use super::Agent;
+
+/// Memory hits at which `Input::MemoryHits` saturates at 1.
+const SATURATING_HITS: f32 = 3.0;
+
+/// A response curve mapping a normalized input to a score in [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    /// `slope * x + intercept`.
+    Linear { slope: f32, intercept: f32 },
+    /// `x^exponent`: above 1 rises slowly then sharply, below 1 the reverse.
+    Power { exponent: f32 },
+    /// S-curve centred on `midpoint`; a negative `steepness` falls instead
+    /// of rising.
+    Logistic { steepness: f32, midpoint: f32 },
+    /// 1 at or above `threshold`, 0 below.
+    Step { threshold: f32 },
+    /// Ignores the input.
+    Constant(f32),
+}
+
+impl Curve {
+    /// Score of input `x`, clamped to [0, 1].
+    pub fn evaluate(self, x: f32) -> f32 {
+        let y = match self {
+            Curve::Linear { slope, intercept } => slope * x + intercept,
+            Curve::Power { exponent } => x.max(0.0).powf(exponent),
+            Curve::Logistic { steepness, midpoint } => 1.0 / (1.0 + (-steepness * (x - midpoint)).exp()),
+            Curve::Step { threshold } => {
+                if x >= threshold { 1.0 } else { 0.0 }
+            }
+            Curve::Constant(y) => y,
+        };
+        y.clamp(0.0, 1.0)
+    }
+}
+
+/// What a consideration looks at, normalized to [0, 1]. Cell-based inputs
+/// are read at the cell the candidate action would end up in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// Energy as a fraction of `MAX_ENERGY`.
+    Energy,
+    /// `Grid::distance` to the goal over the grid's width plus height.
+    GoalDistance,
+    /// Times the cell is in spatial memory, saturating at 3.
+    MemoryHits,
+}
+
+/// One input passed through a response curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Consideration {
+    pub input: Input,
+    pub curve: Curve,
+}
+
+/// The candidate actions a utility agent chooses between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behavior {
+    /// Step to the neighbor closest to the goal.
+    MoveTowardGoal,
+    /// Step to the closest-to-goal neighbor not in memory; unavailable
+    /// when every neighbor is remembered.
+    ExploreUnvisited,
+    /// Recover energy.
+    Rest,
+    /// Step to the neighbor with the fewest memory hits.
+    AvoidRecent,
+}
+
+/// How the winning behavior is picked from the utilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// Take the highest utility; noise makes random moves.
+    Greedy,
+    /// Sample by softmax over the utilities, with the noise as temperature.
+    Softmax,
+}
+
+/// A behavior with its weight and the considerations that score it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub behavior: Behavior,
+    pub weight: f32,
+    pub considerations: Vec<Consideration>,
+}
+
+impl Choice {
+    pub fn new(behavior: Behavior, weight: f32) -> Self {
+        Self {
+            behavior,
+            weight,
+            considerations: Vec::new(),
+        }
+    }
+
+    /// Add a consideration; considerations multiply, so any one can veto.
+    pub fn consider(mut self, input: Input, curve: Curve) -> Self {
+        self.considerations.push(Consideration { input, curve });
+        self
+    }
+}
+
+/// The default choices: head for the goal unless the way there is
+/// remembered, otherwise explore or back away from recent cells, and rest
+/// when energy runs low.
+pub fn default_choices() -> Vec<Choice> {
+    let has_energy = Curve::Logistic { steepness: 20.0, midpoint: 0.1 };
+    vec![
+        Choice::new(Behavior::MoveTowardGoal, 1.0)
+            .consider(Input::Energy, has_energy)
+            .consider(Input::MemoryHits, Curve::Linear { slope: -1.0, intercept: 1.0 }),
+        Choice::new(Behavior::ExploreUnvisited, 0.6).consider(Input::Energy, has_energy),
+        Choice::new(Behavior::AvoidRecent, 0.3)
+            .consider(Input::Energy, has_energy)
+            .consider(Input::MemoryHits, Curve::Linear { slope: -0.5, intercept: 1.0 }),
+        Choice::new(Behavior::Rest, 1.0).consider(Input::Energy, Curve::Logistic { steepness: -20.0, midpoint: 0.2 }),
+    ]
+}
+
+/// Utility-AI agent.
+///
+/// Every tick each `Choice` is scored as its weight times the product of
+/// its considerations, and the highest utility wins. Under
+/// `Selection::Greedy` noise picks a random move, as for the other agents;
+/// under `Selection::Softmax` it becomes the temperature of a softmax over
+/// the utilities, so close calls go either way while clear ones rarely
+/// flip.
+pub struct UtilityAgent {
+    pub choices: Vec<Choice>,
+    memory: SpatialMemory,
+    selection: Selection,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+    /// Utilities of the available behaviors last tick, and the one taken.
+    last_scores: Vec<(Behavior, f32)>,
+    last_choice: Option<Behavior>,
+}
+
+impl UtilityAgent {
+    pub fn new() -> Self {
+        Self {
+            choices: default_choices(),
+            memory: SpatialMemory::new(0),
+            selection: Selection::Greedy,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+            last_scores: Vec::new(),
+            last_choice: None,
+        }
+    }
+
+    /// Create a utility agent with full cognitive config.
+    pub fn with_config(noise: f32, memory_capacity: usize, decay_rate: f32) -> Self {
+        Self {
+            memory: SpatialMemory::new(memory_capacity),
+            noise,
+            decay_rate,
+            ..Self::new()
+        }
+    }
+
+    /// Score with `choices` instead of the defaults.
+    pub fn with_choices(self, choices: Vec<Choice>) -> Self {
+        Self { choices, ..self }
+    }
+
+    /// Pick behaviors by `selection` instead of greedily.
+    pub fn with_selection(self, selection: Selection) -> Self {
+        Self { selection, ..self }
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// Utilities of the behaviors available last tick.
+    pub fn last_scores(&self) -> &[(Behavior, f32)] {
+        &self.last_scores
+    }
+
+    /// Times `pos` is in memory.
+    fn hits(&self, pos: Position) -> usize {
+        self.memory.iter().filter(|&&cell| cell == pos).count()
+    }
+
+    /// Where `behavior` would go from here, or `None` if it can't apply.
+    fn target(&self, behavior: Behavior, obs: &Observation, neighbors: &[Position], rng: &mut SimRng) -> Option<Position> {
+        let by_goal = |cells: Vec<Position>, rng: &mut SimRng| {
+            let closest = cells
+                .iter()
+                .map(|&n| obs.grid.distance(n, obs.goal))
+                .fold(f32::INFINITY, f32::min);
+            let ties: Vec<Position> = cells
+                .into_iter()
+                .filter(|&n| obs.grid.distance(n, obs.goal) <= closest)
+                .collect();
+            ties.choose(rng).copied()
+        };
+        match behavior {
+            Behavior::Rest => Some(obs.position),
+            Behavior::MoveTowardGoal => by_goal(neighbors.to_vec(), rng),
+            Behavior::ExploreUnvisited => {
+                by_goal(neighbors.iter().copied().filter(|&n| !self.memory.contains(&n)).collect(), rng)
+            }
+            Behavior::AvoidRecent => {
+                let fewest = neighbors.iter().map(|&n| self.hits(n)).min()?;
+                by_goal(neighbors.iter().copied().filter(|&n| self.hits(n) == fewest).collect(), rng)
+            }
+        }
+    }
+
+    fn input(&self, input: Input, obs: &Observation, target: Position) -> f32 {
+        match input {
+            Input::Energy => obs.energy as f32 / MAX_ENERGY as f32,
+            Input::GoalDistance => {
+                let span = (obs.grid.width + obs.grid.height) as f32;
+                (obs.grid.distance(target, obs.goal) / span).min(1.0)
+            }
+            Input::MemoryHits => (self.hits(target) as f32 / SATURATING_HITS).min(1.0),
+        }
+    }
+
+    fn utility(&self, choice: &Choice, obs: &Observation, target: Position) -> f32 {
+        choice
+            .considerations
+            .iter()
+            .map(|c| c.curve.evaluate(self.input(c.input, obs, target)))
+            .product::<f32>()
+            * choice.weight
+    }
+}
+
+impl Default for UtilityAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for UtilityAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+        self.memory.record(obs.position);
+        self.last_scores.clear();
+        self.last_choice = None;
+
+        if obs.position == obs.goal {
+            return Action::Interact;
+        }
+
+        let neighbors: Vec<Position> = obs
+            .grid
+            .walkable_neighbors(obs.position.x, obs.position.y)
+            .into_iter()
+            .map(|(x, y)| Position { x, y })
+            .collect();
+
+        let effective_noise = self.noise * self.exploration_rate;
+        if self.selection == Selection::Greedy
+            && effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some(&to) = neighbors.choose(rng)
+        {
+            self.noise_triggered = true;
+            return Direction::between(obs.position, to).map_or(Action::Wait, Action::Move);
+        }
+
+        let mut candidates = Vec::new();
+        for choice in &self.choices {
+            if let Some(target) = self.target(choice.behavior, obs, &neighbors, rng) {
+                candidates.push((choice.behavior, target, self.utility(choice, obs, target)));
+            }
+        }
+        let Some(best) = (0..candidates.len()).max_by(|&a, &b| candidates[a].2.total_cmp(&candidates[b].2)) else {
+            return Action::Wait;
+        };
+
+        let picked = if self.selection == Selection::Softmax && effective_noise > 0.0 {
+            let top = candidates[best].2;
+            let weights: Vec<f32> = candidates
+                .iter()
+                .map(|&(_, _, u)| ((u - top) / effective_noise).exp())
+                .collect();
+            let mut roll = rng.r#gen::<f32>() * weights.iter().sum::<f32>();
+            let mut picked = best;
+            for (i, w) in weights.iter().enumerate() {
+                if roll < *w {
+                    picked = i;
+                    break;
+                }
+                roll -= w;
+            }
+            self.noise_triggered = picked != best;
+            picked
+        } else {
+            best
+        };
+
+        self.last_scores = candidates.iter().map(|&(behavior, _, u)| (behavior, u)).collect();
+        let (behavior, target, _) = candidates[picked];
+        self.last_choice = Some(behavior);
+        match behavior {
+            Behavior::Rest => Action::Rest,
+            _ => Direction::between(obs.position, target).map_or(Action::Wait, Action::Move),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.selection {
+            Selection::Greedy => "Utility",
+            Selection::Softmax => "UtilitySoftmax",
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn debug_state(&self) -> String {
+        let Some(choice) = self.last_choice else {
+            return "Idle".to_string();
+        };
+        let utility = self
+            .last_scores
+            .iter()
+            .find(|&&(behavior, _)| behavior == choice)
+            .map_or(0.0, |&(_, u)| u);
+        format!("{:?} ({:.2})", choice, utility)
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::{Grid, World};
+
+    #[test]
+    fn memory_considerations_lead_out_of_a_cup_and_low_energy_rests() {
+        let grid = Grid::load("tests/fixtures/cup.map").unwrap();
+        let run = |agent: UtilityAgent| World::with_rng(grid.clone(), Box::new(agent), 0, seeded(0)).run_until_done(200);
+        assert!(!run(UtilityAgent::with_config(0.0, 0, 1.0)));
+        assert!(run(UtilityAgent::with_config(0.0, 30, 1.0)));
+        assert!(run(UtilityAgent::with_config(0.1, 30, 1.0).with_selection(Selection::Softmax)));
+
+        let mut agent = UtilityAgent::new();
+        let obs = Observation {
+            grid: &grid,
+            position: grid.start,
+            energy: 5,
+            tick: 0,
+            goal: grid.goal,
+        };
+        assert_eq!(agent.act(&obs, &mut seeded(0)), Action::Rest);
+    }
+}
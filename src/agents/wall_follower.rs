@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::engine::action::{Action, Direction, Observation};
+use crate::engine::rng::SimRng;
+use crate::engine::world::{Grid, Position};
+use super::Agent;
+
+/// Which hand is kept on the wall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    /// A quarter turn towards this hand.
+    pub fn turn(self, dir: Direction) -> Direction {
+        match (self, dir) {
+            (Hand::Right, Direction::North) | (Hand::Left, Direction::South) => Direction::East,
+            (Hand::Right, Direction::East) | (Hand::Left, Direction::West) => Direction::South,
+            (Hand::Right, Direction::South) | (Hand::Left, Direction::North) => Direction::West,
+            (Hand::Right, Direction::West) | (Hand::Left, Direction::East) => Direction::North,
+            // Tracing only ever faces a cardinal direction.
+            (_, other) => other,
+        }
+    }
+
+    /// A quarter turn away from this hand.
+    pub fn away(self, dir: Direction) -> Direction {
+        self.other().turn(dir)
+    }
+
+    fn other(self) -> Hand {
+        match self {
+            Hand::Left => Hand::Right,
+            Hand::Right => Hand::Left,
+        }
+    }
+}
+
+/// Whether the agent at `pos` can step `dir` on `grid`: only the
+/// neighboring cell is looked at.
+pub(crate) fn is_open(grid: &Grid, pos: Position, dir: Direction) -> bool {
+    dir.apply(pos)
+        .is_some_and(|to| grid.walkable_neighbors(pos.x, pos.y).contains(&(to.x, to.y)))
+}
+
+/// The cardinal direction that closes most of the gap from `from` to `to`.
+pub(crate) fn heading_towards(from: Position, to: Position) -> Direction {
+    let dx = to.x as isize - from.x as isize;
+    let dy = to.y as isize - from.y as isize;
+    if dx.abs() >= dy.abs() {
+        if dx >= 0 { Direction::East } else { Direction::West }
+    } else if dy >= 0 {
+        Direction::South
+    } else {
+        Direction::North
+    }
+}
+
+/// Traces a boundary with one hand on the wall, using cardinal moves.
+pub(crate) struct Tracer {
+    pub hand: Hand,
+    pub heading: Direction,
+    /// Moves made so far, as (from, direction); a repeat means the trace
+    /// has gone all the way round.
+    moves: HashSet<(Position, Direction)>,
+    looped: bool,
+}
+
+impl Tracer {
+    /// Start tracing with a wall straight ahead of `heading`.
+    pub fn begin(hand: Hand, heading: Direction) -> Self {
+        Self {
+            hand,
+            // Turn so the wall ends up on the hand side.
+            heading: hand.away(heading),
+            moves: HashSet::new(),
+            looped: false,
+        }
+    }
+
+    /// Next move from `pos`: towards the hand if possible, else straight
+    /// on, else away from the hand, else back. `None` if boxed in.
+    pub fn step(&mut self, grid: &Grid, pos: Position) -> Option<Direction> {
+        let toward = self.hand.turn(self.heading);
+        let away = self.hand.away(self.heading);
+        let back = self.hand.turn(toward);
+        let dir = [toward, self.heading, away, back]
+            .into_iter()
+            .find(|&dir| is_open(grid, pos, dir))?;
+        self.heading = dir;
+        if !self.moves.insert((pos, dir)) {
+            self.looped = true;
+        }
+        Some(dir)
+    }
+
+    /// Forget the moves made so far, keeping the heading.
+    pub fn restart(&mut self) {
+        self.moves.clear();
+        self.looped = false;
+    }
+
+    /// Whether the trace has come back round to a move it already made.
+    pub fn looped(&self) -> bool {
+        self.looped
+    }
+}
+
+/// Classic wall follower.
+///
+/// Heads for the goal until it meets a wall, then keeps `hand` on the
+/// wall for good, stepping onto the goal when it is next to it. It only
+/// looks at neighboring cells (plus the goal's position). Following
+/// solves simply connected mazes but can circle an island forever; once
+/// it is back on a move it already made it gives up and reports stuck.
+pub struct WallFollowerAgent {
+    hand: Hand,
+    tracer: Option<Tracer>,
+    stuck: bool,
+    noise: f32,
+    exploration_rate: f32,
+    decay_rate: f32,
+    noise_triggered: bool,
+}
+
+impl WallFollowerAgent {
+    pub fn new(hand: Hand) -> Self {
+        Self {
+            hand,
+            tracer: None,
+            stuck: false,
+            noise: 0.0,
+            exploration_rate: 1.0,
+            decay_rate: 1.0,
+            noise_triggered: false,
+        }
+    }
+
+    /// Create a wall follower with the shared noise/decay cognitive
+    /// parameters.
+    pub fn with_config(hand: Hand, noise: f32, decay_rate: f32) -> Self {
+        Self {
+            noise,
+            decay_rate,
+            ..Self::new(hand)
+        }
+    }
+
+    pub fn hand(&self) -> Hand {
+        self.hand
+    }
+}
+
+impl Agent for WallFollowerAgent {
+    fn act(&mut self, obs: &Observation, rng: &mut SimRng) -> Action {
+        self.noise_triggered = false;
+        self.exploration_rate *= self.decay_rate;
+        let (grid, pos) = (obs.grid, obs.position);
+
+        if pos == obs.goal {
+            return Action::Interact;
+        }
+        if let Some(dir) = Direction::between(pos, obs.goal).filter(|&dir| is_open(grid, pos, dir)) {
+            return Action::Move(dir);
+        }
+        if self.stuck {
+            return Action::Wait;
+        }
+
+        let effective_noise = self.noise * self.exploration_rate;
+        if effective_noise > 0.0
+            && rng.r#gen::<f32>() < effective_noise
+            && let Some(&(x, y)) = grid.walkable_neighbors(pos.x, pos.y).choose(rng)
+        {
+            // Knocked off the wall: look for one again.
+            self.noise_triggered = true;
+            self.tracer = None;
+            return Direction::between(pos, Position { x, y }).map_or(Action::Wait, Action::Move);
+        }
+
+        if self.tracer.is_none() {
+            let heading = heading_towards(pos, obs.goal);
+            if is_open(grid, pos, heading) {
+                return Action::Move(heading);
+            }
+            self.tracer = Some(Tracer::begin(self.hand, heading));
+        }
+        let Some(tracer) = self.tracer.as_mut() else {
+            return Action::Wait;
+        };
+        match tracer.step(grid, pos) {
+            Some(_) if tracer.looped() => {
+                self.stuck = true;
+                Action::Wait
+            }
+            Some(dir) => Action::Move(dir),
+            None => Action::Wait,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.hand {
+            Hand::Left => "WallFollowerLeft",
+            Hand::Right => "WallFollowerRight",
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn is_stuck(&self) -> bool {
+        self.stuck
+    }
+
+    fn debug_state(&self) -> String {
+        match &self.tracer {
+            _ if self.stuck => "Stuck (circled back)".to_string(),
+            Some(tracer) => format!("Following wall, heading {:?}", tracer.heading),
+            None => "Seeking a wall".to_string(),
+        }
+    }
+
+    fn did_noise_trigger(&self) -> bool {
+        self.noise_triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    #[test]
+    fn follows_walls_through_a_maze_and_gives_up_on_islands() {
+        let maze: Grid = "\
+width 7
+height 5
+map
+S.#....
+.##.##.
+...#...
+.#...#.
+.#.#..G"
+            .parse()
+            .unwrap();
+        for hand in [Hand::Left, Hand::Right] {
+            let mut world = World::with_rng(maze.clone(), Box::new(WallFollowerAgent::new(hand)), 0, seeded(0));
+            assert!(world.run_until_done(100), "{:?}", hand);
+        }
+
+        // The goal sits inside a closed ring the follower can only circle.
+        let island = Grid::load("tests/fixtures/walled_goal.map").unwrap();
+        let mut world = World::with_rng(island, Box::new(WallFollowerAgent::new(Hand::Right)), 0, seeded(0));
+        world.run_until_done(200);
+        assert!(world.is_agent_stuck());
+    }
+}
@@ -1,8 +1,12 @@
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
+use crate::engine::grid::Connectivity;
 use crate::engine::world::Grid;
 
+/// Costs are tracked in fixed point so diagonal (√2) steps stay integral.
+pub(crate) const COST_SCALE: f32 = 1000.0;
+
 /// Internal A* node stored in the open set.
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct Node {
@@ -34,10 +38,24 @@ impl PartialOrd for Node {
     }
 }
 
-fn manhattan(a: (usize, usize), b: (usize, usize)) -> u32 {
-    let dx = a.0.abs_diff(b.0);
-    let dy = a.1.abs_diff(b.1);
-    (dx + dy) as u32
+/// Scaled heuristic: Manhattan for 4-connected grids, octile for 8-connected.
+pub(crate) fn heuristic(a: (usize, usize), b: (usize, usize), connectivity: Connectivity) -> u32 {
+    let dx = a.0.abs_diff(b.0) as u32;
+    let dy = a.1.abs_diff(b.1) as u32;
+    let straight = COST_SCALE as u32;
+    match connectivity {
+        Connectivity::Four => (dx + dy) * straight,
+        Connectivity::Eight(_) => {
+            let diagonal = (std::f32::consts::SQRT_2 * COST_SCALE).round() as u32;
+            straight * dx.max(dy) + (diagonal - straight) * dx.min(dy)
+        }
+    }
+}
+
+/// `Grid::step_cost` in fixed point (`u32::MAX` if `to` can't be entered).
+pub(crate) fn scaled_step_cost(grid: &Grid, from: (usize, usize), to: (usize, usize)) -> u32 {
+    grid.step_cost(from, to)
+        .map_or(u32::MAX, |c| (c * COST_SCALE).round() as u32)
 }
 
 /// A* pathfinding on the provided grid.
@@ -45,6 +63,11 @@ fn manhattan(a: (usize, usize), b: (usize, usize)) -> u32 {
 /// Returns a path of (x, y) coordinates from `start` to `goal`,
 /// including both endpoints, or `None` if no path exists.
 ///
+/// Neighbours follow the grid's connectivity. Each step costs the
+/// terrain cost of the cell being entered, times √2 for diagonals
+/// (`Grid::step_cost`). The Manhattan/octile heuristic stays admissible
+/// because the cheapest terrain costs 1.
+///
 /// If `max_expansions` is `Some(n)`, the search stops after expanding
 /// `n` nodes and returns the best partial path found so far (the path
 /// to the node closest to the goal). This models bounded rationality.
@@ -64,8 +87,9 @@ pub fn find_path(
     let mut closed: HashSet<(usize, usize)> = HashSet::new();
 
     // Track the best (closest-to-goal) node seen so far for partial paths.
+    let connectivity = grid.connectivity;
     let mut best_pos = start;
-    let mut best_h = manhattan(start, goal);
+    let mut best_h = heuristic(start, goal, connectivity);
 
     let mut expansions: usize = 0;
 
@@ -74,7 +98,7 @@ pub fn find_path(
     open_set.push(Node {
         position: start,
         g_cost: 0,
-        h_cost: manhattan(start, goal),
+        h_cost: best_h,
     });
 
     while let Some(current) = open_set.pop() {
@@ -91,7 +115,7 @@ pub fn find_path(
         expansions += 1;
 
         // Update best node tracking.
-        let h = manhattan(current_pos, goal);
+        let h = heuristic(current_pos, goal, connectivity);
         if h < best_h {
             best_h = h;
             best_pos = current_pos;
@@ -107,36 +131,21 @@ pub fn find_path(
 
         let current_g = *g_score.get(&current_pos).unwrap_or(&u32::MAX);
 
-        // 4-directional neighbors.
-        let (cx, cy) = current_pos;
-        let neighbors = [
-            (cx.wrapping_sub(1), cy),     // left (checked below for bounds)
-            (cx + 1, cy),                  // right
-            (cx, cy.wrapping_sub(1)),     // up
-            (cx, cy + 1),                  // down
-        ];
-
-        for &(nx, ny) in &neighbors {
-            if nx >= grid.width || ny >= grid.height {
-                continue;
-            }
-            if !grid.is_walkable(nx, ny) {
-                continue;
-            }
-
-            let neighbor_pos = (nx, ny);
+        // Neighbors under the grid's connectivity (walkable only).
+        for neighbor_pos in grid.walkable_neighbors(current_pos.0, current_pos.1) {
             if closed.contains(&neighbor_pos) {
                 continue;
             }
 
-            let tentative_g = current_g.saturating_add(1);
+            let step_cost = scaled_step_cost(grid, current_pos, neighbor_pos);
+            let tentative_g = current_g.saturating_add(step_cost);
             let best_known_g = *g_score.get(&neighbor_pos).unwrap_or(&u32::MAX);
 
             if tentative_g < best_known_g {
                 g_score.insert(neighbor_pos, tentative_g);
                 came_from.insert(neighbor_pos, current_pos);
 
-                let h = manhattan(neighbor_pos, goal);
+                let h = heuristic(neighbor_pos, goal, connectivity);
                 open_set.push(Node {
                     position: neighbor_pos,
                     g_cost: tentative_g,
@@ -149,6 +158,13 @@ pub fn find_path(
     None
 }
 
+/// Total cost of walking `path` (the first cell is free).
+pub fn path_cost(grid: &Grid, path: &[(usize, usize)]) -> f32 {
+    path.windows(2)
+        .map(|step| grid.step_cost(step[0], step[1]).unwrap_or(0.0))
+        .sum()
+}
+
 /// Reconstruct a path from `came_from` map, ending at `end`.
 fn reconstruct_path(
     came_from: &HashMap<(usize, usize), (usize, usize)>,
@@ -212,6 +228,58 @@ mod tests {
         assert!(path.is_none());
     }
 
+    #[test]
+    fn path_detours_around_expensive_terrain() {
+        use crate::engine::terrain::Terrain;
+
+        // 5x3 grid with a water strip across the middle row except the edges.
+        let goal = Position { x: 4, y: 1 };
+        let mut grid = Grid::new(5, 3, goal);
+        for x in 1..4 {
+            grid.set_terrain(x, 1, Terrain::Water);
+        }
+
+        let path = find_path((0, 1), (4, 1), &grid, None).expect("path should exist");
+        // Straight through costs 5*3 + 1 = 16; going around row 0 costs 6.
+        assert_eq!(path_cost(&grid, &path), 6.0);
+        assert!(path.iter().all(|&(x, y)| grid.terrain(x, y) != Terrain::Water));
+    }
+
+    #[test]
+    fn diagonal_moves_follow_octile_costs() {
+        use crate::engine::grid::CornerRule;
+
+        let goal = Position { x: 4, y: 4 };
+        let mut grid = Grid::new(5, 5, goal);
+        grid.connectivity = Connectivity::Eight(CornerRule::Allow);
+
+        let path = find_path((0, 0), (4, 4), &grid, None).expect("path should exist");
+        assert_eq!(path.len(), 5);
+        assert!((path_cost(&grid, &path) - 4.0 * std::f32::consts::SQRT_2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn corner_rules_limit_diagonal_squeezes() {
+        use crate::engine::grid::CornerRule;
+
+        // Walls at (1,0) and (0,1): the only way out of (0,0) is the
+        // diagonal squeeze to (1,1).
+        let goal = Position { x: 2, y: 2 };
+        let mut grid = Grid::with_obstacles(3, 3, goal, &[(1, 0), (0, 1)]);
+
+        grid.connectivity = Connectivity::Eight(CornerRule::Allow);
+        assert!(find_path((0, 0), (2, 2), &grid, None).is_some());
+
+        grid.connectivity = Connectivity::Eight(CornerRule::NoSqueeze);
+        assert!(find_path((0, 0), (2, 2), &grid, None).is_none());
+
+        // One open side is enough for NoSqueeze but not for NoCut.
+        grid.set_walkable(1, 0, true);
+        assert_eq!(grid.walkable_neighbors(0, 0).len(), 2);
+        grid.connectivity = Connectivity::Eight(CornerRule::NoCut);
+        assert_eq!(grid.walkable_neighbors(0, 0), vec![(1, 0)]);
+    }
+
     #[test]
     fn bounded_search_returns_partial_path() {
         // 10x1 grid — optimal path is 10 cells long (start..=goal).
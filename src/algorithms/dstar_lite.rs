@@ -0,0 +1,297 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::algorithms::astar::{heuristic, scaled_step_cost, COST_SCALE};
+use crate::engine::world::{Grid, Position};
+
+const INF: u32 = u32::MAX;
+
+type Key = (u32, u32);
+
+/// Incremental shortest-path planner (D* Lite, Koenig & Likhachev 2002).
+///
+/// The search runs backwards from the goal, so `g` holds each cell's cost
+/// to reach the goal. When cells change, only the affected part of the
+/// search is repaired instead of planning from scratch, which keeps
+/// replanning cheap as the agent moves through a changing grid.
+///
+/// Costs use the same fixed-point step costs as `find_path`, so both
+/// planners agree on optimal routes.
+pub struct DStarLite {
+    width: usize,
+    start: usize,
+    last: usize,
+    goal: usize,
+    km: u32,
+    g: Vec<u32>,
+    rhs: Vec<u32>,
+    open: BinaryHeap<Reverse<(Key, usize)>>,
+    /// Current key of each queued cell; heap entries with another key are stale.
+    open_key: Vec<Option<Key>>,
+    /// The grid as the planner last saw it.
+    known: Grid,
+    /// Total cells expanded across all searches.
+    pub expansions: usize,
+}
+
+impl DStarLite {
+    /// Plan from `start` to the grid's goal.
+    pub fn new(start: Position, grid: &Grid) -> Self {
+        let cells = grid.width * grid.height;
+        let goal = grid.goal.y * grid.width + grid.goal.x;
+        let start = start.y * grid.width + start.x;
+        let mut planner = Self {
+            width: grid.width,
+            start,
+            last: start,
+            goal,
+            km: 0,
+            g: vec![INF; cells],
+            rhs: vec![INF; cells],
+            open: BinaryHeap::new(),
+            open_key: vec![None; cells],
+            known: grid.clone(),
+            expansions: 0,
+        };
+        if grid.is_walkable(grid.goal.x, grid.goal.y) {
+            planner.rhs[goal] = 0;
+            let key = planner.key(goal);
+            planner.push(goal, key);
+        }
+        planner.compute_shortest_path();
+        planner
+    }
+
+    /// Move the search start to `position` and absorb any cells that
+    /// changed in `grid` since the last call.
+    ///
+    /// Returns `true` if the grid changed and the plan was repaired.
+    pub fn update(&mut self, position: Position, grid: &Grid) -> bool {
+        if grid.width != self.known.width
+            || grid.height != self.known.height
+            || grid.goal != self.known.goal
+            || grid.connectivity != self.known.connectivity
+        {
+            *self = Self::new(position, grid);
+            return true;
+        }
+
+        self.start = self.index((position.x, position.y));
+
+        let mut changed = Vec::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if grid.terrain(x, y) != self.known.terrain(x, y) {
+                    changed.push((x, y));
+                }
+            }
+        }
+        if changed.is_empty() {
+            return false;
+        }
+
+        let connectivity = self.known.connectivity;
+        self.km = self
+            .km
+            .saturating_add(heuristic(self.cell(self.last), self.cell(self.start), connectivity));
+        self.last = self.start;
+
+        for &(x, y) in &changed {
+            self.known.set_terrain(x, y, grid.terrain(x, y));
+        }
+        // A cell's outgoing edges depend on itself, its neighbors and (for
+        // corner rules) the cells beside each diagonal, all of which lie
+        // within one step of the changed cell.
+        for &(x, y) in &changed {
+            for cell in self.around((x, y)) {
+                self.update_vertex(self.index(cell));
+            }
+        }
+
+        self.compute_shortest_path();
+        true
+    }
+
+    /// Whether the goal is currently reachable from the start.
+    pub fn has_path(&self) -> bool {
+        self.g[self.start] != INF
+    }
+
+    /// Cost of the optimal route from the start, in terrain units.
+    pub fn cost_to_goal(&self) -> Option<f32> {
+        self.has_path().then(|| self.g[self.start] as f32 / COST_SCALE)
+    }
+
+    /// Best neighbor to step to from `from`, or `None` if the goal is
+    /// unreachable (or `from` is the goal).
+    pub fn next_step(&self, from: Position) -> Option<Position> {
+        let u = (from.x, from.y);
+        if self.index(u) == self.goal {
+            return None;
+        }
+        self.known
+            .walkable_neighbors(u.0, u.1)
+            .into_iter()
+            .map(|s| (s, scaled_step_cost(&self.known, u, s).saturating_add(self.g[self.index(s)])))
+            .filter(|&(_, cost)| cost != INF)
+            .min_by_key(|&(_, cost)| cost)
+            .map(|((x, y), _)| Position { x, y })
+    }
+
+    /// The current route from the start to the goal, including both ends
+    /// (empty if there is none).
+    pub fn path(&self) -> Vec<(usize, usize)> {
+        if !self.has_path() {
+            return Vec::new();
+        }
+        let mut pos = self.cell(self.start);
+        let mut path = vec![pos];
+        while let Some(next) = self.next_step(Position { x: pos.0, y: pos.1 }) {
+            pos = (next.x, next.y);
+            path.push(pos);
+            if path.len() > self.g.len() {
+                break;
+            }
+        }
+        path
+    }
+
+    fn compute_shortest_path(&mut self) {
+        while let Some((k_old, u)) = self.top() {
+            let start_key = self.key(self.start);
+            if k_old >= start_key && self.rhs[self.start] == self.g[self.start] {
+                break;
+            }
+            self.open.pop();
+            self.open_key[u] = None;
+            self.expansions += 1;
+
+            let k_new = self.key(u);
+            if k_old < k_new {
+                self.push(u, k_new);
+            } else if self.g[u] > self.rhs[u] {
+                self.g[u] = self.rhs[u];
+                for p in self.around(self.cell(u)) {
+                    self.update_vertex(self.index(p));
+                }
+            } else {
+                self.g[u] = INF;
+                self.update_vertex(u);
+                for p in self.around(self.cell(u)) {
+                    self.update_vertex(self.index(p));
+                }
+            }
+        }
+    }
+
+    fn update_vertex(&mut self, u: usize) {
+        if u != self.goal {
+            let (x, y) = self.cell(u);
+            self.rhs[u] = if self.known.is_walkable(x, y) {
+                self.known
+                    .walkable_neighbors(x, y)
+                    .into_iter()
+                    .map(|s| scaled_step_cost(&self.known, (x, y), s).saturating_add(self.g[self.index(s)]))
+                    .min()
+                    .unwrap_or(INF)
+            } else {
+                INF
+            };
+        }
+        self.open_key[u] = None;
+        if self.g[u] != self.rhs[u] {
+            let key = self.key(u);
+            self.push(u, key);
+        }
+    }
+
+    fn key(&self, u: usize) -> Key {
+        let m = self.g[u].min(self.rhs[u]);
+        if m == INF {
+            return (INF, INF);
+        }
+        let h = heuristic(self.cell(self.start), self.cell(u), self.known.connectivity);
+        (m.saturating_add(h).saturating_add(self.km), m)
+    }
+
+    fn push(&mut self, u: usize, key: Key) {
+        self.open_key[u] = Some(key);
+        self.open.push(Reverse((key, u)));
+    }
+
+    /// Smallest live entry in the queue, discarding stale ones.
+    fn top(&mut self) -> Option<(Key, usize)> {
+        while let Some(&Reverse((key, u))) = self.open.peek() {
+            if self.open_key[u] == Some(key) {
+                return Some((key, u));
+            }
+            self.open.pop();
+        }
+        None
+    }
+
+    /// In-bounds cells within one step (including diagonals) of `cell`.
+    fn around(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut out = Vec::with_capacity(9);
+        for ny in y.saturating_sub(1)..=(y + 1).min(self.known.height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                out.push((nx, ny));
+            }
+        }
+        out
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        y * self.width + x
+    }
+
+    fn cell(&self, u: usize) -> (usize, usize) {
+        (u % self.width, u / self.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::astar::{find_path, path_cost};
+    use crate::engine::terrain::Terrain;
+
+    #[test]
+    fn matches_astar_cost_on_static_grid() {
+        let mut grid = Grid::new(6, 4, Position { x: 5, y: 3 });
+        grid.set_terrain(2, 0, Terrain::Water);
+        grid.set_terrain(2, 1, Terrain::Mud);
+        grid.set_walkable(3, 2, false);
+
+        let planner = DStarLite::new(grid.start, &grid);
+        let astar = find_path((0, 0), (5, 3), &grid, None).unwrap();
+        assert_eq!(planner.cost_to_goal(), Some(path_cost(&grid, &astar)));
+        assert_eq!(planner.path().first(), Some(&(0, 0)));
+        assert_eq!(planner.path().last(), Some(&(5, 3)));
+    }
+
+    #[test]
+    fn repairs_plan_when_cells_change() {
+        let mut grid = Grid::new(5, 5, Position { x: 4, y: 0 });
+        let mut planner = DStarLite::new(grid.start, &grid);
+        assert_eq!(planner.cost_to_goal(), Some(4.0));
+        assert!(!planner.update(grid.start, &grid));
+
+        // Wall off the top row except for a detour through row 2.
+        for y in 0..4 {
+            grid.set_walkable(2, y, false);
+        }
+        assert!(planner.update(grid.start, &grid));
+        let astar = find_path((0, 0), (4, 0), &grid, None).unwrap();
+        assert_eq!(planner.cost_to_goal(), Some(path_cost(&grid, &astar)));
+        assert!(planner.path().iter().all(|&(x, y)| grid.is_walkable(x, y)));
+
+        // Close the last gap: the goal becomes unreachable, then reopens.
+        grid.set_walkable(2, 4, false);
+        planner.update(grid.start, &grid);
+        assert!(!planner.has_path());
+        grid.set_walkable(2, 4, true);
+        planner.update(grid.start, &grid);
+        assert_eq!(planner.cost_to_goal(), Some(12.0));
+    }
+}
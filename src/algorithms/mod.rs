@@ -1 +1,2 @@
 pub mod astar;
+pub mod dstar_lite;
@@ -1,10 +1,11 @@
 use std::{thread, time::Duration};
 
+use cognitive_grid::agents::adapter::legacy;
 use cognitive_grid::agents::astar::AStarAgent;
 use cognitive_grid::engine::world::World;
 
 fn main() {
-    let agent = Box::new(AStarAgent::new(0, 0));
+    let agent = legacy(AStarAgent::new(0, 0));
     let mut world = World::new(10, 5, agent);
     let max_steps: usize = 500;
 
@@ -1,18 +1,26 @@
+use std::error::Error;
 use std::{thread, time::Duration};
 
+use cognitive_grid::agents::adapter::legacy;
 use cognitive_grid::agents::behavior_tree::BehaviorTreeAgent;
+use cognitive_grid::agents::bt_format::{load_tree, Registry};
 use cognitive_grid::engine::world::World;
 
-fn main() {
-    // Create BT agent starting at (0,0)
-    let agent = Box::new(BehaviorTreeAgent::new(0, 0));
+fn main() -> Result<(), Box<dyn Error>> {
+    // Create BT agent starting at (0,0), optionally running a tree file
+    // given as the first argument (e.g. trees/cautious.bt).
+    let mut agent = BehaviorTreeAgent::new(0, 0);
+    if let Some(path) = std::env::args().nth(1) {
+        agent = agent.with_tree(load_tree(path, &Registry::standard())?);
+    }
+    let agent = legacy(agent);
     let mut world = World::new(10, 8, agent);
 
     loop {
         world.print();
         println!(
-            "BT Info: Energy={:?}", 
-            world.agent.energy()
+            "BT Info: Energy={}", 
+            world.body.energy
         );
 
         if world.has_reached_goal() {
@@ -28,4 +36,5 @@ fn main() {
         world.update();
         thread::sleep(Duration::from_millis(200));
     }
+    Ok(())
 }
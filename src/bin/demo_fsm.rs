@@ -1,10 +1,11 @@
 use std::{thread, time::Duration};
 
+use cognitive_grid::agents::adapter::legacy;
 use cognitive_grid::agents::fsm::FSMAgent;
 use cognitive_grid::engine::world::World;
 
 fn main() {
-    let agent = Box::new(FSMAgent::new(0, 0));
+    let agent = legacy(FSMAgent::new(0, 0));
     let mut world = World::new(10, 5, agent);
 
     loop {
@@ -12,9 +13,9 @@ fn main() {
 
         // Print extra debug info using trait methods
         println!(
-            "Extra Info: State=[{}] Energy=[{:?}]",
+            "Extra Info: State=[{}] Energy=[{}]",
             world.agent.debug_state(),
-            world.agent.energy()
+            world.body.energy
         );
 
         if world.has_reached_goal() {
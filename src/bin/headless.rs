@@ -1,40 +1,142 @@
 use cognitive_grid::agents::astar::AStarAgent;
+use cognitive_grid::agents::belief::Sensor;
 use cognitive_grid::agents::behavior_tree::BehaviorTreeAgent;
+use cognitive_grid::agents::bt_format::{load_tree, Registry};
+use cognitive_grid::agents::bug::{BugAgent, BugVariant};
+use cognitive_grid::agents::dstar_lite::DStarLiteAgent;
+use cognitive_grid::agents::frontier::FrontierAgent;
 use cognitive_grid::agents::fsm::FSMAgent;
+use cognitive_grid::agents::goap::GoapAgent;
+use cognitive_grid::agents::mcts::MctsAgent;
+use cognitive_grid::agents::potential_field::PotentialFieldAgent;
+use cognitive_grid::agents::utility::UtilityAgent;
+use cognitive_grid::agents::adapter::legacy;
 use cognitive_grid::agents::Agent;
-use cognitive_grid::engine::multi_world::MultiWorld;
+use cognitive_grid::engine::dynamics::ObstacleRule;
+use cognitive_grid::engine::goals::GoalMode;
+use cognitive_grid::engine::multi_world::{MultiWorld, Occupancy, Priority};
+use cognitive_grid::engine::rng::random_seed;
 use cognitive_grid::engine::world::{Grid, Position};
+use rand::seq::SliceRandom;
+use std::error::Error;
 
-fn main() {
-    let grid_w = 10;
-    let grid_h = 5;
+fn main() -> Result<(), Box<dyn Error>> {
     let max_steps: usize = 500;
     let obstacle_density = 0.15;
 
-    // Build a shared grid with random obstacles.
-    let goal = Position {
-        x: grid_w - 1,
-        y: grid_h - 1,
+    // `--seed <n>` replays a previous run; `--map <file>` loads a fixed
+    // layout instead of scattering random obstacles; `--dynamic` adds
+    // wandering blockers and short-lived walls; `--collisions index|random`
+    // makes agents block each other, breaking ties by index or at random;
+    // `--goals <n>` places a random start and `n` goals (`--visit-all` to
+    // require every one); `--spread` gives each agent its own start;
+    // `--tree <file>` gives the BT agent a tree from a file.
+    let mut seed = None;
+    let mut map_path = None;
+    let mut dynamic = false;
+    let mut occupancy = Occupancy::overlap();
+    let mut goal_count = None;
+    let mut visit_all = false;
+    let mut spread = false;
+    let mut tree_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(parse_value::<u64>("--seed", args.next())?),
+            "--map" => map_path = Some(parse_value::<String>("--map", args.next())?),
+            "--dynamic" => dynamic = true,
+            "--collisions" => match args.next().as_deref() {
+                Some("index") => occupancy = Occupancy::blocking(Priority::Index),
+                Some("random") => occupancy = Occupancy::blocking(Priority::Random),
+                _ => return Err("--collisions needs `index` or `random`".into()),
+            },
+            "--goals" => goal_count = Some(parse_value::<usize>("--goals", args.next())?),
+            "--visit-all" => visit_all = true,
+            "--spread" => spread = true,
+            "--tree" => tree_path = Some(parse_value::<String>("--tree", args.next())?),
+            other => eprintln!("Ignoring unknown argument `{}`", other),
+        }
+    }
+    let seed = seed.unwrap_or_else(random_seed);
+
+    let grid = match &map_path {
+        Some(path) => Grid::load(path)?,
+        None => Grid::new(10, 5, Position { x: 9, y: 4 }),
     };
-    let mut grid = Grid::new(grid_w, grid_h, goal);
-    grid.scatter_obstacles(obstacle_density);
+    let (grid_w, grid_h) = (grid.width, grid.height);
 
-    let obstacles = grid.obstacle_positions();
+    let mut bt_agent = BehaviorTreeAgent::with_config(0, 0, 0.15, 10, 0.995);
+    if let Some(path) = &tree_path {
+        bt_agent = bt_agent.with_tree(load_tree(path, &Registry::standard())?);
+    }
 
-    // Create agents with cognitive parameters.
+    // Create agents with cognitive parameters. The world places them, so
+    // their own start cell doesn't matter.
     let agents: Vec<Box<dyn Agent>> = vec![
-        Box::new(FSMAgent::with_config(0, 0, 0.15, 10, 0.995)),
-        Box::new(AStarAgent::with_config(0, 0, Some(30), 0.1, 10, 0.995)),
-        Box::new(BehaviorTreeAgent::with_config(0, 0, 0.15, 10, 0.995)),
+        legacy(FSMAgent::with_config(0, 0, 0.15, 10, 0.995)),
+        legacy(AStarAgent::with_config(0, 0, Some(30), 0.1, 10, 0.995)),
+        legacy(bt_agent),
+        legacy(DStarLiteAgent::with_config(0, 0, 0.1, 10, 0.995)),
+        Box::new(MctsAgent::with_config(200, 0.1, 0.995)),
+        Box::new(FrontierAgent::with_config(0.1, 0.995).with_sensor(Sensor::new(3, true))),
+        Box::new(PotentialFieldAgent::with_config(0.1, 10, 0.995)),
+        Box::new(BugAgent::with_config(BugVariant::Bug2, 0.1, 0.995)),
+        Box::new(UtilityAgent::with_config(0.1, 10, 0.995)),
+        Box::new(GoapAgent::with_config(0.1, 0.995).with_sensor(Sensor::new(3, true))),
     ];
 
     let agent_names: Vec<&str> = agents.iter().map(|a| a.name()).collect();
 
-    let mut world = MultiWorld::new(grid, agents);
+    let mut world = MultiWorld::with_seed(grid, agents, seed).with_occupancy(occupancy);
+
+    // Scatter obstacles from the world's RNG so the layout is part of the replay.
+    if map_path.is_none() {
+        world.grid.scatter_obstacles(obstacle_density, &mut world.rng);
+        let opened = world.grid.repair_connectivity();
+        if opened > 0 {
+            println!("Opened {} wall(s) to connect start and goal", opened);
+        }
+    }
+    if let Some(count) = goal_count {
+        let min_distance = (grid_w + grid_h) / 3;
+        if !world.grid.place_start_and_goals(count, min_distance, &mut world.rng) {
+            println!("Could not place {} goal(s) {}+ moves from a start; keeping the layout's", count, min_distance);
+        }
+    }
+    if visit_all {
+        world.grid.goal_mode = GoalMode::All;
+    }
+    if spread {
+        // Any cell in the primary goal's region.
+        let dist = world.grid.distances_from(world.grid.goal);
+        let cells: Vec<Position> = (0..grid_h)
+            .flat_map(|y| (0..grid_w).map(move |x| Position { x, y }))
+            .filter(|p| dist[p.y][p.x].is_some_and(|d| d > 0))
+            .collect();
+        let starts: Vec<Position> = (0..world.agents.len())
+            .filter_map(|_| cells.choose(&mut world.rng).copied())
+            .collect();
+        world = world.with_starts(&starts);
+    } else {
+        // The start may have moved since the world was built.
+        let starts = vec![world.grid.start; world.agents.len()];
+        world = world.with_starts(&starts);
+    }
+    if dynamic {
+        world = world.with_dynamics(&[
+            ObstacleRule::Wanderers { count: 3, move_chance: 0.5 },
+            ObstacleRule::SpawnWalls { chance: 0.05, lifetime: 20 },
+        ]);
+    }
+    let obstacles = world.grid.obstacle_positions();
 
     println!("Cognitive Grid — Multi-Agent Headless Runner");
-    println!("Grid: {}x{} | Obstacles: {} | Max steps: {}",
-        grid_w, grid_h, obstacles.len(), max_steps);
+    println!("Grid: {}x{} | Obstacles: {} | Goals: {} ({}) | Max steps: {} | Seed: {}",
+        grid_w, grid_h, obstacles.len(), world.grid.goals().len(), world.grid.goal_mode.label(), max_steps, seed);
+    match world.grid.optimal_path_len() {
+        Some(len) => println!("Optimal path: {} steps", len),
+        None => println!("Goal is unreachable from start"),
+    }
     println!("{}", "═".repeat(55));
 
     // Run until all agents reach the goal or we exceed max steps.
@@ -42,8 +144,8 @@ fn main() {
 
     while world.step < max_steps {
         // Check who just finished.
-        for (i, agent) in world.agents.iter().enumerate() {
-            if finish_step[i].is_none() && agent.position() == world.grid.goal {
+        for i in 0..world.agents.len() {
+            if finish_step[i].is_none() && world.agent_at_goal(i) {
                 finish_step[i] = Some(world.step);
                 println!("  ✓ {} reached goal at step {}", agent_names[i], world.step);
             }
@@ -57,23 +159,35 @@ fn main() {
     }
 
     // Final check (in case an agent reached goal on the last tick).
-    for (i, agent) in world.agents.iter().enumerate() {
-        if finish_step[i].is_none() && agent.position() == world.grid.goal {
-            finish_step[i] = Some(world.step);
+    for (i, finish) in finish_step.iter_mut().enumerate() {
+        if finish.is_none() && world.agent_at_goal(i) {
+            *finish = Some(world.step);
         }
     }
 
     // Print summary.
-    println!("{}", "═".repeat(55));
-    println!("{:<15} {:>6} {:>8} {:>8}",
-        "Agent", "Steps", "Success", "Energy");
-    println!("{}", "─".repeat(55));
-    for (i, agent) in world.agents.iter().enumerate() {
+    println!("{}", "═".repeat(73));
+    println!("{:<15} {:>6} {:>8} {:>8} {:>8} {:>10} {:>8}",
+        "Agent", "Steps", "Success", "Energy", "Replans", "Collisions", "Waits");
+    println!("{}", "─".repeat(73));
+    for (i, (agent, body)) in world.agents.iter().zip(&world.bodies).enumerate() {
         let success = finish_step[i].is_some();
         let steps = finish_step[i].unwrap_or(world.step);
-        let energy = agent.energy().unwrap_or(0);
-        println!("{:<15} {:>6} {:>8} {:>8}",
-            agent_names[i], steps, success, energy);
+        let energy = body.energy;
+        let replans = agent.replans().map_or("-".to_string(), |n| n.to_string());
+        let traffic = world.traffic[i];
+        println!("{:<15} {:>6} {:>8} {:>8} {:>8} {:>10} {:>8}",
+            agent_names[i], steps, success, energy, replans, traffic.collisions, traffic.waits);
     }
-    println!("{}", "═".repeat(55));
+    println!("{}", "═".repeat(73));
+    Ok(())
+}
+
+/// Parse the value following `flag`, failing on a missing or malformed one
+/// rather than silently falling back to a default.
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, Box<dyn Error>> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("{}: `{}` is not a valid value", flag, value).into())
 }
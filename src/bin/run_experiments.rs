@@ -1,5 +1,18 @@
-use cognitive_grid::experiments::runner::{run_batch_and_save, ExperimentConfig, AgentType};
+use cognitive_grid::agents::belief::Sensor;
+use cognitive_grid::agents::bt_format::{load_tree, Registry};
+use cognitive_grid::agents::bug::BugVariant;
+use cognitive_grid::agents::qlearning::QTable;
+use cognitive_grid::agents::utility::Selection;
+use cognitive_grid::agents::wall_follower::Hand;
+use cognitive_grid::engine::dynamics::ObstacleRule;
+use cognitive_grid::engine::grid::{Connectivity, CornerRule};
+use cognitive_grid::engine::mapgen::MapGenerator;
+use cognitive_grid::engine::world::{Grid, Position};
+use cognitive_grid::experiments::runner::{run_batch_and_save, train_lrta, train_q_learning, ExperimentConfig, AgentType};
+use cognitive_grid::logging::metrics::write_episode_logs_csv;
 use std::error::Error;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 fn main() -> Result<(), Box<dyn Error>> {
     println!("Cognitive Grid — Experiment Sweeps");
@@ -9,6 +22,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         (AgentType::AStar, "AStar"),
         (AgentType::Fsm, "FSM"),
         (AgentType::BehaviorTree, "BT"),
+        (AgentType::Utility(Selection::Greedy), "Utility"),
+        (AgentType::Goap, "GOAP"),
     ];
 
     // ── Sweep 1: Noise levels ──────────────────────────────
@@ -43,8 +58,9 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // ── Sweep 3: Memory capacity ───────────────────────────
     println!("\n[Sweep] Memory capacity: 0, 5, 20, 100");
+    let memory_agents = agent_types.iter().chain([(AgentType::PotentialField, "PotentialField")].iter());
     for mem in [0, 5, 20, 100] {
-        for &(agent_type, label) in &agent_types {
+        for &(agent_type, label) in memory_agents.clone() {
             let config = ExperimentConfig {
                 agent_type,
                 episodes: 100,
@@ -74,6 +90,273 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    // ── Sweep 5: Benchmark maps ────────────────────────────
+    println!("\n[Sweep] Benchmark maps in maps/");
+    let mut map_paths: Vec<_> = fs::read_dir("maps")?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "map"))
+        .collect();
+    map_paths.sort();
+    for map_path in &map_paths {
+        let map = Grid::load(map_path)?;
+        for &(agent_type, label) in &agent_types {
+            let config = ExperimentConfig {
+                agent_type,
+                episodes: 100,
+                map: Some(map.clone()),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  {} map={} → {:?}", label, map_path.display(), path);
+        }
+    }
+
+    // ── Sweep 5b: Reactive baselines on benchmark maps ─────
+    println!("\n[Sweep] Reactive baselines (BT, wall followers, Bug1/Bug2) on benchmark maps");
+    let reactive = [
+        (AgentType::BehaviorTree, "BT"),
+        (AgentType::WallFollower(Hand::Left), "WallFollowerLeft"),
+        (AgentType::WallFollower(Hand::Right), "WallFollowerRight"),
+        (AgentType::Bug(BugVariant::Bug1), "Bug1"),
+        (AgentType::Bug(BugVariant::Bug2), "Bug2"),
+    ];
+    for map_path in &map_paths {
+        let map = Grid::load(map_path)?;
+        for &(agent_type, label) in &reactive {
+            let config = ExperimentConfig {
+                agent_type,
+                episodes: 100,
+                map: Some(map.clone()),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  {} map={} → {:?}", label, map_path.display(), path);
+        }
+    }
+
+    // ── Sweep 5c: Behavior tree variants from trees/ ───────
+    println!("\n[Sweep] Behavior tree files in trees/ on benchmark maps (memory=20)");
+    let mut tree_paths: Vec<_> = fs::read_dir("trees")?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bt"))
+        .collect();
+    tree_paths.sort();
+    let registry = Registry::standard();
+    for tree_path in &tree_paths {
+        let tree = load_tree(tree_path, &registry)?;
+        for map_path in &map_paths {
+            let config = ExperimentConfig {
+                agent_type: AgentType::BehaviorTree,
+                episodes: 100,
+                map: Some(Grid::load(map_path)?),
+                memory_capacity: 20,
+                behavior_tree: Some(tree.clone()),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  tree={} map={} → {:?}", tree_path.display(), map_path.display(), path);
+        }
+    }
+
+    // ── Sweep 6: Procedural generators ─────────────────────
+    println!("\n[Sweep] Procedural map generators (21x15)");
+    let generators = [
+        MapGenerator::RecursiveBacktracker,
+        MapGenerator::Kruskal,
+        MapGenerator::Cave { fill: 0.45, iterations: 4 },
+        MapGenerator::Dungeon { rooms: 6 },
+        MapGenerator::Trap { pockets: 3 },
+    ];
+    for generator in generators {
+        for &(agent_type, label) in &agent_types {
+            let config = ExperimentConfig {
+                agent_type,
+                episodes: 100,
+                grid_width: 21,
+                grid_height: 15,
+                generator: Some(generator),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  {} generator={} → {:?}", label, generator.label(), path);
+        }
+    }
+
+    // ── Sweep 7: Action-space size × noise ─────────────────
+    println!("\n[Sweep] 4- vs 8-connected movement with noise: 0.0, 0.1, 0.3, 0.5");
+    let connectivities = [
+        Connectivity::Four,
+        Connectivity::Eight(CornerRule::NoCut),
+        Connectivity::Eight(CornerRule::Allow),
+    ];
+    for connectivity in connectivities {
+        for noise in [0.0, 0.1, 0.3, 0.5] {
+            for &(agent_type, label) in &agent_types {
+                let config = ExperimentConfig {
+                    agent_type,
+                    noise,
+                    episodes: 100,
+                    obstacle_density: 0.2,
+                    connectivity: Some(connectivity),
+                    ..Default::default()
+                };
+                let path = run_batch_and_save(&config)?;
+                println!(
+                    "  {} connectivity={} noise={:.1} → {:?}",
+                    label,
+                    connectivity.label(),
+                    noise,
+                    path
+                );
+            }
+        }
+    }
+
+    // ── Sweep 8: Dynamic obstacles ─────────────────────────
+    println!("\n[Sweep] Dynamic obstacles: static, door, wanderers, spawning walls");
+    let wall_gap = Grid::load("maps/wall_gap.map")?;
+    let scenarios: [(&str, Option<&Grid>, Vec<ObstacleRule>); 4] = [
+        ("static", None, Vec::new()),
+        (
+            "door",
+            Some(&wall_gap),
+            vec![ObstacleRule::Door { pos: Position { x: 4, y: 2 }, period: 10, open_for: 5 }],
+        ),
+        ("wanderers", None, vec![ObstacleRule::Wanderers { count: 4, move_chance: 0.5 }]),
+        ("spawn_walls", None, vec![ObstacleRule::SpawnWalls { chance: 0.1, lifetime: 15 }]),
+    ];
+    let replanning_agents = [
+        (AgentType::AStar, "AStar"),
+        (AgentType::DStarLite, "DStarLite"),
+        (AgentType::Fsm, "FSM"),
+        (AgentType::BehaviorTree, "BT"),
+    ];
+    for (scenario, map, dynamics) in &scenarios {
+        for &(agent_type, label) in &replanning_agents {
+            let config = ExperimentConfig {
+                agent_type,
+                episodes: 100,
+                obstacle_density: 0.15,
+                map: map.cloned(),
+                dynamics: dynamics.clone(),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  {} dynamics={} → {:?}", label, scenario, path);
+        }
+    }
+
+    // ── Sweep 9: Sensing radius ────────────────────────────
+    println!("\n[Sweep] Sensing radius (line of sight): full, 5, 3, 1");
+    for radius in [None, Some(5), Some(3), Some(1)] {
+        for (agent_type, label) in [
+            (AgentType::AStar, "AStar"),
+            (AgentType::DStarLite, "DStarLite"),
+            (AgentType::Frontier, "Frontier"),
+            (AgentType::Goap, "GOAP"),
+        ] {
+            let config = ExperimentConfig {
+                agent_type,
+                episodes: 100,
+                grid_width: 21,
+                grid_height: 15,
+                generator: Some(MapGenerator::Trap { pockets: 3 }),
+                sensor: radius.map(|r| Sensor::new(r, true)),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            let radius = radius.map_or("full".to_string(), |r| r.to_string());
+            println!("  {} radius={} → {:?}", label, radius, path);
+        }
+    }
+
+    // ── Sweep 10: Q-learning train/evaluate ────────────────
+    println!("\n[Sweep] Q-learning: train on benchmark maps, save, reload and evaluate greedily");
+    fs::create_dir_all("experiments/data")?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    for name in ["wall_gap", "u_trap"] {
+        let map = Grid::load(format!("maps/{}.map", name))?;
+        let training = ExperimentConfig {
+            episodes: 500,
+            map: Some(map.clone()),
+            noise: 0.3,
+            decay_rate: 0.9995,
+            ..Default::default()
+        };
+        let (logs, table) = train_q_learning(&training);
+        let log_path = format!("experiments/data/{}_qlearning_{}_training.csv", timestamp, name);
+        let table_path = format!("experiments/data/{}_qtable_{}.csv", timestamp, name);
+        write_episode_logs_csv(&log_path, &logs)?;
+        table.save(&table_path)?;
+        println!("  {} trained ({} states) → {} / {}", name, table.len(), log_path, table_path);
+
+        let evaluation = ExperimentConfig {
+            agent_type: AgentType::QLearning,
+            episodes: 20,
+            map: Some(map),
+            q_table: Some(QTable::load(&table_path)?),
+            learning: false,
+            ..Default::default()
+        };
+        let path = run_batch_and_save(&evaluation)?;
+        println!("  {} greedy evaluation → {:?}", name, path);
+    }
+
+    // ── Sweep 11: Sampling vs. search under a budget ───────
+    println!("\n[Sweep] Planning budget, MCTS iterations vs. A* expansions: 10, 50, 200, 800");
+    for budget in [10, 50, 200, 800] {
+        for (agent_type, label) in [(AgentType::Mcts, "MCTS"), (AgentType::AStar, "AStar")] {
+            let config = ExperimentConfig {
+                agent_type,
+                episodes: 100,
+                grid_width: 21,
+                grid_height: 15,
+                generator: Some(MapGenerator::Trap { pockets: 3 }),
+                planning_limit: Some(budget),
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  {} budget={} → {:?}", label, budget, path);
+        }
+    }
+
+    // ── Sweep 12: LRTA* heuristic learning ─────────────────
+    println!("\n[Sweep] LRTA* lookahead depth: 1, 3, 5 (learning_delta traces convergence)");
+    for name in ["wall_gap", "u_trap"] {
+        let map = Grid::load(format!("maps/{}.map", name))?;
+        for depth in [1, 3, 5] {
+            let training = ExperimentConfig {
+                episodes: 50,
+                map: Some(map.clone()),
+                planning_limit: Some(depth),
+                ..Default::default()
+            };
+            let (logs, table) = train_lrta(&training);
+            let log_path = format!("experiments/data/{}_lrta_{}_d{}_training.csv", timestamp, name, depth);
+            let table_path = format!("experiments/data/{}_heuristic_{}_d{}.csv", timestamp, name, depth);
+            write_episode_logs_csv(&log_path, &logs)?;
+            table.save(&table_path)?;
+            println!("  {} depth={} ({} cells learned) → {} / {}", name, depth, table.len(), log_path, table_path);
+        }
+    }
+
+    // ── Sweep 13: Utility AI noise models ─────────────────
+    println!("\n[Sweep] Utility AI, random moves vs. softmax selection with noise: 0.05, 0.1, 0.3");
+    for noise in [0.05, 0.1, 0.3] {
+        for (selection, label) in [(Selection::Greedy, "Greedy"), (Selection::Softmax, "Softmax")] {
+            let config = ExperimentConfig {
+                agent_type: AgentType::Utility(selection),
+                episodes: 100,
+                obstacle_density: 0.2,
+                noise,
+                memory_capacity: 20,
+                ..Default::default()
+            };
+            let path = run_batch_and_save(&config)?;
+            println!("  Utility {} noise={:.2} → {:?}", label, noise, path);
+        }
+    }
+
     println!("\n{}", "=".repeat(50));
     println!("All sweeps completed.");
     Ok(())
@@ -1,7 +1,26 @@
 //! 3D viewer for the Cognitive Grid simulation using Bevy 0.15.
 //!
-//! Run with: `cargo run --bin viewer`
+//! Run with: `cargo run --bin viewer`, or `cargo run --bin viewer -- maps/u_trap.map`
+//! to load a fixed layout instead of random obstacles. Add
+//! `--qtable <file>` to show a Q-learning agent trained by `run_experiments`.
 
-fn main() {
-    cognitive_grid::vis::run();
+use cognitive_grid::agents::qlearning::QTable;
+use cognitive_grid::engine::world::Grid;
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut map = None;
+    let mut q_table = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--qtable" => match args.next() {
+                Some(path) => q_table = Some(QTable::load(path)?),
+                None => return Err("--qtable needs a file".into()),
+            },
+            path => map = Some(Grid::load(path)?),
+        }
+    }
+    cognitive_grid::vis::run(map, q_table);
+    Ok(())
 }
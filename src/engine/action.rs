@@ -0,0 +1,206 @@
+//! The agent/world interface: agents see an `Observation` and return an
+//! `Action`; the world validates it against the grid and applies it to the
+//! agent's `Body`, charging terrain energy costs in one place.
+
+use super::grid::Grid;
+use super::position::Position;
+
+/// Energy a fresh body starts with, and the cap for recovery.
+pub const MAX_ENERGY: u32 = 100;
+/// Energy recovered by one `Rest`.
+pub const REST_RECOVERY: u32 = 10;
+/// Energy recovered by `Interact` on the goal cell ("eating").
+pub const GOAL_RECOVERY: u32 = 20;
+
+/// A step direction. North is towards row 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+    NorthEast,
+    SouthEast,
+    SouthWest,
+    NorthWest,
+}
+
+impl Direction {
+    /// The four orthogonal directions.
+    pub const CARDINAL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    /// All eight directions, orthogonal first.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+    ];
+
+    /// `(dx, dy)` of one step in this direction.
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, -1),
+            Direction::SouthEast => (1, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+
+    /// Direction of a single step from `from` to `to`, or `None` if the
+    /// cells are equal or not adjacent.
+    pub fn between(from: Position, to: Position) -> Option<Direction> {
+        let dx = to.x as isize - from.x as isize;
+        let dy = to.y as isize - from.y as isize;
+        Direction::ALL.into_iter().find(|dir| dir.offset() == (dx, dy))
+    }
+
+    /// The cell one step from `pos`, or `None` if it would leave the grid
+    /// on the low side (high-side bounds are checked by the grid).
+    pub fn apply(&self, pos: Position) -> Option<Position> {
+        let (dx, dy) = self.offset();
+        Some(Position {
+            x: pos.x.checked_add_signed(dx)?,
+            y: pos.y.checked_add_signed(dy)?,
+        })
+    }
+}
+
+/// What an agent chooses to do on a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Step to the neighboring cell in this direction.
+    Move(Direction),
+    /// Stay put and recover `REST_RECOVERY` energy.
+    Rest,
+    /// Stay put.
+    Wait,
+    /// Act on the current cell; on a goal this recovers `GOAL_RECOVERY`.
+    Interact,
+}
+
+/// What the world did with an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Moved { from: Position, to: Position },
+    /// The move was illegal (off-grid, blocked, or a forbidden diagonal).
+    Blocked,
+    /// The move was legal, but another agent held or claimed the cell.
+    Collided,
+    Rested,
+    Waited,
+    Interacted,
+}
+
+/// Everything an agent is told about the world on a tick.
+#[derive(Debug, Clone, Copy)]
+pub struct Observation<'a> {
+    pub grid: &'a Grid,
+    pub position: Position,
+    pub energy: u32,
+    /// World tick this decision is for.
+    pub tick: usize,
+    /// The goal this agent should head for now (see `GoalProgress::target`);
+    /// `grid.goal` on single-goal grids.
+    pub goal: Position,
+}
+
+/// The world-owned physical state of an agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Body {
+    pub position: Position,
+    pub energy: u32,
+}
+
+impl Body {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            energy: MAX_ENERGY,
+        }
+    }
+
+    /// Validate `action` against `grid` and apply it.
+    ///
+    /// Moves must go to a cell in `Grid::walkable_neighbors`, so they obey
+    /// the grid's connectivity and corner rule; entering a cell costs its
+    /// terrain energy. Illegal moves leave the body untouched.
+    pub fn apply(&mut self, grid: &Grid, action: Action) -> Outcome {
+        match action {
+            Action::Move(dir) => {
+                let from = self.position;
+                let Some(to) = dir.apply(from) else {
+                    return Outcome::Blocked;
+                };
+                if !grid.walkable_neighbors(from.x, from.y).contains(&(to.x, to.y)) {
+                    return Outcome::Blocked;
+                }
+                self.position = to;
+                self.energy = self.energy.saturating_sub(grid.terrain(to.x, to.y).energy_cost());
+                Outcome::Moved { from, to }
+            }
+            Action::Rest => {
+                self.energy = (self.energy + REST_RECOVERY).min(MAX_ENERGY);
+                Outcome::Rested
+            }
+            Action::Wait => Outcome::Waited,
+            Action::Interact if grid.is_goal(self.position) => {
+                self.energy = (self.energy + GOAL_RECOVERY).min(MAX_ENERGY);
+                Outcome::Interacted
+            }
+            // Nothing to interact with elsewhere.
+            Action::Interact => Outcome::Waited,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::grid::{Connectivity, CornerRule};
+    use crate::engine::terrain::Terrain;
+
+    #[test]
+    fn moves_are_validated_and_charged() {
+        let mut grid = Grid::with_obstacles(3, 3, Position { x: 2, y: 2 }, &[(1, 0)]);
+        grid.set_terrain(0, 1, Terrain::Mud);
+        let mut body = Body::new(grid.start);
+
+        assert_eq!(body.apply(&grid, Action::Move(Direction::North)), Outcome::Blocked);
+        assert_eq!(body.apply(&grid, Action::Move(Direction::East)), Outcome::Blocked);
+        assert_eq!(body.apply(&grid, Action::Move(Direction::SouthEast)), Outcome::Blocked);
+        assert_eq!(body.energy, MAX_ENERGY);
+
+        let to = Position { x: 0, y: 1 };
+        assert_eq!(
+            body.apply(&grid, Action::Move(Direction::South)),
+            Outcome::Moved { from: grid.start, to }
+        );
+        assert_eq!(body.energy, MAX_ENERGY - Terrain::Mud.energy_cost());
+
+        grid.connectivity = Connectivity::Eight(CornerRule::NoCut);
+        assert!(matches!(body.apply(&grid, Action::Move(Direction::SouthEast)), Outcome::Moved { .. }));
+    }
+
+    #[test]
+    fn rest_and_interact_recover_energy_up_to_the_cap() {
+        let grid = Grid::new(2, 1, Position { x: 1, y: 0 });
+        let mut body = Body { position: grid.start, energy: 95 };
+
+        assert_eq!(body.apply(&grid, Action::Rest), Outcome::Rested);
+        assert_eq!(body.energy, MAX_ENERGY);
+        body.energy = 50;
+        assert_eq!(body.apply(&grid, Action::Interact), Outcome::Waited);
+        body.position = grid.goal;
+        assert_eq!(body.apply(&grid, Action::Interact), Outcome::Interacted);
+        assert_eq!(body.energy, 50 + GOAL_RECOVERY);
+    }
+}
@@ -0,0 +1,350 @@
+//! Connectivity analysis: component labelling, BFS distances and
+//! start→goal solvability checks for `Grid`.
+
+use std::collections::VecDeque;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use super::goals::best_goal_route;
+use super::grid::Grid;
+use super::position::Position;
+
+/// Connected-component label for every cell (`None` for walls).
+pub struct Components {
+    labels: Vec<Vec<Option<usize>>>,
+    count: usize,
+}
+
+impl Components {
+    /// Component id of a cell, or `None` if it is blocked/out of bounds.
+    pub fn label(&self, pos: Position) -> Option<usize> {
+        self.labels.get(pos.y).and_then(|row| row.get(pos.x)).copied().flatten()
+    }
+
+    /// Number of distinct walkable regions.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Whether two cells lie in the same walkable region.
+    pub fn connected(&self, a: Position, b: Position) -> bool {
+        matches!((self.label(a), self.label(b)), (Some(la), Some(lb)) if la == lb)
+    }
+}
+
+/// How layout generation treats start/goal pairs that aren't connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solvability {
+    /// Keep whatever was generated, solvable or not.
+    Any,
+    /// Regenerate up to `max_attempts` times until solvable; the last
+    /// attempt is kept if none succeed.
+    Reject { max_attempts: u32 },
+    /// Knock out the fewest walls needed to join start and goal.
+    Repair,
+}
+
+impl Grid {
+    /// Label walkable cells by connected component (flood fill), moving
+    /// between cells the way agents can under the grid's connectivity.
+    pub fn components(&self) -> Components {
+        let mut labels = vec![vec![None; self.width]; self.height];
+        let mut count = 0;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if labels[y][x].is_some() || !self.is_walkable(x, y) {
+                    continue;
+                }
+                let mut queue = VecDeque::from([(x, y)]);
+                labels[y][x] = Some(count);
+                while let Some((cx, cy)) = queue.pop_front() {
+                    for (nx, ny) in self.walkable_neighbors(cx, cy) {
+                        if labels[ny][nx].is_none() {
+                            labels[ny][nx] = Some(count);
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+                count += 1;
+            }
+        }
+
+        Components { labels, count }
+    }
+
+    /// Fewest moves from `from` to `to`, or `None` if unreachable.
+    pub fn shortest_path_len(&self, from: Position, to: Position) -> Option<usize> {
+        if !self.is_walkable(from.x, from.y) || !self.is_walkable(to.x, to.y) {
+            return None;
+        }
+
+        let mut dist = vec![vec![usize::MAX; self.width]; self.height];
+        let mut queue = VecDeque::from([(from.x, from.y)]);
+        dist[from.y][from.x] = 0;
+
+        while let Some((x, y)) = queue.pop_front() {
+            if (x, y) == (to.x, to.y) {
+                return Some(dist[y][x]);
+            }
+            for (nx, ny) in self.walkable_neighbors(x, y) {
+                if dist[ny][nx] == usize::MAX {
+                    dist[ny][nx] = dist[y][x] + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Fewest moves from `from` to every cell (`None` where unreachable).
+    pub fn distances_from(&self, from: Position) -> Vec<Vec<Option<usize>>> {
+        let mut dist = vec![vec![None; self.width]; self.height];
+        if !self.is_walkable(from.x, from.y) {
+            return dist;
+        }
+        let mut queue = VecDeque::from([(from.x, from.y)]);
+        dist[from.y][from.x] = Some(0);
+
+        while let Some((x, y)) = queue.pop_front() {
+            let d = dist[y][x].unwrap_or(0);
+            for (nx, ny) in self.walkable_neighbors(x, y) {
+                if dist[ny][nx].is_none() {
+                    dist[ny][nx] = Some(d + 1);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        dist
+    }
+
+    /// Optimal number of moves from start to finishing under the grid's
+    /// `goal_mode` (nearest goal, or the best tour of all goals), or
+    /// `None` if unsolvable.
+    pub fn optimal_path_len(&self) -> Option<usize> {
+        best_goal_route(self.start, &self.goals(), self.goal_mode, |a, b| self.shortest_path_len(a, b))
+    }
+
+    /// Whether the goal is reachable from the start.
+    pub fn is_solvable(&self) -> bool {
+        self.optimal_path_len().is_some()
+    }
+
+    /// Pick a random walkable start and `goals` distinct goal cells, each
+    /// reachable from the start in at least `min_distance` moves.
+    ///
+    /// Tries a handful of starts; returns false (leaving the grid as it
+    /// was) if no start has enough cells far enough away.
+    pub fn place_start_and_goals<R: Rng + ?Sized>(
+        &mut self,
+        goals: usize,
+        min_distance: usize,
+        rng: &mut R,
+    ) -> bool {
+        let goals = goals.max(1);
+        let floor: Vec<Position> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Position { x, y }))
+            .filter(|p| self.is_walkable(p.x, p.y))
+            .collect();
+
+        for _ in 0..32 {
+            let Some(&start) = floor.choose(rng) else {
+                return false;
+            };
+            let dist = self.distances_from(start);
+            let far: Vec<Position> = floor
+                .iter()
+                .copied()
+                .filter(|p| dist[p.y][p.x].is_some_and(|d| d >= min_distance.max(1)))
+                .collect();
+            if far.len() < goals {
+                continue;
+            }
+            let mut picked = far.choose_multiple(rng, goals).copied();
+            self.start = start;
+            self.goal = picked.next().unwrap_or(start);
+            self.extra_goals = picked.collect();
+            return true;
+        }
+        false
+    }
+
+    /// Open the fewest walls needed to connect start and every goal.
+    ///
+    /// Runs a 0-1 BFS where stepping onto a wall costs 1 and onto floor
+    /// costs 0, then clears the walls along the cheapest route. Returns
+    /// the number of cells opened (0 if the grid was already solvable).
+    pub fn repair_connectivity(&mut self) -> usize {
+        let start = self.start;
+        self.goals().into_iter().map(|goal| self.repair_between(start, goal)).sum()
+    }
+
+    fn repair_between(&mut self, start: Position, goal: Position) -> usize {
+        if start.x >= self.width || start.y >= self.height || goal.x >= self.width || goal.y >= self.height {
+            return 0;
+        }
+
+        let mut cost = vec![vec![usize::MAX; self.width]; self.height];
+        let mut came_from = vec![vec![None; self.width]; self.height];
+        let mut deque = VecDeque::from([(start.x, start.y)]);
+        cost[start.y][start.x] = usize::from(!self.is_walkable(start.x, start.y));
+
+        while let Some((x, y)) = deque.pop_front() {
+            if (x, y) == (goal.x, goal.y) {
+                break;
+            }
+            let mut neighbors = Vec::with_capacity(4);
+            if x > 0 {
+                neighbors.push((x - 1, y));
+            }
+            if x + 1 < self.width {
+                neighbors.push((x + 1, y));
+            }
+            if y > 0 {
+                neighbors.push((x, y - 1));
+            }
+            if y + 1 < self.height {
+                neighbors.push((x, y + 1));
+            }
+
+            for (nx, ny) in neighbors {
+                let wall = !self.is_walkable(nx, ny);
+                let next = cost[y][x] + usize::from(wall);
+                if next < cost[ny][nx] {
+                    cost[ny][nx] = next;
+                    came_from[ny][nx] = Some((x, y));
+                    if wall {
+                        deque.push_back((nx, ny));
+                    } else {
+                        deque.push_front((nx, ny));
+                    }
+                }
+            }
+        }
+
+        let mut opened = 0;
+        let mut cell = Some((goal.x, goal.y));
+        while let Some((x, y)) = cell {
+            if !self.is_walkable(x, y) {
+                self.set_walkable(x, y, true);
+                opened += 1;
+            }
+            cell = came_from[y][x];
+        }
+        opened
+    }
+}
+
+/// Build a layout with `generate`, applying the given solvability mode.
+pub fn generate_with<R, F>(mode: Solvability, rng: &mut R, mut generate: F) -> Grid
+where
+    R: Rng + ?Sized,
+    F: FnMut(&mut R) -> Grid,
+{
+    generate_checked(mode, rng, |rng| (generate(rng), true)).0
+}
+
+/// Like `generate_with`, but `generate` also says whether the layout is
+/// usable (e.g. whether a start and goals could be placed on it). Under
+/// `Reject` unusable layouts are retried like unsolvable ones. Returns
+/// the final layout and whether it was usable.
+pub fn generate_checked<R, F>(mode: Solvability, rng: &mut R, mut generate: F) -> (Grid, bool)
+where
+    R: Rng + ?Sized,
+    F: FnMut(&mut R) -> (Grid, bool),
+{
+    match mode {
+        Solvability::Any => generate(rng),
+        Solvability::Reject { max_attempts } => {
+            let (mut grid, mut usable) = generate(rng);
+            for _ in 1..max_attempts {
+                if usable && grid.is_solvable() {
+                    break;
+                }
+                (grid, usable) = generate(rng);
+            }
+            (grid, usable)
+        }
+        Solvability::Repair => {
+            let (mut grid, usable) = generate(rng);
+            grid.repair_connectivity();
+            (grid, usable)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mapgen::MapGenerator;
+    use crate::engine::rng::seeded;
+
+    fn walled_off() -> Grid {
+        // Vertical wall at x=2 splits the grid in two.
+        let goal = Position { x: 4, y: 4 };
+        let wall: Vec<_> = (0..5).map(|y| (2, y)).collect();
+        Grid::with_obstacles(5, 5, goal, &wall)
+    }
+
+    #[test]
+    fn labels_separate_regions() {
+        let grid = walled_off();
+        let components = grid.components();
+        assert_eq!(components.count(), 2);
+        assert!(!components.connected(grid.start, grid.goal));
+        assert!(components.connected(grid.start, Position { x: 1, y: 4 }));
+        assert_eq!(components.label(Position { x: 2, y: 0 }), None);
+        assert!(!grid.is_solvable());
+    }
+
+    #[test]
+    fn shortest_path_len_counts_moves() {
+        let grid = Grid::new(5, 3, Position { x: 4, y: 2 });
+        assert_eq!(grid.optimal_path_len(), Some(6));
+    }
+
+    #[test]
+    fn repair_opens_minimal_gap() {
+        let mut grid = walled_off();
+        assert_eq!(grid.repair_connectivity(), 1);
+        assert_eq!(grid.optimal_path_len(), Some(8));
+        assert_eq!(grid.repair_connectivity(), 0);
+    }
+
+    #[test]
+    fn sampled_goals_are_far_enough_and_solvable() {
+        use crate::engine::goals::GoalMode;
+
+        let mut grid = Grid::with_obstacles(8, 6, Position { x: 7, y: 5 }, &[(3, 1), (3, 2), (3, 3), (3, 4)]);
+        let mut rng = seeded(4);
+        assert!(grid.place_start_and_goals(3, 5, &mut rng));
+        assert_eq!(grid.goals().len(), 3);
+        for goal in grid.goals() {
+            assert!(grid.shortest_path_len(grid.start, goal).is_some_and(|d| d >= 5));
+        }
+        grid.goal_mode = GoalMode::All;
+        let tour = grid.optimal_path_len().unwrap();
+        grid.goal_mode = GoalMode::Any;
+        assert!(tour > grid.optimal_path_len().unwrap());
+
+        assert!(!grid.place_start_and_goals(1, 100, &mut rng));
+    }
+
+    #[test]
+    fn reject_and_repair_modes_yield_solvable_grids() {
+        let cave = MapGenerator::Cave { fill: 0.5, iterations: 3 };
+        for seed in 0..10 {
+            let mut rng = seeded(seed);
+            let grid = generate_with(Solvability::Repair, &mut rng, |rng| cave.generate(20, 12, rng));
+            assert!(grid.is_solvable(), "repair seed {}", seed);
+
+            let scatter = MapGenerator::Scatter { density: 0.3 };
+            let grid = generate_with(Solvability::Reject { max_attempts: 100 }, &mut rng, |rng| {
+                scatter.generate(10, 5, rng)
+            });
+            assert!(grid.is_solvable(), "reject seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Obstacle dynamics: cells that become blocked or free while an episode
+//! runs (periodic doors, wandering blockers, short-lived walls).
+//!
+//! Every dynamic element remembers the terrain it covered and restores it
+//! when it clears, so the underlying layout is never lost. Start, goal and
+//! any occupied cells are never blocked.
+
+use rand::Rng;
+
+use super::grid::Grid;
+use super::position::Position;
+use super::terrain::Terrain;
+
+/// A source of changing obstacles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObstacleRule {
+    /// A cell that is open for the first `open_for` ticks of every
+    /// `period` ticks and blocked for the rest.
+    Door { pos: Position, period: u32, open_for: u32 },
+    /// `count` blockers placed on random cells, each stepping to a random
+    /// neighbor with probability `move_chance` per tick.
+    Wanderers { count: usize, move_chance: f32 },
+    /// With probability `chance` per tick, a wall appears on a random cell
+    /// and disappears again after `lifetime` ticks.
+    SpawnWalls { chance: f32, lifetime: u32 },
+}
+
+/// One blocked-or-free cell and the terrain it hides while blocked.
+#[derive(Debug, Clone)]
+struct Blocker {
+    pos: Position,
+    covered: Option<Terrain>,
+}
+
+impl Blocker {
+    fn new(pos: Position) -> Self {
+        Self { pos, covered: None }
+    }
+
+    /// Turn the cell into a wall if it is currently walkable.
+    fn block(&mut self, grid: &mut Grid) -> bool {
+        if self.covered.is_some() || !grid.is_walkable(self.pos.x, self.pos.y) {
+            return false;
+        }
+        self.covered = Some(grid.terrain(self.pos.x, self.pos.y));
+        grid.set_terrain(self.pos.x, self.pos.y, Terrain::Wall);
+        true
+    }
+
+    /// Restore the covered terrain, if this blocker is active.
+    fn clear(&mut self, grid: &mut Grid) -> bool {
+        match self.covered.take() {
+            Some(terrain) => {
+                grid.set_terrain(self.pos.x, self.pos.y, terrain);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Element {
+    Door { blocker: Blocker, period: u32, open_for: u32 },
+    Wanderer { blocker: Blocker, move_chance: f32 },
+    Temporary { blocker: Blocker, expires: u32 },
+}
+
+/// Runtime state of a set of `ObstacleRule`s applied to one grid.
+#[derive(Debug, Clone, Default)]
+pub struct ObstacleDynamics {
+    spawners: Vec<(f32, u32)>,
+    elements: Vec<Element>,
+    tick: u32,
+}
+
+impl ObstacleDynamics {
+    /// Set up `rules` on `grid`, placing wanderers on random free cells
+    /// and never blocking `occupied` cells (agent positions), the start
+    /// or any goal.
+    pub fn new<R: Rng + ?Sized>(
+        rules: &[ObstacleRule],
+        grid: &mut Grid,
+        occupied: &[Position],
+        rng: &mut R,
+    ) -> Self {
+        let mut dynamics = Self::default();
+        let mut protected = grid.goals();
+        protected.push(grid.start);
+        protected.extend_from_slice(occupied);
+
+        for rule in rules {
+            match *rule {
+                ObstacleRule::Door { pos, period, open_for } => {
+                    dynamics.elements.push(Element::Door {
+                        blocker: Blocker::new(pos),
+                        period: period.max(1),
+                        open_for,
+                    });
+                }
+                ObstacleRule::Wanderers { count, move_chance } => {
+                    for _ in 0..count {
+                        let Some(pos) = random_free_cell(grid, &protected, rng) else {
+                            break;
+                        };
+                        let mut blocker = Blocker::new(pos);
+                        blocker.block(grid);
+                        dynamics.elements.push(Element::Wanderer { blocker, move_chance });
+                    }
+                }
+                ObstacleRule::SpawnWalls { chance, lifetime } => {
+                    dynamics.spawners.push((chance, lifetime));
+                }
+            }
+        }
+
+        dynamics.apply_doors(grid, &protected);
+        dynamics
+    }
+
+    /// Whether no dynamic obstacles are configured.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty() && self.spawners.is_empty()
+    }
+
+    /// Advance one tick, never blocking `occupied` cells (agent positions),
+    /// the start or any goal. Returns how many cells changed.
+    pub fn step<R: Rng + ?Sized>(&mut self, grid: &mut Grid, occupied: &[Position], rng: &mut R) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        self.tick += 1;
+
+        let mut protected = grid.goals();
+        protected.push(grid.start);
+        protected.extend_from_slice(occupied);
+        let mut changed = 0;
+
+        // Expire temporary walls.
+        let tick = self.tick;
+        self.elements.retain_mut(|element| match element {
+            Element::Temporary { blocker, expires } if *expires <= tick => {
+                changed += usize::from(blocker.clear(grid));
+                false
+            }
+            _ => true,
+        });
+
+        // Move wanderers.
+        for element in &mut self.elements {
+            let Element::Wanderer { blocker, move_chance } = element else {
+                continue;
+            };
+            if rng.r#gen::<f32>() >= *move_chance {
+                continue;
+            }
+            let options: Vec<Position> = grid
+                .walkable_neighbors(blocker.pos.x, blocker.pos.y)
+                .into_iter()
+                .map(|(x, y)| Position { x, y })
+                .filter(|pos| !protected.contains(pos))
+                .collect();
+            if options.is_empty() {
+                continue;
+            }
+            let next = options[rng.gen_range(0..options.len())];
+            changed += usize::from(blocker.clear(grid));
+            blocker.pos = next;
+            changed += usize::from(blocker.block(grid));
+        }
+
+        // Spawn new temporary walls.
+        for &(chance, lifetime) in &self.spawners {
+            if rng.r#gen::<f32>() >= chance {
+                continue;
+            }
+            let pos = Position {
+                x: rng.gen_range(0..grid.width),
+                y: rng.gen_range(0..grid.height),
+            };
+            if protected.contains(&pos) {
+                continue;
+            }
+            let mut blocker = Blocker::new(pos);
+            if blocker.block(grid) {
+                changed += 1;
+                self.elements.push(Element::Temporary {
+                    blocker,
+                    expires: tick + lifetime,
+                });
+            }
+        }
+
+        changed + self.apply_doors(grid, &protected)
+    }
+
+    /// Clear any dynamic element blocking one of `cells`, e.g. when agents
+    /// are moved onto them after set-up. Returns how many cells changed.
+    pub fn unblock(&mut self, grid: &mut Grid, cells: &[Position]) -> usize {
+        self.elements
+            .iter_mut()
+            .map(|element| match element {
+                Element::Door { blocker, .. }
+                | Element::Wanderer { blocker, .. }
+                | Element::Temporary { blocker, .. } => blocker,
+            })
+            .filter(|blocker| cells.contains(&blocker.pos))
+            .map(|blocker| usize::from(blocker.clear(grid)))
+            .sum()
+    }
+
+    /// Cells currently blocked by a dynamic element.
+    pub fn blocked_cells(&self) -> Vec<Position> {
+        self.elements
+            .iter()
+            .map(|element| match element {
+                Element::Door { blocker, .. }
+                | Element::Wanderer { blocker, .. }
+                | Element::Temporary { blocker, .. } => blocker,
+            })
+            .filter(|blocker| blocker.covered.is_some())
+            .map(|blocker| blocker.pos)
+            .collect()
+    }
+
+    /// Open or close every door for the current tick. A door that should
+    /// close on a protected or already-blocked cell waits until it is free.
+    fn apply_doors(&mut self, grid: &mut Grid, protected: &[Position]) -> usize {
+        let mut changed = 0;
+        for element in &mut self.elements {
+            let Element::Door { blocker, period, open_for } = element else {
+                continue;
+            };
+            if self.tick % *period < *open_for {
+                changed += usize::from(blocker.clear(grid));
+            } else if !protected.contains(&blocker.pos) {
+                changed += usize::from(blocker.block(grid));
+            }
+        }
+        changed
+    }
+}
+
+/// Pick a random walkable cell outside `protected`, giving up after a
+/// bounded number of tries on crowded grids.
+fn random_free_cell<R: Rng + ?Sized>(grid: &Grid, protected: &[Position], rng: &mut R) -> Option<Position> {
+    for _ in 0..100 {
+        let pos = Position {
+            x: rng.gen_range(0..grid.width),
+            y: rng.gen_range(0..grid.height),
+        };
+        if grid.is_walkable(pos.x, pos.y) && !protected.contains(&pos) {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::rng::seeded;
+
+    #[test]
+    fn door_opens_and_closes_on_its_period() {
+        let mut grid = Grid::new(5, 1, Position { x: 4, y: 0 });
+        let door = Position { x: 2, y: 0 };
+        let mut rng = seeded(0);
+        let rules = [ObstacleRule::Door { pos: door, period: 4, open_for: 2 }];
+        let mut dynamics = ObstacleDynamics::new(&rules, &mut grid, &[], &mut rng);
+
+        let mut open = vec![grid.is_walkable(door.x, door.y)];
+        for _ in 0..7 {
+            dynamics.step(&mut grid, &[], &mut rng);
+            open.push(grid.is_walkable(door.x, door.y));
+        }
+        assert_eq!(open, vec![true, true, false, false, true, true, false, false]);
+
+        // An agent standing in the doorway keeps it from closing.
+        dynamics.step(&mut grid, &[], &mut rng);
+        dynamics.step(&mut grid, &[], &mut rng);
+        dynamics.step(&mut grid, &[door], &mut rng);
+        assert!(grid.is_walkable(door.x, door.y));
+    }
+
+    #[test]
+    fn wanderers_and_spawned_walls_restore_terrain() {
+        let mut grid = Grid::new(8, 8, Position { x: 7, y: 7 });
+        grid.set_terrain(3, 3, Terrain::Mud);
+        let mut rng = seeded(3);
+        let rules = [
+            ObstacleRule::Wanderers { count: 4, move_chance: 1.0 },
+            ObstacleRule::SpawnWalls { chance: 0.5, lifetime: 3 },
+        ];
+        let mut dynamics = ObstacleDynamics::new(&rules, &mut grid, &[], &mut rng);
+        let occupied = [Position { x: 1, y: 1 }];
+
+        for _ in 0..50 {
+            dynamics.step(&mut grid, &occupied, &mut rng);
+            assert!(grid.is_walkable(1, 1) && grid.is_walkable(0, 0) && grid.is_walkable(7, 7));
+            assert_eq!(grid.obstacle_positions().len(), dynamics.blocked_cells().len());
+        }
+
+        // Clearing every blocker brings back the original layout.
+        for element in &mut dynamics.elements {
+            let (Element::Door { blocker, .. } | Element::Wanderer { blocker, .. } | Element::Temporary { blocker, .. }) =
+                element;
+            blocker.clear(&mut grid);
+        }
+        assert!(grid.obstacle_positions().is_empty());
+        assert_eq!(grid.terrain(3, 3), Terrain::Mud);
+    }
+}
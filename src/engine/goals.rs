@@ -0,0 +1,211 @@
+//! Goal sets: a grid may have several goal cells, and an agent is done
+//! when it reaches any one of them or once it has visited them all.
+
+use std::ops::Add;
+
+use super::grid::Grid;
+use super::position::Position;
+
+/// What counts as finishing on a grid with several goals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GoalMode {
+    /// Reaching any goal finishes the episode.
+    #[default]
+    Any,
+    /// Every goal must be visited, in any order.
+    All,
+}
+
+impl GoalMode {
+    /// Name used in map headers and logs.
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoalMode::Any => "any",
+            GoalMode::All => "all",
+        }
+    }
+
+    /// Inverse of [`GoalMode::label`].
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "any" => Some(GoalMode::Any),
+            "all" => Some(GoalMode::All),
+            _ => None,
+        }
+    }
+}
+
+/// Random start/goal placement for experiments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoalSampling {
+    /// Number of goal cells to place (at least 1).
+    pub goals: usize,
+    /// Minimum number of moves between the start and every goal.
+    pub min_distance: usize,
+    pub mode: GoalMode,
+}
+
+/// Which of a grid's goals (in `Grid::goals` order) one agent has visited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoalProgress {
+    visited: Vec<bool>,
+    /// Index of the goal currently being headed for.
+    current: Option<usize>,
+}
+
+impl GoalProgress {
+    /// Fresh progress for an agent starting at `start` (which counts as a
+    /// visit if it is a goal).
+    pub fn new(grid: &Grid, start: Position) -> Self {
+        let mut progress = Self {
+            visited: vec![false; grid.goals().len()],
+            current: None,
+        };
+        progress.visit(grid, start);
+        progress
+    }
+
+    /// Record standing on `pos`. Returns true if that reached a goal not
+    /// visited before.
+    pub fn visit(&mut self, grid: &Grid, pos: Position) -> bool {
+        let mut newly = false;
+        for (visited, goal) in self.visited.iter_mut().zip(grid.goals()) {
+            if goal == pos && !*visited {
+                *visited = true;
+                newly = true;
+            }
+        }
+        newly
+    }
+
+    /// Number of distinct goals visited so far.
+    pub fn reached(&self) -> usize {
+        self.visited.iter().filter(|&&v| v).count()
+    }
+
+    /// Whether the agent has finished under `mode`.
+    pub fn is_complete(&self, mode: GoalMode) -> bool {
+        match mode {
+            GoalMode::Any => self.reached() > 0,
+            GoalMode::All => self.reached() == self.visited.len(),
+        }
+    }
+
+    /// The goal an agent at `pos` should head for.
+    ///
+    /// The nearest goal (by `Grid::distance`) is chosen and then kept, so
+    /// the agent doesn't flip between goals as it detours; under
+    /// `GoalMode::All` a new one is chosen from the unvisited goals each
+    /// time the current one is reached.
+    pub fn target(&mut self, grid: &Grid, pos: Position) -> Position {
+        let goals = grid.goals();
+        let pending = grid.goal_mode == GoalMode::All && !self.is_complete(GoalMode::All);
+        let keep = self
+            .current
+            .is_some_and(|i| i < goals.len() && !(pending && self.visited[i]));
+        if !keep {
+            self.current = (0..goals.len())
+                .filter(|&i| !pending || !self.visited[i])
+                .min_by(|&a, &b| grid.distance(pos, goals[a]).total_cmp(&grid.distance(pos, goals[b])));
+        }
+        self.current.map_or(grid.goal, |i| goals[i])
+    }
+}
+
+/// Length of the best route from `start` that satisfies `mode`: the
+/// nearest goal for `Any`, the cheapest visiting order for `All`.
+///
+/// `dist` gives the cost between two cells, or `None` if unreachable.
+/// `All` tries every order, so keep goal sets small.
+pub fn best_goal_route<T, F>(start: Position, goals: &[Position], mode: GoalMode, mut dist: F) -> Option<T>
+where
+    T: Copy + Default + PartialOrd + Add<Output = T>,
+    F: FnMut(Position, Position) -> Option<T>,
+{
+    match mode {
+        GoalMode::Any => goals
+            .iter()
+            .filter_map(|&goal| dist(start, goal))
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)),
+        GoalMode::All => {
+            let from_start: Vec<Option<T>> = goals.iter().map(|&g| dist(start, g)).collect();
+            let between: Vec<Vec<Option<T>>> = goals
+                .iter()
+                .map(|&a| goals.iter().map(|&b| dist(a, b)).collect())
+                .collect();
+            let mut visited = vec![false; goals.len()];
+            cheapest_order(&from_start, &between, &mut visited, None)
+        }
+    }
+}
+
+/// Exhaustive search over the remaining goals; `last` is the goal we are
+/// standing on (`None` = still at the start).
+fn cheapest_order<T>(
+    from_start: &[Option<T>],
+    between: &[Vec<Option<T>>],
+    visited: &mut [bool],
+    last: Option<usize>,
+) -> Option<T>
+where
+    T: Copy + Default + PartialOrd + Add<Output = T>,
+{
+    let mut best: Option<T> = None;
+    let mut any_left = false;
+    for next in 0..visited.len() {
+        if visited[next] {
+            continue;
+        }
+        any_left = true;
+        let step = match last {
+            Some(from) => between[from][next],
+            None => from_start[next],
+        };
+        let Some(step) = step else { continue };
+        visited[next] = true;
+        if let Some(rest) = cheapest_order(from_start, between, visited, Some(next)) {
+            let total = step + rest;
+            if best.is_none_or(|b| total < b) {
+                best = Some(total);
+            }
+        }
+        visited[next] = false;
+    }
+    if !any_left {
+        return Some(T::default());
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_targets_nearest_unvisited_goal() {
+        let mut grid = Grid::new(9, 1, Position { x: 8, y: 0 });
+        grid.extra_goals = vec![Position { x: 2, y: 0 }];
+        grid.goal_mode = GoalMode::All;
+        let mut progress = GoalProgress::new(&grid, grid.start);
+
+        assert_eq!(progress.target(&grid, grid.start), Position { x: 2, y: 0 });
+        // Sticks with its choice even where the other goal is nearer.
+        assert_eq!(progress.target(&grid, Position { x: 7, y: 0 }), Position { x: 2, y: 0 });
+        assert!(progress.visit(&grid, Position { x: 2, y: 0 }));
+        assert!(!progress.is_complete(GoalMode::All));
+        assert!(progress.is_complete(GoalMode::Any));
+        assert_eq!(progress.target(&grid, Position { x: 2, y: 0 }), grid.goal);
+    }
+
+    #[test]
+    fn best_route_visits_goals_in_cheapest_order() {
+        let start = Position { x: 5, y: 0 };
+        let goals = [Position { x: 0, y: 0 }, Position { x: 7, y: 0 }];
+        let line = |a: Position, b: Position| Some(a.x.abs_diff(b.x));
+
+        assert_eq!(best_goal_route(start, &goals, GoalMode::Any, line), Some(2));
+        // 5 → 7 → 0 (2 + 7) beats 5 → 0 → 7 (5 + 7).
+        assert_eq!(best_goal_route(start, &goals, GoalMode::All, line), Some(9));
+        assert_eq!(best_goal_route(start, &goals, GoalMode::All, |_, _| None::<usize>), None);
+    }
+}
@@ -1,22 +1,97 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use super::goals::GoalMode;
 use super::position::Position;
+use super::terrain::Terrain;
+
+/// When a diagonal step may pass between the two orthogonal cells it cuts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CornerRule {
+    /// Diagonals are always allowed, even squeezing between two walls.
+    Allow,
+    /// Forbidden only when both orthogonal cells are blocked.
+    NoSqueeze,
+    /// Forbidden when either orthogonal cell is blocked.
+    NoCut,
+}
+
+/// Movement neighbourhood shared by planners and agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Left/right/up/down only.
+    #[default]
+    Four,
+    /// Orthogonal plus diagonal moves; diagonals cost √2 × terrain cost.
+    Eight(CornerRule),
+}
+
+impl CornerRule {
+    /// Name used in map headers and logs.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CornerRule::Allow => "allow",
+            CornerRule::NoSqueeze => "no_squeeze",
+            CornerRule::NoCut => "no_cut",
+        }
+    }
+
+    /// Inverse of [`CornerRule::label`].
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "allow" => Some(CornerRule::Allow),
+            "no_squeeze" => Some(CornerRule::NoSqueeze),
+            "no_cut" => Some(CornerRule::NoCut),
+            _ => None,
+        }
+    }
+}
+
+impl Connectivity {
+    /// Short label for logs, e.g. `4` or `8/no_cut`.
+    pub fn label(&self) -> String {
+        match self {
+            Connectivity::Four => "4".to_string(),
+            Connectivity::Eight(rule) => format!("8/{}", rule.label()),
+        }
+    }
+}
 
-/// A simple 2D grid with a single goal cell.
-#[derive(Clone)]
+/// A simple 2D grid with a start cell and one or more goal cells.
+#[derive(Clone, Debug)]
 pub struct Grid {
     pub width: usize,
     pub height: usize,
+    pub start: Position,
+    /// The primary goal; single-goal code only ever looks at this one.
     pub goal: Position,
-    tiles: Vec<Vec<bool>>,
+    /// Further goal cells, if any (see `goals`).
+    pub extra_goals: Vec<Position>,
+    /// Whether agents must reach any goal or visit all of them.
+    pub goal_mode: GoalMode,
+    /// Movement neighbourhood used by every agent and planner.
+    pub connectivity: Connectivity,
+    /// Free-form header entries carried by map files (e.g. `name`).
+    pub metadata: BTreeMap<String, String>,
+    tiles: Vec<Vec<Terrain>>,
 }
 
 impl Grid {
+    /// Create an open grid. The start cell defaults to (0, 0).
     pub fn new(width: usize, height: usize, goal: Position) -> Self {
-        let tiles = vec![vec![true; width]; height];
+        let tiles = vec![vec![Terrain::Floor; width]; height];
 
         Self {
             width,
             height,
+            start: Position { x: 0, y: 0 },
             goal,
+            extra_goals: Vec::new(),
+            goal_mode: GoalMode::Any,
+            connectivity: Connectivity::Four,
+            metadata: BTreeMap::new(),
             tiles,
         }
     }
@@ -33,65 +108,164 @@ impl Grid {
 
         for &(x, y) in obstacles {
             if x < width && y < height && (x != goal.x || y != goal.y) {
-                grid.tiles[y][x] = false;
+                grid.tiles[y][x] = Terrain::Wall;
             }
         }
 
         grid
     }
 
+    /// Every goal cell: the primary `goal` first, then `extra_goals`.
+    pub fn goals(&self) -> Vec<Position> {
+        std::iter::once(self.goal).chain(self.extra_goals.iter().copied()).collect()
+    }
+
+    /// Whether `pos` is one of the goal cells.
+    pub fn is_goal(&self, pos: Position) -> bool {
+        pos == self.goal || self.extra_goals.contains(&pos)
+    }
+
     /// Return whether the given cell is walkable (in-bounds and not blocked).
     pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        self.terrain(x, y).is_walkable()
+    }
+
+    /// Terrain at a cell. Out-of-bounds cells read as `Wall`.
+    pub fn terrain(&self, x: usize, y: usize) -> Terrain {
         if x >= self.width || y >= self.height {
-            return false;
+            return Terrain::Wall;
         }
         self.tiles[y][x]
     }
 
-    /// Return a random walkable neighbor of `(x, y)`, or `None` if boxed in.
-    pub fn random_walkable_neighbor(&self, x: usize, y: usize) -> Option<(usize, usize)> {
-        use rand::seq::SliceRandom;
+    /// Set a cell's terrain. No-op if out of bounds.
+    pub fn set_terrain(&mut self, x: usize, y: usize, terrain: Terrain) {
+        if x < self.width && y < self.height {
+            self.tiles[y][x] = terrain;
+        }
+    }
+
+    /// Cost of stepping onto `(x, y)`, or `None` if it can't be entered.
+    pub fn move_cost(&self, x: usize, y: usize) -> Option<u32> {
+        self.terrain(x, y).move_cost()
+    }
+
+    /// Cost of a single move between adjacent cells: the terrain cost of
+    /// `to`, times √2 for diagonal moves. `None` if `to` can't be entered.
+    pub fn step_cost(&self, from: (usize, usize), to: (usize, usize)) -> Option<f32> {
+        let cost = self.move_cost(to.0, to.1)? as f32;
+        if from.0 != to.0 && from.1 != to.1 {
+            Some(cost * std::f32::consts::SQRT_2)
+        } else {
+            Some(cost)
+        }
+    }
+
+    /// Admissible distance estimate between two cells under the grid's
+    /// connectivity: Manhattan for 4-connected, octile for 8-connected.
+    pub fn distance(&self, a: Position, b: Position) -> f32 {
+        let dx = a.x.abs_diff(b.x) as f32;
+        let dy = a.y.abs_diff(b.y) as f32;
+        match self.connectivity {
+            Connectivity::Four => dx + dy,
+            Connectivity::Eight(_) => {
+                dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+            }
+        }
+    }
 
-        let mut candidates = Vec::new();
+    /// Walkable neighbors of `(x, y)` under the grid's connectivity:
+    /// left/right/up/down first, then diagonals allowed by the corner rule.
+    pub fn walkable_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut out = Vec::with_capacity(8);
         if x > 0 && self.is_walkable(x - 1, y) {
-            candidates.push((x - 1, y));
+            out.push((x - 1, y));
         }
         if x + 1 < self.width && self.is_walkable(x + 1, y) {
-            candidates.push((x + 1, y));
+            out.push((x + 1, y));
         }
         if y > 0 && self.is_walkable(x, y - 1) {
-            candidates.push((x, y - 1));
+            out.push((x, y - 1));
         }
         if y + 1 < self.height && self.is_walkable(x, y + 1) {
-            candidates.push((x, y + 1));
+            out.push((x, y + 1));
+        }
+
+        if let Connectivity::Eight(rule) = self.connectivity {
+            for (dx, dy) in [(-1i32, -1i32), (1, -1), (-1, 1), (1, 1)] {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || !self.is_walkable(nx as usize, ny as usize) {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                let side_a = self.is_walkable(nx, y);
+                let side_b = self.is_walkable(x, ny);
+                let allowed = match rule {
+                    CornerRule::Allow => true,
+                    CornerRule::NoSqueeze => side_a || side_b,
+                    CornerRule::NoCut => side_a && side_b,
+                };
+                if allowed {
+                    out.push((nx, ny));
+                }
+            }
         }
+        out
+    }
 
-        let mut rng = rand::thread_rng();
-        candidates.choose(&mut rng).copied()
+    /// Return a random walkable neighbor of `(x, y)`, or `None` if boxed in.
+    pub fn random_walkable_neighbor<R: Rng + ?Sized>(
+        &self,
+        x: usize,
+        y: usize,
+        rng: &mut R,
+    ) -> Option<(usize, usize)> {
+        self.walkable_neighbors(x, y).choose(rng).copied()
     }
 
     /// Set a cell's walkability. No-op if out of bounds.
+    /// Blocking makes it a `Wall`; unblocking a wall makes it `Floor`
+    /// (other walkable terrain is left alone).
     pub fn set_walkable(&mut self, x: usize, y: usize, walkable: bool) {
         if x < self.width && y < self.height {
-            self.tiles[y][x] = walkable;
+            if !walkable {
+                self.tiles[y][x] = Terrain::Wall;
+            } else if self.tiles[y][x] == Terrain::Wall {
+                self.tiles[y][x] = Terrain::Floor;
+            }
         }
     }
 
     /// Scatter random obstacles across the grid.
     /// `density` is the fraction of cells to block (0.0–1.0).
-    /// The start cell (0,0) and the goal cell are always kept walkable.
-    pub fn scatter_obstacles(&mut self, density: f32) {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-
+    /// The start and goal cells are always kept walkable.
+    pub fn scatter_obstacles<R: Rng + ?Sized>(&mut self, density: f32, rng: &mut R) {
         for y in 0..self.height {
             for x in 0..self.width {
                 // Never block start or goal.
-                if (x == 0 && y == 0) || (x == self.goal.x && y == self.goal.y) {
+                let pos = Position { x, y };
+                if pos == self.start || self.is_goal(pos) {
                     continue;
                 }
                 if rng.r#gen::<f32>() < density {
-                    self.tiles[y][x] = false;
+                    self.tiles[y][x] = Terrain::Wall;
+                }
+            }
+        }
+    }
+
+    /// Paint `terrain` over a random `density` fraction of floor cells.
+    /// Start and goal are left untouched.
+    pub fn scatter_terrain<R: Rng + ?Sized>(&mut self, terrain: Terrain, density: f32, rng: &mut R) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = Position { x, y };
+                if pos == self.start || self.is_goal(pos) {
+                    continue;
+                }
+                if self.tiles[y][x] == Terrain::Floor && rng.r#gen::<f32>() < density {
+                    self.tiles[y][x] = terrain;
                 }
             }
         }
@@ -102,7 +276,7 @@ impl Grid {
         let mut out = Vec::new();
         for y in 0..self.height {
             for x in 0..self.width {
-                if !self.tiles[y][x] {
+                if self.tiles[y][x] == Terrain::Wall {
                     out.push((x, y));
                 }
             }
@@ -0,0 +1,444 @@
+//! Plain-text map format for loading and saving `Grid` layouts.
+//!
+//! A map file is a header of `key value` lines, a line containing just
+//! `map`, and then one row of tiles per line:
+//!
+//! ```text
+//! name corridor
+//! width 6
+//! height 3
+//! map
+//! S.....
+//! .####.
+//! .....G
+//! ```
+//!
+//! Tiles: `.` floor, `#` wall, `:` sand, `,` mud, `~` water, `S` start,
+//! `G` goal and `+` a start that is also a goal (all on floor). A map may
+//! have several `G`s (or a `+` and `G`s); the first in
+//! reading order is the primary goal, and `goals any|all` says whether
+//! reaching one finishes (default) or all must be visited. `width` and
+//! `height` are optional and validated against the rows when present.
+//! `connectivity 8` enables diagonal moves, with
+//! `corners allow|no_squeeze|no_cut` choosing the corner-cutting rule
+//! (default `no_cut`). Any other header keys are kept in `Grid::metadata`.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::goals::GoalMode;
+use super::grid::{Connectivity, CornerRule, Grid};
+use super::position::Position;
+use super::terrain::Terrain;
+
+/// What went wrong while parsing a map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapErrorKind {
+    /// The file has no `map` line separating header from tiles.
+    MissingMapSection,
+    /// A header line is not of the form `key value`.
+    MalformedHeader,
+    /// A `width`/`height` header value is not a number.
+    InvalidNumber(String),
+    /// A `connectivity`/`corners`/`goals` header value is not recognised.
+    InvalidValue { key: String, value: String },
+    /// The same header key appears twice.
+    DuplicateKey(String),
+    /// An unrecognised tile character.
+    InvalidTile(char),
+    /// A row whose length differs from the map width.
+    RaggedRow { expected: usize, found: usize },
+    /// The number of rows differs from the `height` header.
+    HeightMismatch { expected: usize, found: usize },
+    /// The `map` section contains no rows.
+    EmptyMap,
+    /// More than one `S` (or `+`) tile.
+    DuplicateStart,
+    /// No `S` or `+` tile.
+    MissingStart,
+    /// No `G` or `+` tile.
+    MissingGoal,
+}
+
+/// A map parse error with a 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: MapErrorKind,
+}
+
+impl MapParseError {
+    fn new(line: usize, column: usize, kind: MapErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl fmt::Display for MapErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapErrorKind::MissingMapSection => write!(f, "missing `map` line before the tiles"),
+            MapErrorKind::MalformedHeader => write!(f, "header lines must be `key value`"),
+            MapErrorKind::InvalidNumber(value) => write!(f, "`{}` is not a valid number", value),
+            MapErrorKind::InvalidValue { key, value } => {
+                write!(f, "`{}` is not a valid value for `{}`", value, key)
+            }
+            MapErrorKind::DuplicateKey(key) => write!(f, "duplicate header key `{}`", key),
+            MapErrorKind::InvalidTile(c) => write!(f, "unknown tile `{}`", c),
+            MapErrorKind::RaggedRow { expected, found } => {
+                write!(f, "row has {} tiles, expected {}", found, expected)
+            }
+            MapErrorKind::HeightMismatch { expected, found } => {
+                write!(f, "map has {} rows, header says {}", found, expected)
+            }
+            MapErrorKind::EmptyMap => write!(f, "map section has no rows"),
+            MapErrorKind::DuplicateStart => write!(f, "more than one start tile `S`"),
+            MapErrorKind::MissingStart => write!(f, "no start tile `S`"),
+            MapErrorKind::MissingGoal => write!(f, "no goal tile `G`"),
+        }
+    }
+}
+
+impl fmt::Display for MapParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl Error for MapParseError {}
+
+impl FromStr for Grid {
+    type Err = MapParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line.trim_end()));
+
+        // ── Header ──────────────────────────────────────
+        let mut width: Option<usize> = None;
+        let mut height: Option<usize> = None;
+        let mut diagonal: Option<bool> = None;
+        let mut corners: Option<CornerRule> = None;
+        let mut goal_mode: Option<GoalMode> = None;
+        let mut metadata = std::collections::BTreeMap::new();
+        let mut map_line = None;
+
+        for (line_no, line) in lines.by_ref() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.trim() == "map" {
+                map_line = Some(line_no);
+                break;
+            }
+
+            let indent = line.len() - line.trim_start().len();
+            let Some((key, value)) = line.trim_start().split_once(char::is_whitespace) else {
+                return Err(MapParseError::new(line_no, indent + 1, MapErrorKind::MalformedHeader));
+            };
+            let value = value.trim();
+            // `line` is trimmed at the end, so `value` is its suffix.
+            let value_col = line.len() - value.len() + 1;
+
+            match key {
+                "width" | "height" => {
+                    let n: usize = value.parse().map_err(|_| {
+                        MapParseError::new(line_no, value_col, MapErrorKind::InvalidNumber(value.to_string()))
+                    })?;
+                    let slot = if key == "width" { &mut width } else { &mut height };
+                    if slot.replace(n).is_some() {
+                        return Err(MapParseError::new(
+                            line_no,
+                            indent + 1,
+                            MapErrorKind::DuplicateKey(key.to_string()),
+                        ));
+                    }
+                }
+                "connectivity" | "corners" | "goals" => {
+                    let invalid = || {
+                        MapParseError::new(
+                            line_no,
+                            value_col,
+                            MapErrorKind::InvalidValue { key: key.to_string(), value: value.to_string() },
+                        )
+                    };
+                    let duplicate = if key == "connectivity" {
+                        let eight = match value {
+                            "4" => false,
+                            "8" => true,
+                            _ => return Err(invalid()),
+                        };
+                        diagonal.replace(eight).is_some()
+                    } else if key == "corners" {
+                        corners.replace(CornerRule::from_label(value).ok_or_else(invalid)?).is_some()
+                    } else {
+                        goal_mode.replace(GoalMode::from_label(value).ok_or_else(invalid)?).is_some()
+                    };
+                    if duplicate {
+                        return Err(MapParseError::new(
+                            line_no,
+                            indent + 1,
+                            MapErrorKind::DuplicateKey(key.to_string()),
+                        ));
+                    }
+                }
+                _ => {
+                    if metadata.insert(key.to_string(), value.to_string()).is_some() {
+                        return Err(MapParseError::new(
+                            line_no,
+                            indent + 1,
+                            MapErrorKind::DuplicateKey(key.to_string()),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let Some(map_line) = map_line else {
+            let last = s.lines().count().max(1);
+            return Err(MapParseError::new(last, 1, MapErrorKind::MissingMapSection));
+        };
+
+        // ── Tiles ───────────────────────────────────────
+        let mut rows: Vec<(usize, &str)> = lines.collect();
+        while rows.last().is_some_and(|(_, row)| row.is_empty()) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            return Err(MapParseError::new(map_line, 1, MapErrorKind::EmptyMap));
+        }
+
+        let expected_width = width.unwrap_or_else(|| rows[0].1.chars().count());
+        if let Some(expected) = height
+            && rows.len() != expected
+        {
+            let line = rows.last().map_or(map_line, |(n, _)| *n);
+            return Err(MapParseError::new(
+                line,
+                1,
+                MapErrorKind::HeightMismatch { expected, found: rows.len() },
+            ));
+        }
+
+        let goal_placeholder = Position { x: 0, y: 0 };
+        let mut grid = Grid::new(expected_width, rows.len(), goal_placeholder);
+        let mut start = None;
+        let mut goals = Vec::new();
+
+        for (y, &(line_no, row)) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != expected_width {
+                return Err(MapParseError::new(
+                    line_no,
+                    found.min(expected_width) + 1,
+                    MapErrorKind::RaggedRow { expected: expected_width, found },
+                ));
+            }
+
+            for (x, c) in row.chars().enumerate() {
+                let pos = Position { x, y };
+                match c {
+                    'S' => {
+                        if start.replace(pos).is_some() {
+                            return Err(MapParseError::new(line_no, x + 1, MapErrorKind::DuplicateStart));
+                        }
+                    }
+                    'G' => goals.push(pos),
+                    '+' => {
+                        if start.replace(pos).is_some() {
+                            return Err(MapParseError::new(line_no, x + 1, MapErrorKind::DuplicateStart));
+                        }
+                        goals.push(pos);
+                    }
+                    other => match Terrain::from_symbol(other) {
+                        Some(terrain) => grid.set_terrain(x, y, terrain),
+                        None => {
+                            return Err(MapParseError::new(line_no, x + 1, MapErrorKind::InvalidTile(other)));
+                        }
+                    },
+                }
+            }
+        }
+
+        grid.start = start.ok_or(MapParseError::new(map_line, 1, MapErrorKind::MissingStart))?;
+        let mut goals = goals.into_iter();
+        grid.goal = goals.next().ok_or(MapParseError::new(map_line, 1, MapErrorKind::MissingGoal))?;
+        grid.extra_goals = goals.collect();
+        grid.goal_mode = goal_mode.unwrap_or_default();
+        grid.metadata = metadata;
+        if diagonal == Some(true) {
+            grid.connectivity = Connectivity::Eight(corners.unwrap_or(CornerRule::NoCut));
+        }
+        Ok(grid)
+    }
+}
+
+impl fmt::Display for Grid {
+    /// Serialise the grid in the map file format (see module docs).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "width {}", self.width)?;
+        writeln!(f, "height {}", self.height)?;
+        if let Connectivity::Eight(rule) = self.connectivity {
+            writeln!(f, "connectivity 8")?;
+            writeln!(f, "corners {}", rule.label())?;
+        }
+        if self.goal_mode != GoalMode::Any {
+            writeln!(f, "goals {}", self.goal_mode.label())?;
+        }
+        for (key, value) in &self.metadata {
+            writeln!(f, "{} {}", key, value)?;
+        }
+        writeln!(f, "map")?;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = Position { x, y };
+                let c = if pos == self.start && self.is_goal(pos) {
+                    '+'
+                } else if pos == self.start {
+                    'S'
+                } else if self.is_goal(pos) {
+                    'G'
+                } else {
+                    self.terrain(x, y).symbol()
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Grid {
+    /// Load a grid from a map file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(&path)?;
+        let grid = text
+            .parse::<Grid>()
+            .map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        Ok(grid)
+    }
+
+    /// Save the grid as a map file, creating/overwriting `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORRIDOR: &str = "\
+name corridor
+width 6
+height 3
+map
+S.....
+.####.
+.....G
+";
+
+    #[test]
+    fn parses_tiles_start_goal_and_metadata() {
+        let grid: Grid = CORRIDOR.parse().expect("map should parse");
+        assert_eq!((grid.width, grid.height), (6, 3));
+        assert_eq!(grid.start, Position { x: 0, y: 0 });
+        assert_eq!(grid.goal, Position { x: 5, y: 2 });
+        assert!(!grid.is_walkable(1, 1));
+        assert!(grid.is_walkable(5, 1));
+        assert_eq!(grid.metadata.get("name").map(String::as_str), Some("corridor"));
+    }
+
+    #[test]
+    fn to_string_round_trips() {
+        let grid: Grid = CORRIDOR.parse().unwrap();
+        let text = grid.to_string();
+        let reparsed: Grid = text.parse().unwrap();
+        assert_eq!(reparsed.to_string(), text);
+        assert_eq!(reparsed.obstacle_positions(), grid.obstacle_positions());
+    }
+
+    #[test]
+    fn parses_terrain_symbols() {
+        let grid: Grid = "map\nS:,~\n...G\n".parse().unwrap();
+        assert_eq!(grid.terrain(1, 0), Terrain::Sand);
+        assert_eq!(grid.terrain(2, 0), Terrain::Mud);
+        assert_eq!(grid.terrain(3, 0), Terrain::Water);
+        assert_eq!(grid.to_string().lines().last(), Some("...G"));
+        assert!(grid.to_string().contains("S:,~"));
+    }
+
+    #[test]
+    fn parses_connectivity_header() {
+        let grid: Grid = "connectivity 8\ncorners no_squeeze\nmap\nS.\n.G\n".parse().unwrap();
+        assert_eq!(grid.connectivity, Connectivity::Eight(CornerRule::NoSqueeze));
+        let reparsed: Grid = grid.to_string().parse().unwrap();
+        assert_eq!(reparsed.connectivity, grid.connectivity);
+
+        let err = "connectivity 6\nmap\nSG\n".parse::<Grid>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 14));
+        assert!(matches!(err.kind, MapErrorKind::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn parses_multiple_goals() {
+        let grid: Grid = "goals all\nmap\nS.G\nG..\n".parse().unwrap();
+        assert_eq!(grid.goal, Position { x: 2, y: 0 });
+        assert_eq!(grid.extra_goals, vec![Position { x: 0, y: 1 }]);
+        assert_eq!(grid.goal_mode, GoalMode::All);
+        let reparsed: Grid = grid.to_string().parse().unwrap();
+        assert_eq!(reparsed.goals(), grid.goals());
+        assert_eq!(reparsed.goal_mode, GoalMode::All);
+    }
+
+    #[test]
+    fn start_on_a_goal_round_trips_and_value_columns_skip_the_key() {
+        let grid = Grid::new(3, 1, Position { x: 0, y: 0 });
+        assert_eq!(grid.to_string().lines().last(), Some("+.."));
+        let reparsed: Grid = grid.to_string().parse().unwrap();
+        assert_eq!((reparsed.start, reparsed.goals()), (grid.start, grid.goals()));
+
+        let grid: Grid = "goals all\nmap\n.G+\n".parse().unwrap();
+        assert_eq!(grid.start, Position { x: 2, y: 0 });
+        assert_eq!(grid.goals(), vec![Position { x: 1, y: 0 }, Position { x: 2, y: 0 }]);
+
+        // Each bad value also appears inside its key.
+        let err = "goals o\nmap\nSG\n".parse::<Grid>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
+        let err = "  width  w\nmap\nSG\n".parse::<Grid>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 10));
+    }
+
+    #[test]
+    fn reports_line_and_column_of_bad_tile() {
+        let err = "map\nS..\n.x.\n..G\n".parse::<Grid>().unwrap_err();
+        assert_eq!((err.line, err.column), (3, 2));
+        assert_eq!(err.kind, MapErrorKind::InvalidTile('x'));
+    }
+
+    #[test]
+    fn rejects_ragged_rows_and_missing_goal() {
+        let err = "map\nS..\n..\n".parse::<Grid>().unwrap_err();
+        assert_eq!(err.kind, MapErrorKind::RaggedRow { expected: 3, found: 2 });
+        assert_eq!(err.line, 3);
+
+        let err = "map\nS..\n...\n".parse::<Grid>().unwrap_err();
+        assert_eq!(err.kind, MapErrorKind::MissingGoal);
+    }
+
+    #[test]
+    fn bundled_benchmark_maps_parse() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("maps");
+        for entry in fs::read_dir(dir).expect("maps/ directory should exist") {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "map") {
+                Grid::load(&path).unwrap_or_else(|e| panic!("{}", e));
+            }
+        }
+    }
+}
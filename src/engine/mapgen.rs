@@ -0,0 +1,492 @@
+//! Procedural map generators.
+//!
+//! Every generator draws all of its randomness from the supplied RNG, so a
+//! layout is reproducible from the episode seed. Generated grids carry the
+//! generator's label in `metadata["name"]` and pick their own start/goal.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use super::grid::Grid;
+use super::position::Position;
+
+/// Which procedural layout to build.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MapGenerator {
+    /// I.i.d. obstacles at the given density (`Grid::scatter_obstacles`).
+    Scatter { density: f32 },
+    /// Perfect maze carved by a randomized depth-first search.
+    RecursiveBacktracker,
+    /// Perfect maze built from randomized Kruskal's algorithm.
+    Kruskal,
+    /// Cellular-automata caves: random `fill`, then `iterations` smoothing passes.
+    Cave { fill: f32, iterations: u32 },
+    /// Up to `rooms` rectangular rooms joined by L-shaped corridors.
+    Dungeon { rooms: usize },
+    /// Open field with `pockets` concave dead ends facing the start,
+    /// built to catch greedy goal-seeking.
+    Trap { pockets: usize },
+}
+
+impl MapGenerator {
+    /// Short label used in map metadata and experiment logs.
+    pub fn label(&self) -> &'static str {
+        match self {
+            MapGenerator::Scatter { .. } => "scatter",
+            MapGenerator::RecursiveBacktracker => "maze_backtracker",
+            MapGenerator::Kruskal => "maze_kruskal",
+            MapGenerator::Cave { .. } => "cave",
+            MapGenerator::Dungeon { .. } => "dungeon",
+            MapGenerator::Trap { .. } => "trap",
+        }
+    }
+
+    /// Build a `width` x `height` grid with this generator.
+    pub fn generate<R: Rng + ?Sized>(&self, width: usize, height: usize, rng: &mut R) -> Grid {
+        let mut grid = match *self {
+            MapGenerator::Scatter { density } => {
+                let mut grid = Grid::new(width, height, far_corner(width, height));
+                grid.scatter_obstacles(density, rng);
+                grid
+            }
+            MapGenerator::RecursiveBacktracker => maze_backtracker(width, height, rng),
+            MapGenerator::Kruskal => maze_kruskal(width, height, rng),
+            MapGenerator::Cave { fill, iterations } => cave(width, height, fill, iterations, rng),
+            MapGenerator::Dungeon { rooms } => dungeon(width, height, rooms, rng),
+            MapGenerator::Trap { pockets } => trap(width, height, pockets, rng),
+        };
+        grid.metadata.insert("name".to_string(), self.label().to_string());
+        grid
+    }
+}
+
+fn far_corner(width: usize, height: usize) -> Position {
+    Position {
+        x: width.saturating_sub(1),
+        y: height.saturating_sub(1),
+    }
+}
+
+/// A grid with every cell blocked, for generators that carve passages.
+fn solid(width: usize, height: usize, goal: Position) -> Grid {
+    let mut grid = Grid::new(width, height, goal);
+    for y in 0..height {
+        for x in 0..width {
+            grid.set_walkable(x, y, false);
+        }
+    }
+    grid
+}
+
+// ── Mazes ────────────────────────────────────────────────
+//
+// Maze cells sit on even coordinates; odd cells between them are walls
+// that get knocked out to join two cells. The goal is the cell nearest
+// the far corner.
+
+fn maze_goal(width: usize, height: usize) -> Position {
+    let corner = far_corner(width, height);
+    Position {
+        x: corner.x & !1,
+        y: corner.y & !1,
+    }
+}
+
+fn maze_cells(width: usize, height: usize) -> (usize, usize) {
+    (width.div_ceil(2), height.div_ceil(2))
+}
+
+/// Perfect maze via randomized depth-first search.
+pub fn maze_backtracker<R: Rng + ?Sized>(width: usize, height: usize, rng: &mut R) -> Grid {
+    let mut grid = solid(width, height, maze_goal(width, height));
+    let (cw, ch) = maze_cells(width, height);
+    if cw == 0 || ch == 0 {
+        return grid;
+    }
+
+    let mut visited = vec![vec![false; cw]; ch];
+    let mut stack = vec![(0usize, 0usize)];
+    visited[0][0] = true;
+    grid.set_walkable(0, 0, true);
+
+    while let Some(&(cx, cy)) = stack.last() {
+        let mut next = Vec::with_capacity(4);
+        if cx > 0 && !visited[cy][cx - 1] {
+            next.push((cx - 1, cy));
+        }
+        if cx + 1 < cw && !visited[cy][cx + 1] {
+            next.push((cx + 1, cy));
+        }
+        if cy > 0 && !visited[cy - 1][cx] {
+            next.push((cx, cy - 1));
+        }
+        if cy + 1 < ch && !visited[cy + 1][cx] {
+            next.push((cx, cy + 1));
+        }
+
+        match next.choose(rng) {
+            Some(&(nx, ny)) => {
+                visited[ny][nx] = true;
+                grid.set_walkable(cx + nx, cy + ny, true);
+                grid.set_walkable(nx * 2, ny * 2, true);
+                stack.push((nx, ny));
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    grid
+}
+
+/// Perfect maze via randomized Kruskal's algorithm.
+pub fn maze_kruskal<R: Rng + ?Sized>(width: usize, height: usize, rng: &mut R) -> Grid {
+    let mut grid = solid(width, height, maze_goal(width, height));
+    let (cw, ch) = maze_cells(width, height);
+
+    let mut edges = Vec::new();
+    for cy in 0..ch {
+        for cx in 0..cw {
+            grid.set_walkable(cx * 2, cy * 2, true);
+            if cx + 1 < cw {
+                edges.push(((cx, cy), (cx + 1, cy)));
+            }
+            if cy + 1 < ch {
+                edges.push(((cx, cy), (cx, cy + 1)));
+            }
+        }
+    }
+    edges.shuffle(rng);
+
+    // Union-find over maze cells.
+    let mut parent: Vec<usize> = (0..cw * ch).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for ((ax, ay), (bx, by)) in edges {
+        let ra = find(&mut parent, ay * cw + ax);
+        let rb = find(&mut parent, by * cw + bx);
+        if ra != rb {
+            parent[ra] = rb;
+            grid.set_walkable(ax + bx, ay + by, true);
+        }
+    }
+
+    grid
+}
+
+// ── Caves ────────────────────────────────────────────────
+
+/// Cellular-automata caves using the 4-5 rule: a cell becomes wall when at
+/// least 5 of its 8 neighbours are walls and floor when at most 3 are.
+/// Out-of-bounds cells count as walls, so the corners are usually rock;
+/// start and goal are the floor cells nearest the top-left and
+/// bottom-right corners.
+pub fn cave<R: Rng + ?Sized>(
+    width: usize,
+    height: usize,
+    fill: f32,
+    iterations: u32,
+    rng: &mut R,
+) -> Grid {
+    let mut walls: Vec<Vec<bool>> = (0..height)
+        .map(|_| (0..width).map(|_| rng.r#gen::<f32>() < fill).collect())
+        .collect();
+
+    for _ in 0..iterations {
+        let mut next = walls.clone();
+        for (y, row) in next.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let mut count = 0;
+                for dy in -1i32..=1 {
+                    for dx in -1i32..=1 {
+                        if dx == 0 && dy == 0 {
+                            continue;
+                        }
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        let wall = nx < 0
+                            || ny < 0
+                            || nx as usize >= width
+                            || ny as usize >= height
+                            || walls[ny as usize][nx as usize];
+                        if wall {
+                            count += 1;
+                        }
+                    }
+                }
+                if count >= 5 {
+                    *cell = true;
+                } else if count <= 3 {
+                    *cell = false;
+                }
+            }
+        }
+        walls = next;
+    }
+
+    let mut grid = Grid::new(width, height, far_corner(width, height));
+    for (y, row) in walls.iter().enumerate() {
+        for (x, &wall) in row.iter().enumerate() {
+            grid.set_walkable(x, y, !wall);
+        }
+    }
+
+    let corner = far_corner(width, height);
+    grid.start = nearest_floor(&grid, Position { x: 0, y: 0 }).unwrap_or(grid.start);
+    grid.goal = nearest_floor(&grid, corner).unwrap_or(corner);
+    grid.set_walkable(grid.start.x, grid.start.y, true);
+    grid.set_walkable(grid.goal.x, grid.goal.y, true);
+    grid
+}
+
+/// The walkable cell closest (Manhattan) to `target`, if any.
+fn nearest_floor(grid: &Grid, target: Position) -> Option<Position> {
+    let mut best: Option<(Position, usize)> = None;
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if !grid.is_walkable(x, y) {
+                continue;
+            }
+            let d = x.abs_diff(target.x) + y.abs_diff(target.y);
+            if best.is_none_or(|(_, best_d)| d < best_d) {
+                best = Some((Position { x, y }, d));
+            }
+        }
+    }
+    best.map(|(pos, _)| pos)
+}
+
+// ── Dungeons ─────────────────────────────────────────────
+
+#[derive(Clone, Copy)]
+struct Room {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+}
+
+impl Room {
+    fn center(&self) -> (usize, usize) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    /// Overlap test with a one-cell margin so rooms never share walls.
+    fn intersects(&self, other: &Room) -> bool {
+        self.x <= other.x + other.w
+            && other.x <= self.x + self.w
+            && self.y <= other.y + other.h
+            && other.y <= self.y + self.h
+    }
+}
+
+/// Rooms-and-corridors dungeon. Rooms are placed at random without
+/// overlap and each is joined to the previous one by an L-shaped
+/// corridor. Start is the first room's top-left cell, goal the last
+/// room's bottom-right cell.
+pub fn dungeon<R: Rng + ?Sized>(width: usize, height: usize, rooms: usize, rng: &mut R) -> Grid {
+    let mut grid = solid(width, height, far_corner(width, height));
+    if width == 0 || height == 0 {
+        return grid;
+    }
+    let max_w = (width / 3).max(3).min(width);
+    let max_h = (height / 3).max(3).min(height);
+
+    let mut placed: Vec<Room> = Vec::new();
+    for _ in 0..rooms.saturating_mul(10) {
+        if placed.len() >= rooms {
+            break;
+        }
+        let w = rng.gen_range(2.min(max_w)..=max_w);
+        let h = rng.gen_range(2.min(max_h)..=max_h);
+        let room = Room {
+            x: rng.gen_range(0..=width - w),
+            y: rng.gen_range(0..=height - h),
+            w,
+            h,
+        };
+        if placed.iter().all(|other| !room.intersects(other)) {
+            placed.push(room);
+        }
+    }
+    if placed.is_empty() {
+        placed.push(Room { x: 0, y: 0, w: width, h: height });
+    }
+
+    for room in &placed {
+        for y in room.y..room.y + room.h {
+            for x in room.x..room.x + room.w {
+                grid.set_walkable(x, y, true);
+            }
+        }
+    }
+
+    for pair in placed.windows(2) {
+        let (ax, ay) = pair[0].center();
+        let (bx, by) = pair[1].center();
+        // Pick which leg of the L comes first at random.
+        let corner = if rng.gen_bool(0.5) { (bx, ay) } else { (ax, by) };
+        carve_line(&mut grid, (ax, ay), corner);
+        carve_line(&mut grid, corner, (bx, by));
+    }
+
+    let first = placed[0];
+    let last = placed[placed.len() - 1];
+    grid.start = Position { x: first.x, y: first.y };
+    grid.goal = Position {
+        x: last.x + last.w - 1,
+        y: last.y + last.h - 1,
+    };
+    grid
+}
+
+/// Carve a straight horizontal or vertical corridor between two cells.
+fn carve_line(grid: &mut Grid, from: (usize, usize), to: (usize, usize)) {
+    for x in from.0.min(to.0)..=from.0.max(to.0) {
+        grid.set_walkable(x, from.1, true);
+    }
+    for y in from.1.min(to.1)..=from.1.max(to.1) {
+        grid.set_walkable(to.0, y, true);
+    }
+}
+
+// ── Traps ────────────────────────────────────────────────
+
+/// Open field with concave pockets between start (top-left) and goal
+/// (bottom-right). Each pocket is walled on its goal-facing sides and
+/// open toward the start, so a greedy agent walking downhill on
+/// Manhattan distance enters it and finds no improving move.
+pub fn trap<R: Rng + ?Sized>(width: usize, height: usize, pockets: usize, rng: &mut R) -> Grid {
+    let mut grid = Grid::new(width, height, far_corner(width, height));
+    let max_r = (width.min(height) / 4).max(1);
+
+    for i in 0..pockets {
+        let r = rng.gen_range(1..=max_r);
+        // Spread pocket corners along the start→goal diagonal, with jitter.
+        let t = (i + 1) as f32 / (pockets + 1) as f32;
+        let jitter = r as i32;
+        let cx = ((width as f32 - 1.0) * t) as i32 + rng.gen_range(-jitter..=jitter);
+        let cy = ((height as f32 - 1.0) * t) as i32 + rng.gen_range(-jitter..=jitter);
+        let r = r as i32;
+
+        let mut block = |x: i32, y: i32| {
+            if x >= 0 && y >= 0 {
+                grid.set_walkable(x as usize, y as usize, false);
+            }
+        };
+        // Goal-facing walls: the right side and the bottom side.
+        for d in -r..=r {
+            block(cx + r, cy + d);
+            block(cx + d, cy + r);
+        }
+        // Half-height lips on the top and left leave a diagonal mouth
+        // facing the start.
+        for d in 0..=r {
+            block(cx + d, cy - r);
+            block(cx - r, cy + d);
+        }
+    }
+
+    grid.set_walkable(grid.start.x, grid.start.y, true);
+    grid.set_walkable(grid.goal.x, grid.goal.y, true);
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::adapter::legacy;
+    use crate::agents::behavior_tree::{move_towards_goal, BehaviorTreeAgent, Node};
+    use crate::algorithms::astar::find_path;
+    use crate::engine::rng::seeded;
+    use crate::engine::world::World;
+
+    fn solvable(grid: &Grid) -> bool {
+        let start = (grid.start.x, grid.start.y);
+        let goal = (grid.goal.x, grid.goal.y);
+        find_path(start, goal, grid, None).is_some_and(|path| path.last() == Some(&goal))
+    }
+
+    #[test]
+    fn mazes_are_solvable_and_perfect() {
+        for generator in [MapGenerator::RecursiveBacktracker, MapGenerator::Kruskal] {
+            for seed in 0..10 {
+                let grid = generator.generate(11, 9, &mut seeded(seed));
+                assert!(solvable(&grid), "{} seed {}", generator.label(), seed);
+
+                // A perfect maze is a spanning tree: cells = 6x5, passages = cells - 1.
+                let floor = 11 * 9 - grid.obstacle_positions().len();
+                assert_eq!(floor, 30 + 29);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let generators = [
+            MapGenerator::Scatter { density: 0.2 },
+            MapGenerator::RecursiveBacktracker,
+            MapGenerator::Kruskal,
+            MapGenerator::Cave { fill: 0.45, iterations: 4 },
+            MapGenerator::Dungeon { rooms: 4 },
+            MapGenerator::Trap { pockets: 2 },
+        ];
+        for generator in generators {
+            let a = generator.generate(16, 12, &mut seeded(3));
+            let b = generator.generate(16, 12, &mut seeded(3));
+            assert_eq!(a.to_string(), b.to_string(), "{}", generator.label());
+            assert!(a.is_walkable(a.start.x, a.start.y));
+            assert!(a.is_walkable(a.goal.x, a.goal.y));
+        }
+    }
+
+    #[test]
+    fn dungeon_rooms_are_connected() {
+        for seed in 0..10 {
+            let grid = dungeon(20, 14, 5, &mut seeded(seed));
+            assert!(solvable(&grid), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn degenerate_sizes_do_not_panic() {
+        let generators = [
+            MapGenerator::Scatter { density: 0.2 },
+            MapGenerator::RecursiveBacktracker,
+            MapGenerator::Kruskal,
+            MapGenerator::Cave { fill: 0.45, iterations: 4 },
+            MapGenerator::Dungeon { rooms: 3 },
+            MapGenerator::Trap { pockets: 2 },
+        ];
+        for generator in generators {
+            for (width, height) in [(0, 0), (0, 5), (5, 0), (1, 1)] {
+                let grid = generator.generate(width, height, &mut seeded(0));
+                assert_eq!((grid.width, grid.height), (width, height), "{}", generator.label());
+            }
+        }
+    }
+
+    #[test]
+    fn traps_catch_greedy_descent_on_solvable_maps() {
+        let (mut solvable_maps, mut caught) = (0, 0);
+        for seed in 0..20 {
+            let grid = MapGenerator::Trap { pockets: 3 }.generate(16, 12, &mut seeded(seed));
+            if !solvable(&grid) {
+                continue;
+            }
+            solvable_maps += 1;
+            // The BT's greedy leaf on its own, without the wander fallback.
+            let greedy = BehaviorTreeAgent::new(0, 0).with_tree(Node::Action(move_towards_goal));
+            let mut world = World::with_rng(grid, legacy(greedy), 0, seeded(0));
+            caught += usize::from(!world.run_until_done(200));
+        }
+        assert!(caught * 4 >= solvable_maps, "caught {} of {}", caught, solvable_maps);
+    }
+}
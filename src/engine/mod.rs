@@ -1,6 +1,14 @@
+pub mod action;
+pub mod connectivity;
+pub mod dynamics;
+pub mod goals;
 pub mod grid;
+pub mod map_format;
+pub mod mapgen;
 pub mod multi_world;
 pub mod position;
+pub mod rng;
+pub mod terrain;
 pub mod world;
 
 // Core engine modules.
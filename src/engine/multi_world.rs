@@ -1,52 +1,381 @@
+use rand::seq::SliceRandom;
+
 use crate::agents::Agent;
 
 pub use super::grid::Grid;
 pub use super::position::Position;
+use super::action::{Action, Body, Observation, Outcome};
+use super::goals::GoalProgress;
+use super::dynamics::{ObstacleDynamics, ObstacleRule};
+use super::rng::{random_seed, seeded, SimRng};
+
+/// Who wins when several agents want the same cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    /// Lower agent index wins.
+    #[default]
+    Index,
+    /// Ties are broken by a fresh random order each tick.
+    Random,
+}
+
+/// How agents share cells.
+///
+/// Moves are resolved simultaneously: every agent picks its action from
+/// the same snapshot, then conflicting moves are refused. An agent that
+/// stays put always keeps its cell; among agents moving into the same
+/// cell, the one with the highest `priority` gets it. Goal cells can
+/// always hold any number of agents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Occupancy {
+    /// If false, agents overlap freely and none of the other rules apply.
+    pub agents_block: bool,
+    /// Whether two adjacent agents may trade cells in one tick.
+    pub allow_swaps: bool,
+    pub priority: Priority,
+}
+
+impl Occupancy {
+    /// Agents overlap freely (the original behavior).
+    pub fn overlap() -> Self {
+        Self::default()
+    }
+
+    /// Agents block each other, with swaps forbidden.
+    pub fn blocking(priority: Priority) -> Self {
+        Self {
+            agents_block: true,
+            allow_swaps: false,
+            priority,
+        }
+    }
+}
+
+/// Per-agent congestion counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Traffic {
+    /// Moves refused because another agent held or claimed the cell.
+    pub collisions: u32,
+    /// Ticks spent without moving before finishing, for any reason.
+    pub waits: u32,
+}
 
 /// A world that holds multiple agents navigating the same grid.
 pub struct MultiWorld {
     pub grid: Grid,
     pub agents: Vec<Box<dyn Agent>>,
+    /// Position and energy of each agent, indexed like `agents`.
+    pub bodies: Vec<Body>,
+    /// Goals each agent has visited, indexed like `agents`.
+    pub progress: Vec<GoalProgress>,
     pub step: usize,
+    /// Seed the shared RNG was created from.
+    pub seed: u64,
+    /// Source of all randomness for this episode, shared by every agent.
+    pub rng: SimRng,
+    /// Obstacles that change between ticks (empty for a static grid).
+    pub dynamics: ObstacleDynamics,
+    /// How agents share cells.
+    pub occupancy: Occupancy,
+    /// Collision and wait counts, indexed like `agents`.
+    pub traffic: Vec<Traffic>,
 }
 
 impl MultiWorld {
     /// Create a multi-agent world from a pre-built grid and a list of agents.
     pub fn new(grid: Grid, agents: Vec<Box<dyn Agent>>) -> Self {
+        Self::with_seed(grid, agents, random_seed())
+    }
+
+    /// Create a multi-agent world whose randomness is driven by `seed`.
+    pub fn with_seed(grid: Grid, agents: Vec<Box<dyn Agent>>, seed: u64) -> Self {
         Self {
+            bodies: vec![Body::new(grid.start); agents.len()],
+            progress: vec![GoalProgress::new(&grid, grid.start); agents.len()],
+            traffic: vec![Traffic::default(); agents.len()],
             grid,
             agents,
             step: 0,
+            seed,
+            rng: seeded(seed),
+            dynamics: ObstacleDynamics::default(),
+            occupancy: Occupancy::default(),
         }
     }
 
-    /// Advance every agent by one tick.
-    pub fn update(&mut self) {
-        for agent in &mut self.agents {
-            agent.update(&self.grid);
+    /// Give each agent its own start cell, in `agents` order. Agents
+    /// beyond the end of `starts` keep `grid.start`. Dynamic obstacles
+    /// already placed on a start are cleared.
+    pub fn with_starts(mut self, starts: &[Position]) -> Self {
+        for ((body, progress), &start) in self.bodies.iter_mut().zip(&mut self.progress).zip(starts) {
+            *body = Body::new(start);
+            *progress = GoalProgress::new(&self.grid, start);
+        }
+        self.dynamics.unblock(&mut self.grid, starts);
+        self
+    }
+
+    /// Set how agents share cells.
+    pub fn with_occupancy(mut self, occupancy: Occupancy) -> Self {
+        self.occupancy = occupancy;
+        self
+    }
+
+    /// Attach dynamic obstacles, set up with the world's RNG around the
+    /// agents' current positions.
+    pub fn with_dynamics(mut self, rules: &[ObstacleRule]) -> Self {
+        let occupied: Vec<Position> = self.bodies.iter().map(|b| b.position).collect();
+        self.dynamics = ObstacleDynamics::new(rules, &mut self.grid, &occupied, &mut self.rng);
+        self
+    }
+
+    /// Advance the obstacle dynamics, then every agent, by one tick.
+    ///
+    /// All agents decide from the same snapshot; their moves are then
+    /// resolved against the `occupancy` rules and applied together.
+    /// Returns each agent's outcome, indexed like `agents`.
+    pub fn update(&mut self) -> Vec<Outcome> {
+        let occupied: Vec<Position> = self.bodies.iter().map(|b| b.position).collect();
+        self.dynamics.step(&mut self.grid, &occupied, &mut self.rng);
+
+        let mut actions = Vec::with_capacity(self.agents.len());
+        for ((agent, body), progress) in self.agents.iter_mut().zip(&self.bodies).zip(&mut self.progress) {
+            let obs = Observation {
+                grid: &self.grid,
+                position: body.position,
+                energy: body.energy,
+                tick: self.step,
+                goal: progress.target(&self.grid, body.position),
+            };
+            actions.push(agent.act(&obs, &mut self.rng));
+        }
+
+        let refused = if self.occupancy.agents_block {
+            self.resolve_collisions(&actions)
+        } else {
+            vec![false; actions.len()]
+        };
+
+        let mut outcomes = Vec::with_capacity(self.agents.len());
+        for i in 0..self.agents.len() {
+            let outcome = if refused[i] {
+                self.traffic[i].collisions += 1;
+                Outcome::Collided
+            } else {
+                self.bodies[i].apply(&self.grid, actions[i])
+            };
+            if !matches!(outcome, Outcome::Moved { .. }) && !self.agent_at_goal(i) {
+                self.traffic[i].waits += 1;
+            }
+            self.progress[i].visit(&self.grid, self.bodies[i].position);
+            self.agents[i].on_outcome(&outcome);
+            outcomes.push(outcome);
         }
         self.step += 1;
+        outcomes
+    }
+
+    /// Which agents' moves must be refused so that no two agents end the
+    /// tick on the same (non-goal) cell, and, unless allowed, no two
+    /// agents swap cells.
+    fn resolve_collisions(&mut self, actions: &[Action]) -> Vec<bool> {
+        let current: Vec<Position> = self.bodies.iter().map(|b| b.position).collect();
+        // Where each agent would end up if it were alone on the grid.
+        let wanted: Vec<Position> = self
+            .bodies
+            .iter()
+            .zip(actions)
+            .map(|(body, &action)| {
+                let mut probe = *body;
+                probe.apply(&self.grid, action);
+                probe.position
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..actions.len()).collect();
+        if self.occupancy.priority == Priority::Random {
+            order.shuffle(&mut self.rng);
+        }
+
+        let mut refused = vec![false; actions.len()];
+        let target = |i: usize, refused: &[bool]| if refused[i] { current[i] } else { wanted[i] };
+        let moving = |i: usize, refused: &[bool]| !refused[i] && wanted[i] != current[i];
+
+        // Each pass refuses at least one move, and a refusal can only
+        // create new conflicts for cells it vacates, so this terminates.
+        loop {
+            let mut changed = false;
+            for (rank, &i) in order.iter().enumerate() {
+                if !moving(i, &refused) {
+                    continue;
+                }
+                let cell = target(i, &refused);
+                if self.grid.is_goal(cell) {
+                    continue;
+                }
+                let conflict = order.iter().enumerate().any(|(other_rank, &j)| {
+                    if j == i || target(j, &refused) != cell {
+                        return false;
+                    }
+                    // Agents staying put keep their cell; otherwise the
+                    // earlier agent in priority order wins.
+                    !moving(j, &refused) || other_rank < rank
+                });
+                let swap = !self.occupancy.allow_swaps
+                    && (0..actions.len()).any(|j| {
+                        j != i && moving(j, &refused) && current[j] == cell && target(j, &refused) == current[i]
+                    });
+                if conflict || swap {
+                    refused[i] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return refused;
+            }
+        }
     }
 
-    /// Check if a specific agent has reached the goal.
+    /// Check if a specific agent has finished (reached any goal, or
+    /// visited them all under `GoalMode::All`).
     pub fn agent_at_goal(&self, index: usize) -> bool {
-        if let Some(agent) = self.agents.get(index) {
-            agent.position() == self.grid.goal
+        if let Some(progress) = self.progress.get(index) {
+            progress.is_complete(self.grid.goal_mode)
         } else {
             false
         }
     }
 
-    /// How many agents have reached the goal?
+    /// How many agents have finished?
     pub fn done_count(&self) -> usize {
-        self.agents
+        self.progress
             .iter()
-            .filter(|a| a.position() == self.grid.goal)
+            .filter(|p| p.is_complete(self.grid.goal_mode))
             .count()
     }
 
-    /// Are all agents at the goal?
+    /// Have all agents finished?
     pub fn all_done(&self) -> bool {
         self.done_count() == self.agents.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::action::Direction;
+
+    /// Always takes the same action.
+    struct Scripted(Action);
+
+    impl Agent for Scripted {
+        fn act(&mut self, _obs: &Observation, _rng: &mut SimRng) -> Action {
+            self.0
+        }
+        fn name(&self) -> &'static str {
+            "Scripted"
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn corridor(actions: &[Action], starts: &[usize], occupancy: Occupancy) -> MultiWorld {
+        let grid = Grid::new(6, 1, Position { x: 5, y: 0 });
+        let agents = actions.iter().map(|&a| Box::new(Scripted(a)) as Box<dyn Agent>).collect();
+        let mut world = MultiWorld::with_seed(grid, agents, 0).with_occupancy(occupancy);
+        for (body, &x) in world.bodies.iter_mut().zip(starts) {
+            body.position = Position { x, y: 0 };
+        }
+        world
+    }
+
+    #[test]
+    fn swaps_follow_the_policy() {
+        let actions = [Action::Move(Direction::East), Action::Move(Direction::West)];
+
+        let mut world = corridor(&actions, &[1, 2], Occupancy::blocking(Priority::Index));
+        assert_eq!(world.update(), vec![Outcome::Collided, Outcome::Collided]);
+        assert_eq!(world.traffic[0], Traffic { collisions: 1, waits: 1 });
+
+        let swaps = Occupancy { allow_swaps: true, ..Occupancy::blocking(Priority::Index) };
+        let mut world = corridor(&actions, &[1, 2], swaps);
+        world.update();
+        assert_eq!(world.bodies[0].position.x, 2);
+        assert_eq!(world.bodies[1].position.x, 1);
+
+        let mut world = corridor(&actions, &[1, 2], Occupancy::overlap());
+        world.update();
+        assert_eq!(world.traffic[0].collisions, 0);
+    }
+
+    #[test]
+    fn contested_cells_go_by_priority_and_followers_chain() {
+        // Two agents converge on x=2; a third stays on x=3.
+        let actions = [
+            Action::Move(Direction::West),
+            Action::Move(Direction::East),
+            Action::Wait,
+        ];
+        let mut world = corridor(&actions, &[3, 1, 4], Occupancy::blocking(Priority::Index));
+        // Agent 0 moves off x=3 into x=2, beating agent 1.
+        assert_eq!(world.update()[1], Outcome::Collided);
+        assert_eq!(world.bodies[0].position.x, 2);
+
+        // A follower may step into a cell its leader is leaving.
+        let east = Action::Move(Direction::East);
+        let mut world = corridor(&[east, east], &[1, 0], Occupancy::blocking(Priority::Index));
+        world.update();
+        assert_eq!((world.bodies[0].position.x, world.bodies[1].position.x), (2, 1));
+        // ...but not into one whose occupant is refused.
+        let mut world = corridor(&[east, east, Action::Wait], &[1, 0, 2], Occupancy::blocking(Priority::Random));
+        assert_eq!(world.update()[..2], [Outcome::Collided, Outcome::Collided]);
+    }
+
+    #[test]
+    fn per_agent_starts_and_visit_all_goals() {
+        use crate::engine::goals::GoalMode;
+
+        let mut grid = Grid::new(6, 1, Position { x: 5, y: 0 });
+        grid.extra_goals = vec![Position { x: 0, y: 0 }];
+        grid.goal_mode = GoalMode::All;
+        let east = Action::Move(Direction::East);
+        let agents = vec![Box::new(Scripted(east)) as Box<dyn Agent>, Box::new(Scripted(east))];
+        let mut world = MultiWorld::with_seed(grid, agents, 0).with_starts(&[Position { x: 3, y: 0 }]);
+        assert_eq!(world.bodies[0].position.x, 3);
+        assert_eq!(world.bodies[1].position, world.grid.start);
+
+        for _ in 0..5 {
+            world.update();
+        }
+        // Agent 1 started on the extra goal and walked to the primary one.
+        assert!(world.agent_at_goal(1));
+        assert!(!world.agent_at_goal(0));
+        assert_eq!(world.done_count(), 1);
+    }
+
+    #[test]
+    fn dynamic_obstacles_keep_clear_of_per_agent_starts() {
+        use crate::engine::dynamics::ObstacleRule;
+
+        // Wanderers cover most free cells, so unprotected starts would be
+        // hit.
+        let rules = [ObstacleRule::Wanderers { count: 20, move_chance: 0.0 }];
+        let starts: Vec<Position> = (1..4).map(|x| Position { x, y: 1 }).collect();
+        let world = |starts_first: bool| {
+            let grid = Grid::new(5, 5, Position { x: 4, y: 4 });
+            let agents = (0..3).map(|_| Box::new(Scripted(Action::Wait)) as Box<dyn Agent>).collect();
+            let world = MultiWorld::with_seed(grid, agents, 0);
+            if starts_first {
+                world.with_starts(&starts).with_dynamics(&rules)
+            } else {
+                world.with_dynamics(&rules).with_starts(&starts)
+            }
+        };
+        for starts_first in [true, false] {
+            let world = world(starts_first);
+            assert!(starts.iter().all(|s| world.grid.is_walkable(s.x, s.y)));
+            assert!(!world.dynamics.blocked_cells().iter().any(|cell| starts.contains(cell)));
+        }
+    }
+}
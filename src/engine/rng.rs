@@ -0,0 +1,18 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// The RNG used for every stochastic decision in a simulation.
+///
+/// A single instance is owned by the world/episode and lent to the grid
+/// and agents, so an episode is fully determined by its seed.
+pub type SimRng = StdRng;
+
+/// Create a simulation RNG from a seed.
+pub fn seeded(seed: u64) -> SimRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Draw a fresh seed from OS entropy, for runs that don't pin one.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
@@ -0,0 +1,66 @@
+/// Tile type of a grid cell.
+///
+/// Costs are charged for *entering* a cell: `move_cost` is what planners
+/// minimise, `energy_cost` is what energy-tracking agents pay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Terrain {
+    #[default]
+    Floor,
+    Sand,
+    Mud,
+    Water,
+    Wall,
+}
+
+impl Terrain {
+    /// All terrain types, cheapest first.
+    pub const ALL: [Terrain; 5] = [
+        Terrain::Floor,
+        Terrain::Sand,
+        Terrain::Mud,
+        Terrain::Water,
+        Terrain::Wall,
+    ];
+
+    pub fn is_walkable(self) -> bool {
+        self != Terrain::Wall
+    }
+
+    /// Path cost of stepping onto this tile, or `None` for walls.
+    pub fn move_cost(self) -> Option<u32> {
+        match self {
+            Terrain::Floor => Some(1),
+            Terrain::Sand => Some(2),
+            Terrain::Mud => Some(3),
+            Terrain::Water => Some(5),
+            Terrain::Wall => None,
+        }
+    }
+
+    /// Energy spent stepping onto this tile (0 for walls, which can't be entered).
+    pub fn energy_cost(self) -> u32 {
+        match self {
+            Terrain::Floor => 1,
+            Terrain::Sand => 2,
+            Terrain::Mud => 3,
+            Terrain::Water => 4,
+            Terrain::Wall => 0,
+        }
+    }
+
+    /// Character used in map files.
+    pub fn symbol(self) -> char {
+        match self {
+            Terrain::Floor => '.',
+            Terrain::Sand => ':',
+            Terrain::Mud => ',',
+            Terrain::Water => '~',
+            Terrain::Wall => '#',
+        }
+    }
+
+    /// Inverse of `symbol`.
+    pub fn from_symbol(c: char) -> Option<Terrain> {
+        Terrain::ALL.into_iter().find(|t| t.symbol() == c)
+    }
+}
@@ -2,35 +2,62 @@ use crate::agents::Agent;
 
 pub use super::position::Position;
 pub use super::grid::Grid;
+use super::action::{Body, Observation, Outcome};
+use super::goals::GoalProgress;
+use super::rng::{random_seed, seeded, SimRng};
 
-/// The world contains the grid and a polymorphic agent.
+/// The world contains the grid, a polymorphic agent and the body it
+/// controls. The agent only chooses actions; the world applies them.
 pub struct World {
     pub grid: Grid,
     pub agent: Box<dyn Agent>,
+    /// The agent's position and energy, owned by the world.
+    pub body: Body,
+    /// Goals the agent has visited.
+    pub progress: GoalProgress,
     pub step: usize,
+    /// Seed the world's RNG was created from; replaying with the same
+    /// seed reproduces the episode exactly.
+    pub seed: u64,
+    /// Source of all randomness for this episode.
+    pub rng: SimRng,
 }
 
 impl World {
-    /// Create a new world with the given agent.
+    /// Create a new world with the given agent and a random seed.
     pub fn new(width: usize, height: usize, agent: Box<dyn Agent>) -> Self {
+        Self::with_seed(width, height, agent, random_seed())
+    }
+
+    /// Create a new world whose randomness is driven by `seed`.
+    pub fn with_seed(width: usize, height: usize, agent: Box<dyn Agent>, seed: u64) -> Self {
         let goal = Position {
             x: width.saturating_sub(1),
             y: height.saturating_sub(1),
         };
 
         let grid = Grid::new(width, height, goal);
+        Self::with_rng(grid, agent, seed, seeded(seed))
+    }
 
+    /// Build a world around an existing grid, continuing `rng` (e.g. the
+    /// stream that generated the grid). The body starts on `grid.start`.
+    pub fn with_rng(grid: Grid, agent: Box<dyn Agent>, seed: u64, rng: SimRng) -> Self {
         Self {
+            body: Body::new(grid.start),
+            progress: GoalProgress::new(&grid, grid.start),
             grid,
             agent,
             step: 0,
+            seed,
+            rng,
         }
     }
 
-    /// Has the agent reached the goal cell?
+    /// Has the agent finished: reached any goal, or visited every goal
+    /// under `GoalMode::All`?
     pub fn has_reached_goal(&self) -> bool {
-        let pos = self.agent.position();
-        pos == self.grid.goal
+        self.progress.is_complete(self.grid.goal_mode)
     }
 
     /// Whether the agent is stuck (mostly for A*).
@@ -38,31 +65,49 @@ impl World {
         self.agent.is_stuck()
     }
 
-    /// Advance the world by one tick: update the agent.
-    pub fn update(&mut self) {
-        self.agent.update(&self.grid);
+    /// Advance the world by one tick: ask the agent for an action,
+    /// apply it to the body and report the outcome back.
+    pub fn update(&mut self) -> Outcome {
+        let goal = self.progress.target(&self.grid, self.body.position);
+        let obs = Observation {
+            grid: &self.grid,
+            position: self.body.position,
+            energy: self.body.energy,
+            tick: self.step,
+            goal,
+        };
+        let action = self.agent.act(&obs, &mut self.rng);
+        let outcome = self.body.apply(&self.grid, action);
+        self.progress.visit(&self.grid, self.body.position);
+        self.agent.on_outcome(&outcome);
         self.step += 1;
+        outcome
+    }
+
+    /// Step until the goal is reached, the agent reports it is stuck or
+    /// `step` hits `max_steps`. Returns whether the goal was reached.
+    pub fn run_until_done(&mut self, max_steps: usize) -> bool {
+        while !self.has_reached_goal() && !self.is_agent_stuck() && self.step < max_steps {
+            self.update();
+        }
+        self.has_reached_goal()
     }
 
     /// Print a simple ASCII representation of the grid,
     /// showing the agent and the goal.
     pub fn print(&self) {
-        println!("Step {} | Agent at {:?}", self.step, self.agent.position());
+        println!("Step {} | Agent at {:?}", self.step, self.body.position);
 
         for y in 0..self.grid.height {
             for x in 0..self.grid.width {
                 let pos = Position { x, y };
 
-                if pos == self.agent.position() {
+                if pos == self.body.position {
                     print!("A ");
-                } else if pos == self.grid.goal {
+                } else if self.grid.is_goal(pos) {
                     print!("G ");
                 } else {
-                    if self.grid.is_walkable(x, y) {
-                        print!(". ");
-                    } else {
-                        print!("# ");
-                    }
+                    print!("{} ", self.grid.terrain(x, y).symbol());
                 }
             }
             println!();
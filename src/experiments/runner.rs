@@ -5,10 +5,31 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use rand::Rng;
 
+use crate::agents::adapter::legacy;
 use crate::agents::astar::AStarAgent;
-use crate::agents::behavior_tree::BehaviorTreeAgent;
-use crate::agents::fsm::{FSMAgent, FSMState};
-use crate::engine::world::{Grid, Position};
+use crate::agents::behavior_tree::{BehaviorTreeAgent, Node};
+use crate::agents::belief::Sensor;
+use crate::agents::bug::{BugAgent, BugVariant};
+use crate::agents::dstar_lite::DStarLiteAgent;
+use crate::agents::frontier::FrontierAgent;
+use crate::agents::fsm::FSMAgent;
+use crate::agents::goap::GoapAgent;
+use crate::agents::lrta::{HeuristicTable, LrtaAgent};
+use crate::agents::mcts::{self, MctsAgent};
+use crate::agents::potential_field::{FieldWeights, PotentialFieldAgent};
+use crate::agents::qlearning::{QLearningAgent, QTable};
+use crate::agents::utility::{Selection, UtilityAgent};
+use crate::agents::wall_follower::{Hand, WallFollowerAgent};
+use crate::agents::Agent;
+use crate::algorithms::astar::{find_path, path_cost as route_cost};
+use crate::engine::action::Outcome;
+use crate::engine::connectivity::{generate_checked, Solvability};
+use crate::engine::mapgen::MapGenerator;
+use crate::engine::rng::{random_seed, seeded, SimRng};
+use crate::engine::dynamics::{ObstacleDynamics, ObstacleRule};
+use crate::engine::goals::{best_goal_route, GoalSampling};
+use crate::engine::grid::Connectivity;
+use crate::engine::world::{Grid, Position, World};
 use crate::logging::metrics::{write_episode_logs_csv, EpisodeLog};
 
 /// Which agent implementation to evaluate.
@@ -17,9 +38,38 @@ pub enum AgentType {
     Fsm,
     AStar,
     BehaviorTree,
+    /// Incremental replanner (D* Lite); repairs its plan as the grid changes.
+    DStarLite,
+    /// Tabular Q-learning; keeps learning across the episodes of a batch.
+    QLearning,
+    /// Monte Carlo Tree Search with an iteration budget per move.
+    Mcts,
+    /// Explores the nearest frontier until the goal is in sight.
+    Frontier,
+    /// Descends a potential field; memory repels it from recent cells.
+    PotentialField,
+    /// Keeps one hand on the wall after first meeting one.
+    WallFollower(Hand),
+    /// Bug1/Bug2: heads for the goal, following obstacles it hits.
+    Bug(BugVariant),
+    /// LRTA*: learns a heuristic table across the episodes of a batch.
+    Lrta,
+    /// Utility AI: scores candidate actions and picks one by `Selection`.
+    Utility(Selection),
+    /// GOAP: plans symbolic tasks (rest, explore, go to goal) with A*.
+    Goap,
+}
+
+impl AgentType {
+    /// Whether one agent is kept for a whole batch so that what it learns
+    /// in one episode carries over to the next.
+    pub fn learns_across_episodes(&self) -> bool {
+        matches!(self, AgentType::QLearning | AgentType::Lrta)
+    }
 }
 
 /// Configuration for a batch of episodes.
+#[derive(Clone)]
 pub struct ExperimentConfig {
     pub episodes: u32,
     pub grid_width: usize,
@@ -31,10 +81,48 @@ pub struct ExperimentConfig {
     pub max_steps: u32,
     // --- Cognitive parameters ---
     pub noise: f32,
-    /// A* planning limit. `None` = unlimited.
+    /// Planning budget: A* node expansions, MCTS iterations per move, or
+    /// LRTA* lookahead depth. `None` = unlimited for A*,
+    /// `mcts::DEFAULT_ITERATIONS` for MCTS and 1 for LRTA*.
     pub planning_limit: Option<usize>,
     pub memory_capacity: usize,
     pub decay_rate: f32,
+    /// Base seed for the batch; episode `i` runs with `seed + i`.
+    /// `None` draws a fresh base seed for every batch.
+    pub seed: Option<u64>,
+    /// Fixed layout (e.g. loaded from a map file) used for every episode.
+    /// Overrides the grid size and obstacle density when set.
+    pub map: Option<Grid>,
+    /// Procedural generator for per-episode layouts. `None` scatters
+    /// obstacles at `obstacle_density`. Ignored when `map` is set.
+    pub generator: Option<MapGenerator>,
+    /// What to do with generated layouts whose goal is unreachable.
+    /// Defaults to rejecting them so sweeps only measure solvable episodes.
+    pub solvability: Solvability,
+    /// Movement neighbourhood for every episode. `None` keeps the map's
+    /// own setting (4-connected for generated layouts).
+    pub connectivity: Option<Connectivity>,
+    /// Obstacles that appear, disappear or move during each episode.
+    pub dynamics: Vec<ObstacleRule>,
+    /// Limited perception for the planning agents (A*, D* Lite, frontier
+    /// explorer, GOAP), which then plan on a belief map. `None` gives them the
+    /// full grid.
+    pub sensor: Option<Sensor>,
+    /// Random start and goal cells for every episode, replacing the
+    /// layout's own. `None` keeps the map's or generator's placement.
+    pub goal_sampling: Option<GoalSampling>,
+    /// Starting Q-table for `AgentType::QLearning` (empty when `None`).
+    pub q_table: Option<QTable>,
+    /// Starting heuristic table for `AgentType::Lrta` (empty when `None`).
+    pub heuristic_table: Option<HeuristicTable>,
+    /// Whether learning agents update what they know during the batch;
+    /// turn off to evaluate a trained table.
+    pub learning: bool,
+    /// Force weights for `AgentType::PotentialField`.
+    pub field_weights: FieldWeights,
+    /// Tree for `AgentType::BehaviorTree`, e.g. loaded with
+    /// `bt_format::load_tree`. `None` uses the built-in tree.
+    pub behavior_tree: Option<Node>,
 }
 
 impl Default for ExperimentConfig {
@@ -50,6 +138,19 @@ impl Default for ExperimentConfig {
             planning_limit: None,
             memory_capacity: 0,
             decay_rate: 1.0,
+            seed: None,
+            map: None,
+            generator: None,
+            solvability: Solvability::Reject { max_attempts: 100 },
+            connectivity: None,
+            dynamics: Vec::new(),
+            sensor: None,
+            goal_sampling: None,
+            q_table: None,
+            heuristic_table: None,
+            learning: true,
+            field_weights: FieldWeights::default(),
+            behavior_tree: None,
         }
     }
 }
@@ -78,114 +179,539 @@ pub fn run_batch_and_save(config: &ExperimentConfig) -> Result<PathBuf, Box<dyn
 
 /// Run a batch of episodes and return the collected episode logs.
 pub fn run_batch(config: &ExperimentConfig) -> Vec<EpisodeLog> {
+    run_episodes(config).0
+}
+
+/// Train a Q-learning agent over a batch (`config.agent_type` is treated
+/// as `QLearning`) and return the logs along with the learned table.
+pub fn train_q_learning(config: &ExperimentConfig) -> (Vec<EpisodeLog>, QTable) {
+    let config = ExperimentConfig {
+        agent_type: AgentType::QLearning,
+        ..config.clone()
+    };
+    let (logs, agent) = run_episodes(&config);
+    let table = agent
+        .as_ref()
+        .and_then(|agent| agent.as_any().downcast_ref::<QLearningAgent>())
+        .map(|agent| agent.table().clone())
+        .unwrap_or_default();
+    (logs, table)
+}
+
+/// Train an LRTA* agent over a batch (`config.agent_type` is treated as
+/// `Lrta`) and return the logs, whose `learning_delta` traces convergence,
+/// along with the learned heuristic table. Without a fixed `map` every
+/// episode starts from the configured table and the last one's is returned.
+pub fn train_lrta(config: &ExperimentConfig) -> (Vec<EpisodeLog>, HeuristicTable) {
+    let config = ExperimentConfig {
+        agent_type: AgentType::Lrta,
+        ..config.clone()
+    };
+    let (logs, agent) = run_episodes(&config);
+    let table = agent
+        .as_ref()
+        .and_then(|agent| agent.as_any().downcast_ref::<LrtaAgent>())
+        .map(|agent| agent.table().clone())
+        .unwrap_or_default();
+    (logs, table)
+}
+
+/// Run every episode of a batch, keeping one agent throughout when the
+/// agent type learns across episodes. Returns the last episode's agent.
+///
+/// LRTA*'s table is keyed by cell and goal only, so values learned on one
+/// generated layout could overestimate on the next; it is only kept when
+/// every episode uses the same `map`.
+fn run_episodes(config: &ExperimentConfig) -> (Vec<EpisodeLog>, Option<Box<dyn Agent>>) {
     let mut logs = Vec::with_capacity(config.episodes as usize);
+    let base_seed = config.seed.unwrap_or_else(random_seed);
+    let keep = match config.agent_type {
+        AgentType::Lrta => config.map.is_some(),
+        agent_type => agent_type.learns_across_episodes(),
+    };
+    let mut last = None;
 
     for episode in 0..config.episodes {
-        let log = run_single_episode(config, episode);
+        let seed = base_seed.wrapping_add(episode as u64);
+        let kept = if keep { last.take() } else { None };
+        let (log, agent) = play_episode(config, episode, seed, kept);
+        last = Some(agent);
         logs.push(log);
     }
 
-    logs
+    (logs, last)
 }
 
-fn run_single_episode(config: &ExperimentConfig, episode_idx: u32) -> EpisodeLog {
+/// Run a single episode driven entirely by `seed`.
+///
+/// Passing the `seed` recorded in an `EpisodeLog` (with the same config)
+/// replays that episode exactly.
+pub fn run_episode(config: &ExperimentConfig, episode_idx: u32, seed: u64) -> EpisodeLog {
+    play_episode(config, episode_idx, seed, None).0
+}
+
+/// Run one episode with `agent`, or a fresh agent from `config` if
+/// `None`, and hand the agent back afterwards.
+fn play_episode(
+    config: &ExperimentConfig,
+    episode_idx: u32,
+    seed: u64,
+    agent: Option<Box<dyn Agent>>,
+) -> (EpisodeLog, Box<dyn Agent>) {
+    let mut rng = seeded(seed);
     let mut steps = 0u32;
+    let mut path_cost = 0.0f32;
     let mut success = false;
-    let energy_remaining: u32;
 
-    let goal = Position {
-        x: config.grid_width - 1,
-        y: config.grid_height - 1,
+    // Also reports whether goal sampling found a placement; if not, the
+    // layout keeps its own start and goals.
+    let with_overrides = |mut grid: Grid, rng: &mut SimRng| {
+        if let Some(connectivity) = config.connectivity {
+            grid.connectivity = connectivity;
+        }
+        let mut placed = true;
+        if let Some(sampling) = config.goal_sampling {
+            placed = grid.place_start_and_goals(sampling.goals, sampling.min_distance, rng);
+            grid.goal_mode = sampling.mode;
+        }
+        (grid, placed)
     };
-    let grid = make_grid_with_obstacles(config, goal);
-
-    match config.agent_type {
-        AgentType::Fsm => {
-            let mut agent = FSMAgent::with_config(0, 0, config.noise, config.memory_capacity, config.decay_rate);
-
-            while steps < config.max_steps {
-                if agent.state() == FSMState::FoundGoal {
-                    success = true;
-                    break;
+    let (mut grid, placed) = match &config.map {
+        Some(map) => with_overrides(map.clone(), &mut rng),
+        // Overrides are applied per attempt so the solvability check sees
+        // the same neighbourhood and goals the agents will have, and a
+        // failed placement rejects the layout.
+        None => generate_checked(config.solvability, &mut rng, |rng| {
+            let grid = match config.generator {
+                Some(generator) => generator.generate(config.grid_width, config.grid_height, rng),
+                None => {
+                    let goal = Position {
+                        x: config.grid_width - 1,
+                        y: config.grid_height - 1,
+                    };
+                    make_grid_with_obstacles(config, goal, rng)
                 }
-                agent.update(&grid);
-                steps += 1;
-            }
+            };
+            with_overrides(grid, rng)
+        }),
+    };
+    let start = grid.start;
+    let optimal_path_len = grid.optimal_path_len();
+    let optimal_path_cost = best_goal_route(start, &grid.goals(), grid.goal_mode, |a, b| {
+        find_path((a.x, a.y), (b.x, b.y), &grid, None).map(|path| route_cost(&grid, &path))
+    });
+    // Optimal figures above describe the layout before any dynamics run.
+    let mut dynamics = ObstacleDynamics::new(&config.dynamics, &mut grid, &[], &mut rng);
+    let mut obstacle_changes = 0u32;
+
+    let agent = agent.unwrap_or_else(|| make_agent(config, start));
+    let agent_type = agent.name().to_string();
+    let learned_before = agent.learning_progress().unwrap_or(0.0);
+    let mut world = World::with_rng(grid, agent, seed, rng);
 
-            energy_remaining = agent.energy();
+    while steps < config.max_steps {
+        if world.has_reached_goal() {
+            success = true;
+            break;
+        }
+        // A cut-off goal may reopen on a dynamic grid, so only give up
+        // when nothing can change.
+        if world.is_agent_stuck() && config.dynamics.is_empty() {
+            break;
+        }
+        let occupied = [world.body.position];
+        obstacle_changes += dynamics.step(&mut world.grid, &occupied, &mut world.rng) as u32;
+        if let Outcome::Moved { from, to } = world.update() {
+            path_cost += world.grid.step_cost((from.x, from.y), (to.x, to.y)).unwrap_or(0.0);
         }
+        steps += 1;
+    }
+
+    let log = EpisodeLog {
+        episode: episode_idx,
+        agent_type,
+        map: world.grid.metadata.get("name").cloned().unwrap_or_default(),
+        connectivity: world.grid.connectivity.label(),
+        steps,
+        success,
+        goal_mode: world.grid.goal_mode.label().to_string(),
+        goals: world.grid.goals().len() as u32,
+        goals_reached: world.progress.reached() as u32,
+        energy_remaining: world.body.energy,
+        solvable: optimal_path_len.is_some(),
+        optimal_path_len: optimal_path_len.map(|len| len as u32),
+        path_cost,
+        optimal_path_cost,
+        obstacle_changes,
+        replans: world.agent.replans().unwrap_or(0),
+        learning_delta: world.agent.learning_progress().map_or(0.0, |total| total - learned_before),
+        sensor_radius: config.sensor.map_or(0, |sensor| sensor.radius as u32),
+        noise: config.noise,
+        planning_limit: planning_budget(config).unwrap_or(0) as u32,
+        memory_capacity: config.memory_capacity as u32,
+        decay_rate: config.decay_rate,
+        seed,
+        sampling_failed: !placed,
+    };
+    (log, world.agent)
+}
+
+/// Build the configured agent at `start`, wrapped for the world.
+fn make_agent(config: &ExperimentConfig, start: Position) -> Box<dyn Agent> {
+    match config.agent_type {
+        AgentType::Fsm => legacy(FSMAgent::with_config(
+            start.x, start.y,
+            config.noise,
+            config.memory_capacity,
+            config.decay_rate,
+        )),
         AgentType::AStar => {
             let mut agent = AStarAgent::with_config(
-                0, 0,
+                start.x, start.y,
                 config.planning_limit,
                 config.noise,
                 config.memory_capacity,
                 config.decay_rate,
             );
-
-            while steps < config.max_steps {
-                if agent.position() == grid.goal {
-                    success = true;
-                    break;
-                }
-                if agent.is_stuck() {
-                    break;
-                }
-                agent.update(&grid);
-                steps += 1;
+            if let Some(sensor) = config.sensor {
+                agent = agent.with_sensor(sensor);
             }
-
-            energy_remaining = 0;
+            legacy(agent)
         }
         AgentType::BehaviorTree => {
-            let mut agent = BehaviorTreeAgent::with_config(0, 0, config.noise, config.memory_capacity, config.decay_rate);
-
-            while steps < config.max_steps {
-                if agent.position() == grid.goal {
-                    success = true;
-                    break;
-                }
-                agent.update(&grid);
-                steps += 1;
+            let mut agent = BehaviorTreeAgent::with_config(
+                start.x, start.y,
+                config.noise,
+                config.memory_capacity,
+                config.decay_rate,
+            );
+            if let Some(tree) = &config.behavior_tree {
+                agent = agent.with_tree(tree.clone());
             }
-
-            energy_remaining = agent.energy();
+            legacy(agent)
+        }
+        AgentType::DStarLite => {
+            let mut agent = DStarLiteAgent::with_config(
+                start.x, start.y,
+                config.noise,
+                config.memory_capacity,
+                config.decay_rate,
+            );
+            if let Some(sensor) = config.sensor {
+                agent = agent.with_sensor(sensor);
+            }
+            legacy(agent)
+        }
+        AgentType::QLearning => {
+            let mut agent = QLearningAgent::with_config(config.noise, config.decay_rate)
+                .with_table(config.q_table.clone().unwrap_or_default());
+            agent.learning = config.learning;
+            Box::new(agent)
+        }
+        AgentType::Goap => {
+            let mut agent = GoapAgent::with_config(config.noise, config.decay_rate);
+            if let Some(sensor) = config.sensor {
+                agent = agent.with_sensor(sensor);
+            }
+            Box::new(agent)
+        }
+        AgentType::Utility(selection) => Box::new(
+            UtilityAgent::with_config(config.noise, config.memory_capacity, config.decay_rate)
+                .with_selection(selection),
+        ),
+        AgentType::Mcts => Box::new(MctsAgent::with_config(
+            planning_budget(config).unwrap_or(mcts::DEFAULT_ITERATIONS),
+            config.noise,
+            config.decay_rate,
+        )),
+        AgentType::Frontier => {
+            let mut agent = FrontierAgent::with_config(config.noise, config.decay_rate);
+            if let Some(sensor) = config.sensor {
+                agent = agent.with_sensor(sensor);
+            }
+            Box::new(agent)
+        }
+        AgentType::PotentialField => Box::new(
+            PotentialFieldAgent::with_config(config.noise, config.memory_capacity, config.decay_rate)
+                .with_weights(config.field_weights),
+        ),
+        AgentType::WallFollower(hand) => {
+            Box::new(WallFollowerAgent::with_config(hand, config.noise, config.decay_rate))
+        }
+        AgentType::Bug(variant) => Box::new(BugAgent::with_config(variant, config.noise, config.decay_rate)),
+        AgentType::Lrta => {
+            let lookahead = planning_budget(config).unwrap_or(1);
+            let mut agent = LrtaAgent::with_config(lookahead, config.noise, config.decay_rate)
+                .with_table(config.heuristic_table.clone().unwrap_or_default());
+            agent.learning = config.learning;
+            Box::new(agent)
         }
     }
+}
 
-    EpisodeLog {
-        episode: episode_idx,
-        agent_type: match config.agent_type {
-            AgentType::Fsm => "FSM".to_string(),
-            AgentType::AStar => "AStar".to_string(),
-            AgentType::BehaviorTree => "BehaviorTree".to_string(),
-        },
-        steps,
-        success,
-        energy_remaining,
-        noise: config.noise,
-        planning_limit: config.planning_limit.unwrap_or(0) as u32,
-        memory_capacity: config.memory_capacity as u32,
-        decay_rate: config.decay_rate,
+/// The planning budget the configured agent actually runs with.
+fn planning_budget(config: &ExperimentConfig) -> Option<usize> {
+    match config.agent_type {
+        AgentType::Mcts => Some(config.planning_limit.unwrap_or(mcts::DEFAULT_ITERATIONS)),
+        AgentType::Lrta => Some(config.planning_limit.unwrap_or(1)),
+        _ => config.planning_limit,
     }
 }
 
-fn make_grid_with_obstacles(config: &ExperimentConfig, goal: Position) -> Grid {
-    let mut rng = rand::thread_rng();
-    let mut obstacles = Vec::new();
+fn make_grid_with_obstacles(config: &ExperimentConfig, goal: Position, rng: &mut SimRng) -> Grid {
+    let mut grid = Grid::new(config.grid_width, config.grid_height, goal);
 
     for y in 0..config.grid_height {
         for x in 0..config.grid_width {
             // Always keep start and goal walkable.
-            if (x == 0 && y == 0) || (x == goal.x && y == goal.y) {
+            let pos = Position { x, y };
+            if pos == grid.start || pos == goal {
                 continue;
             }
 
             if rng.gen_range(0.0f32..1.0f32) < config.obstacle_density {
-                obstacles.push((x, y));
+                grid.set_walkable(x, y, false);
             }
         }
     }
 
-    Grid::with_obstacles(config.grid_width, config.grid_height, goal, &obstacles)
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_replays_identical_episode() {
+        let config = ExperimentConfig {
+            agent_type: AgentType::Fsm,
+            obstacle_density: 0.2,
+            noise: 0.3,
+            memory_capacity: 5,
+            max_steps: 200,
+            ..Default::default()
+        };
+
+        let first = run_episode(&config, 0, 42);
+        let replay = run_episode(&config, 0, 42);
+        assert_eq!(first.seed, 42);
+        assert_eq!(first.steps, replay.steps);
+        assert_eq!(first.success, replay.success);
+        assert_eq!(first.energy_remaining, replay.energy_remaining);
+    }
+
+    #[test]
+    fn batch_seeds_are_derived_from_base_seed() {
+        let config = ExperimentConfig {
+            episodes: 3,
+            seed: Some(7),
+            ..Default::default()
+        };
+
+        let seeds: Vec<u64> = run_batch(&config).iter().map(|log| log.seed).collect();
+        assert_eq!(seeds, vec![7, 8, 9]);
+    }
+
+    #[test]
+    fn dynamic_obstacles_change_the_grid_and_replay() {
+        let config = ExperimentConfig {
+            agent_type: AgentType::AStar,
+            dynamics: vec![
+                ObstacleRule::Wanderers { count: 3, move_chance: 0.5 },
+                ObstacleRule::SpawnWalls { chance: 0.2, lifetime: 5 },
+            ],
+            ..Default::default()
+        };
+
+        let first = run_episode(&config, 0, 11);
+        let replay = run_episode(&config, 0, 11);
+        assert!(first.obstacle_changes > 0);
+        assert_eq!(first.obstacle_changes, replay.obstacle_changes);
+        assert_eq!(first.steps, replay.steps);
+    }
+
+    #[test]
+    fn door_strands_astar_but_dstar_waits_it_out() {
+        let corridor = Grid::new(10, 1, Position { x: 9, y: 0 });
+        let config = |agent_type| ExperimentConfig {
+            agent_type,
+            map: Some(corridor.clone()),
+            dynamics: vec![ObstacleRule::Door { pos: Position { x: 5, y: 0 }, period: 6, open_for: 2 }],
+            ..Default::default()
+        };
+
+        let astar = run_episode(&config(AgentType::AStar), 0, 0);
+        assert!(!astar.success);
+        assert_eq!(astar.replans, 1);
+
+        let dstar = run_episode(&config(AgentType::DStarLite), 0, 0);
+        assert!(dstar.success);
+        assert!(dstar.replans > 0);
+        assert!(dstar.steps > 9);
+    }
+
+    #[test]
+    fn limited_sensing_discovers_walls_and_replans() {
+        // A wall across the direct route with a single gap at the bottom.
+        let wall: Vec<_> = (0..4).map(|y| (5, y)).collect();
+        let map = Grid::with_obstacles(10, 5, Position { x: 9, y: 0 }, &wall);
+        for agent_type in [AgentType::AStar, AgentType::DStarLite, AgentType::Frontier] {
+            let full = ExperimentConfig { agent_type, map: Some(map.clone()), ..Default::default() };
+            let sensed = ExperimentConfig {
+                sensor: Some(Sensor::new(2, true)),
+                ..ExperimentConfig { agent_type, map: Some(map.clone()), ..Default::default() }
+            };
+
+            let full = run_episode(&full, 0, 0);
+            let sensed = run_episode(&sensed, 0, 0);
+            assert!(full.success && sensed.success);
+            assert_eq!(full.replans, 0);
+            assert!(sensed.replans > 0);
+            assert!(sensed.steps >= full.steps);
+            assert_eq!(sensed.sensor_radius, 2);
+        }
+    }
+
+    #[test]
+    fn diagonal_episodes_reach_goal_in_fewer_steps() {
+        use crate::engine::grid::CornerRule;
+
+        let four = ExperimentConfig { agent_type: AgentType::AStar, ..Default::default() };
+        let eight = ExperimentConfig {
+            connectivity: Some(Connectivity::Eight(CornerRule::NoCut)),
+            ..Default::default()
+        };
+
+        let log4 = run_episode(&four, 0, 1);
+        let log8 = run_episode(&eight, 0, 1);
+        assert!(log4.success && log8.success);
+        assert_eq!(log4.steps, 13);
+        assert_eq!(log8.steps, 9);
+        assert_eq!(log8.connectivity, "8/no_cut");
+    }
+
+    #[test]
+    fn visit_all_goals_with_sampled_placement() {
+        use crate::engine::goals::GoalMode;
+
+        for agent_type in [AgentType::AStar, AgentType::DStarLite] {
+            let config = ExperimentConfig {
+                agent_type,
+                grid_width: 12,
+                grid_height: 8,
+                obstacle_density: 0.15,
+                goal_sampling: Some(GoalSampling { goals: 3, min_distance: 4, mode: GoalMode::All }),
+                ..Default::default()
+            };
+            let log = run_episode(&config, 0, 5);
+            assert_eq!((log.goals, log.goal_mode.as_str()), (3, "all"));
+            assert!(!log.sampling_failed);
+            assert!(log.success, "{:?}", agent_type);
+            assert_eq!(log.goals_reached, 3);
+            assert!(log.steps >= log.optimal_path_len.unwrap());
+        }
+    }
+
+    #[test]
+    fn failed_goal_sampling_is_logged() {
+        use crate::engine::goals::GoalMode;
+
+        // No cell on a 10x5 grid is 100 moves from anywhere.
+        let sampling = Some(GoalSampling { goals: 1, min_distance: 100, mode: GoalMode::Any });
+        let generated = ExperimentConfig { goal_sampling: sampling, ..Default::default() };
+        let fixed = ExperimentConfig { map: Some(Grid::load("maps/open_10x5.map").unwrap()), ..generated.clone() };
+        for config in [generated, fixed] {
+            assert!(run_episode(&config, 0, 1).sampling_failed);
+        }
+    }
+
+    #[test]
+    fn q_learning_improves_across_a_batch_and_evaluates_greedily() {
+        let wall: Vec<_> = (0..4).map(|y| (4, y)).collect();
+        let map = Grid::with_obstacles(8, 5, Position { x: 7, y: 0 }, &wall);
+        let training = ExperimentConfig {
+            episodes: 150,
+            map: Some(map.clone()),
+            noise: 0.3,
+            decay_rate: 0.999,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let (logs, table) = train_q_learning(&training);
+        assert!(!table.is_empty());
+        assert!(logs.last().unwrap().steps < logs[0].steps);
+
+        let evaluation = ExperimentConfig {
+            episodes: 1,
+            agent_type: AgentType::QLearning,
+            map: Some(map),
+            q_table: Some(table),
+            learning: false,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let log = &run_batch(&evaluation)[0];
+        assert!(log.success);
+        assert_eq!(Some(log.steps), log.optimal_path_len);
+    }
+
+    #[test]
+    fn lrta_logs_its_lookahead_and_learning_dies_down() {
+        let map = Grid::load("maps/u_trap.map").unwrap();
+        for depth in [1, 3] {
+            let config = ExperimentConfig {
+                episodes: 40,
+                map: Some(map.clone()),
+                planning_limit: Some(depth),
+                seed: Some(0),
+                ..Default::default()
+            };
+            let (logs, table) = train_lrta(&config);
+            assert!(!table.is_empty());
+            assert!(logs.iter().all(|log| log.planning_limit == depth as u32 && log.agent_type == "LRTA*"));
+            assert!(logs[0].learning_delta > 0.0);
+            assert_eq!(logs.last().unwrap().learning_delta, 0.0);
+        }
+    }
+
+    #[test]
+    fn lrta_starts_fresh_on_every_generated_layout() {
+        let config = ExperimentConfig {
+            agent_type: AgentType::Lrta,
+            episodes: 5,
+            obstacle_density: 0.2,
+            seed: Some(3),
+            ..Default::default()
+        };
+        for (episode, log) in run_batch(&config).iter().enumerate() {
+            let alone = run_episode(&config, episode as u32, log.seed);
+            assert_eq!(log.steps, alone.steps);
+            assert_eq!(log.learning_delta, alone.learning_delta);
+        }
+    }
+
+    #[test]
+    fn mcts_budget_is_logged_and_buys_shorter_routes() {
+        let wall: Vec<_> = (0..4).map(|y| (4, y)).collect();
+        let map = Grid::with_obstacles(9, 5, Position { x: 8, y: 0 }, &wall);
+        let steps = |budget| {
+            let config = ExperimentConfig {
+                agent_type: AgentType::Mcts,
+                episodes: 5,
+                map: Some(map.clone()),
+                planning_limit: budget,
+                seed: Some(1),
+                ..Default::default()
+            };
+            let logs = run_batch(&config);
+            assert!(logs.iter().all(|log| log.success));
+            (logs[0].planning_limit, logs.iter().map(|log| log.steps).sum::<u32>())
+        };
+
+        let (small_budget, small_steps) = steps(Some(10));
+        let (default_budget, default_steps) = steps(None);
+        assert_eq!((small_budget, default_budget), (10, mcts::DEFAULT_ITERATIONS as u32));
+        assert!(default_steps < small_steps);
+    }
 }
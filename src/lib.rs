@@ -1,4 +1,6 @@
 pub mod algorithms;
 pub mod agents;
 pub mod engine;
+pub mod experiments;
+pub mod logging;
 pub mod vis;
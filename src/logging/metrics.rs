@@ -7,22 +7,58 @@ pub struct EpisodeLog {
     pub episode: u32,
     /// Human-readable agent type, e.g. "FSM", "AStar", "BehaviorTree".
     pub agent_type: String,
+    /// Map name from the map file header or the generator label
+    /// (empty for plain random obstacles).
+    pub map: String,
+    /// Movement neighbourhood, e.g. `4` or `8/no_cut`.
+    pub connectivity: String,
     /// Number of steps taken in the episode.
     pub steps: u32,
-    /// Whether the agent reached the goal.
+    /// Whether the agent reached the goal (under `goal_mode all`, every goal).
     pub success: bool,
-    /// Agent's remaining energy at the end of the episode
-    /// (0 for agents that do not track energy).
+    /// `any` or `all` (see `GoalMode`).
+    pub goal_mode: String,
+    /// Number of goal cells on the grid.
+    pub goals: u32,
+    /// Distinct goals the agent visited.
+    pub goals_reached: u32,
+    /// Energy left in the agent's body at the end of the episode
+    /// (the world charges every agent the same terrain costs).
     pub energy_remaining: u32,
+    /// Whether the goal was reachable from the start at all.
+    pub solvable: bool,
+    /// Fewest moves from start to finishing: the nearest goal, or the
+    /// best tour of all goals (empty when unsolvable).
+    pub optimal_path_len: Option<u32>,
+    /// Total terrain cost of the cells the agent stepped onto
+    /// (diagonal steps count √2 times the terrain cost).
+    pub path_cost: f32,
+    /// Cheapest terrain cost of the same route (empty when unsolvable).
+    pub optimal_path_cost: Option<f32>,
+    /// Cells blocked or freed by obstacle dynamics during the episode.
+    pub obstacle_changes: u32,
+    /// Times a planning agent re-planned (0 for reactive agents).
+    pub replans: u32,
+    /// How much a learning agent raised its learned values this episode
+    /// (LRTA* heuristic increases); falls to 0 as learning converges.
+    pub learning_delta: f32,
+    /// Sensing radius of the planning agent (0 = sees the whole grid).
+    pub sensor_radius: u32,
     // --- Cognitive parameters ---
     /// Decision noise probability (0.0–1.0).
     pub noise: f32,
-    /// A* planning limit (0 = unlimited).
+    /// Planning budget: A* node expansions, MCTS iterations per move or
+    /// LRTA* lookahead depth (0 = unlimited).
     pub planning_limit: u32,
     /// Spatial memory capacity (0 = disabled).
     pub memory_capacity: u32,
     /// Exploration decay rate per tick.
     pub decay_rate: f32,
+    /// RNG seed the episode ran with; replays it exactly.
+    pub seed: u64,
+    /// Whether goal sampling found no start/goal placement, so the
+    /// episode ran on the layout's own start and goal.
+    pub sampling_failed: bool,
 }
 
 /// Optional per-step log for more detailed analysis.
@@ -1,10 +1,11 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
+use crate::engine::dynamics::ObstacleDynamics;
+use crate::engine::rng::{random_seed, seeded};
 use crate::engine::world::{Grid, Position};
-use crate::agents::fsm::FSMAgent;
-use crate::agents::astar::AStarAgent;
-use crate::agents::behavior_tree::BehaviorTreeAgent;
-use super::resources::{SimState, HeatmapMaterials};
+use crate::agents::Agent;
+use crate::agents::qlearning::QLearningAgent;
+use super::resources::{viewer_agents, MapSource, SimState, HeatmapMaterials};
 use super::components::{AgentKind, AgentMarker, OrbitCamera, GoalMarker};
 
 // Constants replicated for setup. Ideally these should be in a shared config or passed in.
@@ -13,6 +14,8 @@ const GRID_H: usize = 8;
 const CELL_SIZE: f32 = 1.0;
 const AGENT_Y: f32 = 0.35;
 const OBSTACLE_DENSITY: f32 = 0.15;
+/// Shades in the potential-field overlay.
+const FIELD_LEVELS: usize = 8;
 
 fn grid_to_world(pos: Position, y_offset: f32) -> Vec3 {
     Vec3::new(
@@ -27,6 +30,9 @@ fn agent_color(kind: AgentKind) -> Color {
         AgentKind::Fsm => Color::srgb(0.2, 0.8, 0.4),        // green
         AgentKind::AStar => Color::srgb(0.3, 0.5, 1.0),       // blue
         AgentKind::BehaviorTree => Color::srgb(1.0, 0.4, 0.2), // orange
+        AgentKind::QLearning => Color::srgb(0.85, 0.3, 0.85),  // magenta
+        AgentKind::Mcts => Color::srgb(0.2, 0.8, 0.85),        // cyan
+        AgentKind::PotentialField => Color::srgb(1.0, 0.55, 0.7), // pink
     }
 }
 
@@ -35,6 +41,9 @@ fn agent_y_offset(kind: AgentKind) -> f32 {
         AgentKind::Fsm => AGENT_Y,
         AgentKind::AStar => AGENT_Y + 0.01,
         AgentKind::BehaviorTree => AGENT_Y + 0.02,
+        AgentKind::QLearning => AGENT_Y + 0.03,
+        AgentKind::Mcts => AGENT_Y + 0.04,
+        AgentKind::PotentialField => AGENT_Y + 0.05,
     }
 }
 
@@ -42,13 +51,25 @@ pub fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    map_source: Res<MapSource>,
 ) {
-    let goal = Position {
-        x: GRID_W - 1,
-        y: GRID_H - 1,
+    let seed = random_seed();
+    let mut rng = seeded(seed);
+    let grid = match &map_source.grid {
+        Some(map) => map.clone(),
+        None => {
+            let goal = Position {
+                x: GRID_W - 1,
+                y: GRID_H - 1,
+            };
+            let mut grid = Grid::new(GRID_W, GRID_H, goal);
+            grid.scatter_obstacles(OBSTACLE_DENSITY, &mut rng);
+            grid.repair_connectivity();
+            grid
+        }
     };
-    let mut grid = Grid::new(GRID_W, GRID_H, goal);
-    grid.scatter_obstacles(OBSTACLE_DENSITY);
+    let (grid_w, grid_h) = (grid.width, grid.height);
+    let start = grid.start;
 
     // ── Materials ──────────────────────────
     let default_light = materials.add(StandardMaterial {
@@ -72,26 +93,69 @@ pub fn setup(
         base_color: Color::srgb(0.95, 0.7, 0.6), 
         ..default()
     });
+    let q_visited = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.65, 0.9),
+        ..default()
+    });
+    let mcts_visited = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.6, 0.9, 0.92),
+        ..default()
+    });
+    let pf_visited = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.97, 0.75, 0.82),
+        ..default()
+    });
     let multi_visited = materials.add(StandardMaterial {
         base_color: Color::srgb(0.6, 0.5, 0.6), 
         ..default()
     });
 
+    // Potential-field overlay, from low (blue) to high (red) potential.
+    let field_levels = (0..FIELD_LEVELS)
+        .map(|i| {
+            let t = i as f32 / (FIELD_LEVELS - 1) as f32;
+            materials.add(StandardMaterial {
+                base_color: Color::srgb(0.2 + 0.7 * t, 0.35, 0.9 - 0.7 * t),
+                ..default()
+            })
+        })
+        .collect();
+
+    let sand = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.93, 0.85, 0.55),
+        ..default()
+    });
+    let mud = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.33, 0.2),
+        ..default()
+    });
+    let water = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.25, 0.45, 0.85),
+        ..default()
+    });
+
     commands.insert_resource(HeatmapMaterials {
         default_light: default_light.clone(),
         default_dark: default_dark.clone(),
         fsm_visited,
         astar_visited,
         bt_visited,
+        q_visited,
+        mcts_visited,
+        pf_visited,
         multi_visited,
+        field_levels,
+        sand,
+        mud,
+        water,
     });
 
     let cell_mesh = meshes.add(Cuboid::new(CELL_SIZE * 0.95, 0.05, CELL_SIZE * 0.95));
 
-    let mut grid_tile_entities = vec![vec![Entity::PLACEHOLDER; GRID_W]; GRID_H];
+    let mut grid_tile_entities = vec![vec![Entity::PLACEHOLDER; grid_w]; grid_h];
 
-    for y in 0..GRID_H {
-        for x in 0..GRID_W {
+    for y in 0..grid_h {
+        for x in 0..grid_w {
             let mat = if (x + y) % 2 == 0 {
                 default_light.clone()
             } else {
@@ -106,27 +170,35 @@ pub fn setup(
         }
     }
 
-    // ── Goal marker ─────────────────────────────────────
-    commands.spawn((
-        Mesh3d(meshes.add(Cylinder::new(0.3, 0.6))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(1.0, 0.85, 0.0),
-            emissive: bevy::color::LinearRgba::new(2.0, 1.7, 0.0, 1.0),
-            ..default()
-        })),
-        Transform::from_xyz(
-            goal.x as f32 * CELL_SIZE,
-            0.35,
-            goal.y as f32 * CELL_SIZE,
-        ),
-        GoalMarker,
-    ));
+    // ── Goal markers ────────────────────────────────────
+    let goal_mesh = meshes.add(Cylinder::new(0.3, 0.6));
+    let goal_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(1.0, 0.85, 0.0),
+        emissive: bevy::color::LinearRgba::new(2.0, 1.7, 0.0, 1.0),
+        ..default()
+    });
+    for goal in grid.goals() {
+        commands.spawn((
+            Mesh3d(goal_mesh.clone()),
+            MeshMaterial3d(goal_material.clone()),
+            Transform::from_xyz(
+                goal.x as f32 * CELL_SIZE,
+                0.35,
+                goal.y as f32 * CELL_SIZE,
+            ),
+            GoalMarker,
+        ));
+    }
 
     // ── Agent cubes ─────────────────────────────────────
     let agent_mesh = meshes.add(Cuboid::new(0.4, 0.4, 0.4));
-    for kind in [AgentKind::Fsm, AgentKind::AStar, AgentKind::BehaviorTree] {
+    let mut kinds = vec![AgentKind::Fsm, AgentKind::AStar, AgentKind::BehaviorTree, AgentKind::Mcts, AgentKind::PotentialField];
+    if map_source.q_table.is_some() {
+        kinds.push(AgentKind::QLearning);
+    }
+    for kind in kinds {
         let color = agent_color(kind);
-        let pos = Position { x: 0, y: 0 };
+        let pos = start;
         commands.spawn((
             Mesh3d(agent_mesh.clone()),
             MeshMaterial3d(materials.add(StandardMaterial {
@@ -148,17 +220,17 @@ pub fn setup(
             ..default()
         },
         Transform::from_xyz(
-            GRID_W as f32 * 0.5,
+            grid_w as f32 * 0.5,
             14.0,
-            GRID_H as f32 * 0.5,
+            grid_h as f32 * 0.5,
         ),
     ));
 
     // ── Orbital camera ──────────────────────────────────
     let focus = Vec3::new(
-        (GRID_W as f32 - 1.0) * 0.5,
+        (grid_w as f32 - 1.0) * 0.5,
         0.0,
-        (GRID_H as f32 - 1.0) * 0.5,
+        (grid_h as f32 - 1.0) * 0.5,
     );
     let radius = 14.0;
     let yaw: f32 = -0.6;
@@ -181,22 +253,24 @@ pub fn setup(
     ));
 
     // ── Simulation state ────────────────────────────────
-    let fsm = FSMAgent::with_config(0, 0, 0.15, 10, 0.995);
-    let astar = AStarAgent::with_config(0, 0, Some(30), 0.1, 10, 0.995);
-    let bt = BehaviorTreeAgent::with_config(0, 0, 0.15, 10, 0.995);
+    // A loaded Q-table is shown greedily, without further learning.
+    let qlearning = map_source.q_table.clone().map(|table| {
+        let mut agent = QLearningAgent::new().with_table(table);
+        agent.learning = false;
+        Box::new(agent) as Box<dyn Agent + Send + Sync>
+    });
 
     commands.insert_resource(SimState {
         grid,
-        fsm,
-        astar,
-        bt,
+        agents: viewer_agents(start, qlearning),
         tick_timer: 0.0,
         total_ticks: 0,
-        fsm_done: false,
-        astar_done: false,
-        bt_done: false,
         all_done_printed: false,
         cell_visitors: HashMap::new(),
         grid_tile_entities,
+        seed,
+        rng,
+        dynamics: ObstacleDynamics::default(),
+        obstacles_changed: false,
     });
 }
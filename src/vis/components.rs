@@ -6,6 +6,23 @@ pub enum AgentKind {
     Fsm,
     AStar,
     BehaviorTree,
+    QLearning,
+    Mcts,
+    PotentialField,
+}
+
+impl AgentKind {
+    /// Name shown in the UI and console.
+    pub fn label(self) -> &'static str {
+        match self {
+            AgentKind::Fsm => "FSM",
+            AgentKind::AStar => "A*",
+            AgentKind::BehaviorTree => "BT",
+            AgentKind::QLearning => "Q-learning",
+            AgentKind::Mcts => "MCTS",
+            AgentKind::PotentialField => "Potential field",
+        }
+    }
 }
 
 #[derive(Component)]
@@ -7,9 +7,14 @@ pub mod ui;
 
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
-use resources::UiState;
+use crate::agents::qlearning::QTable;
+use crate::engine::world::Grid;
+use resources::{MapSource, UiState};
 
-pub fn run() {
+/// Launch the viewer. With `Some(map)` every run uses that layout;
+/// otherwise obstacles are scattered randomly. With `Some(q_table)` a
+/// trained Q-learning agent runs alongside the others.
+pub fn run(map: Option<Grid>, q_table: Option<QTable>) {
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -21,6 +26,7 @@ pub fn run() {
         }))
         .add_plugins(EguiPlugin)
         .init_resource::<UiState>()
+        .insert_resource(MapSource { grid: map, q_table })
         .add_systems(Startup, app::setup)
         .add_systems(Update, (
             camera::orbit_camera,
@@ -1,9 +1,17 @@
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet};
+use crate::engine::dynamics::{ObstacleDynamics, ObstacleRule};
+use crate::engine::rng::{random_seed, seeded, SimRng};
 use crate::engine::world::{Grid, Position};
+use crate::agents::Agent;
+use crate::agents::adapter::LegacyAdapter;
 use crate::agents::fsm::FSMAgent;
 use crate::agents::astar::AStarAgent;
 use crate::agents::behavior_tree::BehaviorTreeAgent;
+use crate::agents::mcts::MctsAgent;
+use crate::agents::potential_field::PotentialFieldAgent;
+use crate::agents::qlearning::QTable;
+use crate::engine::action::{Body, Observation};
 use super::components::AgentKind;
 
 #[derive(Resource)]
@@ -12,6 +20,12 @@ pub struct UiState {
     pub time_scale: f32,
     pub show_heatmap: bool,
     pub show_path_gizmos: bool,
+    /// Add wandering blockers and short-lived walls on the next restart.
+    pub dynamic_obstacles: bool,
+    /// Colour tiles by the potential-field agent's field instead of visits.
+    pub show_field: bool,
+    /// Show the BT agent's tree with its last tick's results.
+    pub show_bt_trace: bool,
 }
 
 impl Default for UiState {
@@ -21,29 +35,105 @@ impl Default for UiState {
             time_scale: 1.0,
             show_heatmap: true,
             show_path_gizmos: true,
+            dynamic_obstacles: false,
+            show_field: false,
+            show_bt_trace: true,
         }
     }
 }
 
+/// Fixed layout and trained Q-table the viewer was launched with, if any.
+#[derive(Resource, Default)]
+pub struct MapSource {
+    pub grid: Option<Grid>,
+    pub q_table: Option<QTable>,
+}
+
+/// One agent on screen: its policy and the body the viewer moves for it,
+/// as in a `World`.
+pub struct ViewerAgent {
+    pub kind: AgentKind,
+    /// Bevy resources must be `Send + Sync`.
+    pub agent: Box<dyn Agent + Send + Sync>,
+    pub body: Body,
+    /// Reached the goal or got stuck; no longer stepped or drawn.
+    pub done: bool,
+}
+
+impl ViewerAgent {
+    pub fn new(kind: AgentKind, agent: Box<dyn Agent + Send + Sync>, start: Position) -> Self {
+        Self { kind, agent, body: Body::new(start), done: false }
+    }
+
+    /// Ask the agent for an action and apply it to the body, like
+    /// `World::update`.
+    pub fn step(&mut self, grid: &Grid, tick: usize, rng: &mut SimRng) {
+        let obs = Observation {
+            grid,
+            position: self.body.position,
+            energy: self.body.energy,
+            tick,
+            goal: grid.goal,
+        };
+        let action = self.agent.act(&obs, rng);
+        let outcome = self.body.apply(grid, action);
+        self.agent.on_outcome(&outcome);
+    }
+}
+
+/// The viewer's agents, all starting on `start`. `qlearning` is a trained
+/// Q-learning agent, shown when the viewer is given a Q-table.
+pub fn viewer_agents(start: Position, qlearning: Option<Box<dyn Agent + Send + Sync>>) -> Vec<ViewerAgent> {
+    let mut agents = vec![
+        ViewerAgent::new(AgentKind::Fsm, Box::new(LegacyAdapter::new(FSMAgent::with_config(start.x, start.y, 0.15, 10, 0.995))), start),
+        ViewerAgent::new(AgentKind::AStar, Box::new(LegacyAdapter::new(AStarAgent::with_config(start.x, start.y, Some(30), 0.1, 10, 0.995))), start),
+        ViewerAgent::new(AgentKind::BehaviorTree, Box::new(LegacyAdapter::new(BehaviorTreeAgent::with_config(start.x, start.y, 0.15, 10, 0.995))), start),
+    ];
+    if let Some(agent) = qlearning {
+        agents.push(ViewerAgent::new(AgentKind::QLearning, agent, start));
+    }
+    // MCTS's planning-radius gizmo shows how far its last search looked
+    // ahead; the potential-field agent's field can be drawn as an overlay.
+    agents.push(ViewerAgent::new(AgentKind::Mcts, Box::new(MctsAgent::with_config(200, 0.1, 0.995)), start));
+    agents.push(ViewerAgent::new(AgentKind::PotentialField, Box::new(PotentialFieldAgent::with_config(0.1, 10, 0.995)), start));
+    agents
+}
+
 #[derive(Resource)]
 pub struct SimState {
     pub grid: Grid,
-    pub fsm: FSMAgent,
-    pub astar: AStarAgent,
-    pub bt: BehaviorTreeAgent,
+    pub agents: Vec<ViewerAgent>,
     pub tick_timer: f32,
     pub total_ticks: u32,
-    pub fsm_done: bool,
-    pub astar_done: bool,
-    pub bt_done: bool,
     pub all_done_printed: bool,
     pub cell_visitors: HashMap<(usize, usize), HashSet<AgentKind>>,
     pub grid_tile_entities: Vec<Vec<Entity>>,
+    /// Seed of the current run, shown in the UI so it can be replayed.
+    pub seed: u64,
+    pub rng: SimRng,
+    pub dynamics: ObstacleDynamics,
+    /// Set when dynamics changed the grid so obstacle meshes are rebuilt.
+    pub obstacles_changed: bool,
 }
 
 impl SimState {
     pub fn is_all_done(&self) -> bool {
-        self.fsm_done && self.astar_done && self.bt_done
+        self.agents.iter().all(|viewer| viewer.done)
+    }
+
+    /// The agent shown as `kind`, if it is in this run.
+    pub fn agent(&self, kind: AgentKind) -> Option<&ViewerAgent> {
+        self.agents.iter().find(|viewer| viewer.kind == kind)
+    }
+
+    /// The behavior tree agent, for the tree panel.
+    pub fn bt(&self) -> Option<&BehaviorTreeAgent> {
+        self.agent(AgentKind::BehaviorTree)?.agent.as_any().downcast_ref()
+    }
+
+    /// The potential-field agent, for the field overlay.
+    pub fn potential(&self) -> Option<&PotentialFieldAgent> {
+        self.agent(AgentKind::PotentialField)?.agent.as_any().downcast_ref()
     }
 
     pub fn update_visits(&mut self, pos: Position, kind: AgentKind) {
@@ -55,18 +145,29 @@ impl SimState {
         }
     }
     
-    pub fn reset(&mut self, mut grid: Grid, obstacle_density: f32) {
-        grid.scatter_obstacles(obstacle_density);
+    pub fn reset(&mut self, mut grid: Grid, obstacle_density: f32, dynamics: &[ObstacleRule]) {
+        self.seed = random_seed();
+        self.rng = seeded(self.seed);
+        // Loaded maps are passed with density 0 and kept exactly as authored.
+        if obstacle_density > 0.0 {
+            grid.scatter_obstacles(obstacle_density, &mut self.rng);
+            grid.repair_connectivity();
+        }
         
+        self.dynamics = ObstacleDynamics::new(dynamics, &mut grid, &[], &mut self.rng);
+        self.obstacles_changed = true;
+
+        let start = grid.start;
         self.grid = grid;
-        self.fsm = FSMAgent::with_config(0, 0, 0.15, 10, 0.995);
-        self.astar = AStarAgent::with_config(0, 0, Some(30), 0.1, 10, 0.995);
-        self.bt = BehaviorTreeAgent::with_config(0, 0, 0.15, 10, 0.995);
+        // The Q-learning agent keeps its table; only its body is reset.
+        let qlearning = self
+            .agents
+            .iter()
+            .position(|viewer| viewer.kind == AgentKind::QLearning)
+            .map(|index| self.agents.remove(index).agent);
+        self.agents = viewer_agents(start, qlearning);
         self.tick_timer = 0.0;
         self.total_ticks = 0;
-        self.fsm_done = false;
-        self.astar_done = false;
-        self.bt_done = false;
         self.all_done_printed = false;
         self.cell_visitors.clear();
     }
@@ -79,5 +180,13 @@ pub struct HeatmapMaterials {
     pub fsm_visited: Handle<StandardMaterial>,
     pub astar_visited: Handle<StandardMaterial>,
     pub bt_visited: Handle<StandardMaterial>,
+    pub q_visited: Handle<StandardMaterial>,
+    pub mcts_visited: Handle<StandardMaterial>,
+    pub pf_visited: Handle<StandardMaterial>,
     pub multi_visited: Handle<StandardMaterial>,
+    /// Potential-field overlay shades, lowest potential first.
+    pub field_levels: Vec<Handle<StandardMaterial>>,
+    pub sand: Handle<StandardMaterial>,
+    pub mud: Handle<StandardMaterial>,
+    pub water: Handle<StandardMaterial>,
 }
@@ -1,9 +1,8 @@
 use bevy::prelude::*;
 use bevy::math::Isometry3d;
 use rand::Rng;
+use crate::engine::terrain::Terrain;
 use crate::engine::world::Position;
-use crate::agents::fsm::FSMState;
-use crate::agents::Agent;
 
 use super::components::{AgentKind, AgentMarker, TrailDot, Shaking, Obstacle, GoalMarker};
 use super::resources::{SimState, UiState, HeatmapMaterials};
@@ -12,8 +11,6 @@ use super::resources::{SimState, UiState, HeatmapMaterials};
 const CELL_SIZE: f32 = 1.0;
 const BASE_TICK_INTERVAL: f32 = 0.25;
 const AGENT_Y: f32 = 0.35;
-const GRID_W: usize = 12;
-const GRID_H: usize = 8;
 const MAX_TICKS: u32 = 1000;
 
 fn grid_to_world(pos: Position, y_offset: f32) -> Vec3 {
@@ -29,6 +26,9 @@ fn agent_color(kind: AgentKind) -> Color {
         AgentKind::Fsm => Color::srgb(0.2, 0.8, 0.4),        // green
         AgentKind::AStar => Color::srgb(0.3, 0.5, 1.0),       // blue
         AgentKind::BehaviorTree => Color::srgb(1.0, 0.4, 0.2), // orange
+        AgentKind::QLearning => Color::srgb(0.85, 0.3, 0.85),  // magenta
+        AgentKind::Mcts => Color::srgb(0.2, 0.8, 0.85),        // cyan
+        AgentKind::PotentialField => Color::srgb(1.0, 0.55, 0.7), // pink
     }
 }
 
@@ -37,6 +37,9 @@ fn agent_y_offset(kind: AgentKind) -> f32 {
         AgentKind::Fsm => AGENT_Y,
         AgentKind::AStar => AGENT_Y + 0.01,
         AgentKind::BehaviorTree => AGENT_Y + 0.02,
+        AgentKind::QLearning => AGENT_Y + 0.03,
+        AgentKind::Mcts => AGENT_Y + 0.04,
+        AgentKind::PotentialField => AGENT_Y + 0.05,
     }
 }
 
@@ -52,6 +55,8 @@ pub fn tick_simulation(
         return;
     }
 
+    // Reborrow so agent updates can borrow the RNG alongside the agents.
+    let sim = &mut *sim;
     sim.tick_timer += time.delta_secs() * ui_state.time_scale;
 
     while sim.tick_timer >= BASE_TICK_INTERVAL {
@@ -76,15 +81,12 @@ pub fn tick_simulation(
         sim.total_ticks += 1;
 
         let trail_mesh = meshes.add(Sphere::new(0.08));
-        let active_agents: Vec<(Position, AgentKind)> = [
-            (!sim.fsm_done, sim.fsm.position(), AgentKind::Fsm),
-            (!sim.astar_done, sim.astar.position(), AgentKind::AStar),
-            (!sim.bt_done, sim.bt.position(), AgentKind::BehaviorTree),
-        ]
-        .iter()
-        .filter(|(active, _, _)| *active)
-        .map(|(_, pos, kind)| (*pos, *kind))
-        .collect();
+        let active_agents: Vec<(Position, AgentKind)> = sim
+            .agents
+            .iter()
+            .filter(|viewer| !viewer.done)
+            .map(|viewer| (viewer.body.position, viewer.kind))
+            .collect();
 
         for (pos, kind) in &active_agents {
             let color = agent_color(*kind).with_alpha(0.3);
@@ -104,40 +106,28 @@ pub fn tick_simulation(
             ));
         }
 
-        let grid = sim.grid.clone();
-
-        if !sim.fsm_done {
-            sim.fsm.update(&grid);
-            let pos = sim.fsm.position();
-            sim.update_visits(pos, AgentKind::Fsm);
-            if sim.fsm.state() == FSMState::FoundGoal {
-                sim.fsm_done = true;
-                println!("✓ FSM reached goal at tick {}", sim.total_ticks);
-            }
+        let occupied: Vec<Position> = sim.agents.iter().map(|viewer| viewer.body.position).collect();
+        if sim.dynamics.step(&mut sim.grid, &occupied, &mut sim.rng) > 0 {
+            sim.obstacles_changed = true;
         }
 
-        if !sim.astar_done {
-            sim.astar.update(&grid);
-             let pos = sim.astar.position();
-            sim.update_visits(pos, AgentKind::AStar);
-            if sim.astar.position() == grid.goal || sim.astar.is_stuck() {
-                sim.astar_done = true;
-                if sim.astar.position() == grid.goal {
-                    println!("✓ A* reached goal at tick {}", sim.total_ticks);
-                } else {
-                    println!("✗ A* got stuck at tick {}", sim.total_ticks);
-                }
+        let grid = sim.grid.clone();
+        let tick = sim.total_ticks as usize;
+        let mut visits = Vec::new();
+        for viewer in sim.agents.iter_mut().filter(|viewer| !viewer.done) {
+            viewer.step(&grid, tick, &mut sim.rng);
+            let pos = viewer.body.position;
+            visits.push((pos, viewer.kind));
+            if pos == grid.goal {
+                viewer.done = true;
+                println!("✓ {} reached goal at tick {}", viewer.kind.label(), sim.total_ticks);
+            } else if viewer.agent.is_stuck() {
+                viewer.done = true;
+                println!("✗ {} got stuck at tick {}", viewer.kind.label(), sim.total_ticks);
             }
         }
-
-        if !sim.bt_done {
-            sim.bt.update(&grid);
-             let pos = sim.bt.position();
-            sim.update_visits(pos, AgentKind::BehaviorTree);
-            if sim.bt.position() == grid.goal {
-                sim.bt_done = true;
-                println!("✓ BT reached goal at tick {}", sim.total_ticks);
-            }
+        for (pos, kind) in visits {
+            sim.update_visits(pos, kind);
         }
     }
 }
@@ -148,21 +138,38 @@ pub fn render_heatmap(
     heatmap_mats: Res<HeatmapMaterials>,
     mut query: Query<&mut MeshMaterial3d<StandardMaterial>>,
 ) {
+    // The field overlay replaces the heatmap while it is shown.
+    let field = sim
+        .potential()
+        .filter(|_| ui_state.show_field)
+        .map(|potential| potential.field(&sim.grid, sim.grid.goal));
+    let (low, high) = field.iter().flatten().flatten().flatten().fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(low, high), &u| (low.min(u), high.max(u)),
+    );
+
     // FIX 9.1: We run this loop ALWAYS, to actively revert colors if toggled off
-    for y in 0..GRID_H {
-        for x in 0..GRID_W {
+    for y in 0..sim.grid.height {
+        for x in 0..sim.grid.width {
             let entity = sim.grid_tile_entities[y][x];
             if entity == Entity::PLACEHOLDER { continue; }
 
             if let Ok(mut mat) = query.get_mut(entity) {
-                let default_mat = if (x + y) % 2 == 0 { 
-                    heatmap_mats.default_light.clone() 
-                } else { 
-                    heatmap_mats.default_dark.clone() 
+                let default_mat = match sim.grid.terrain(x, y) {
+                    Terrain::Sand => heatmap_mats.sand.clone(),
+                    Terrain::Mud => heatmap_mats.mud.clone(),
+                    Terrain::Water => heatmap_mats.water.clone(),
+                    _ if (x + y) % 2 == 0 => heatmap_mats.default_light.clone(),
+                    _ => heatmap_mats.default_dark.clone(),
                 };
 
+                let potential = field.as_ref().and_then(|field| field[y][x]);
                 // If disabled, we WANT default mat.
-                let desired_mat = if !ui_state.show_heatmap {
+                let desired_mat = if let Some(u) = potential {
+                    let t = if high > low { (u - low) / (high - low) } else { 0.0 };
+                    let last = heatmap_mats.field_levels.len() - 1;
+                    heatmap_mats.field_levels[(t * last as f32).round() as usize].clone()
+                } else if !ui_state.show_heatmap {
                      default_mat
                 } else {
                     // If enabled, check visitors
@@ -176,6 +183,12 @@ pub fn render_heatmap(
                             heatmap_mats.astar_visited.clone()
                         } else if visitors.contains(&AgentKind::BehaviorTree) {
                             heatmap_mats.bt_visited.clone()
+                        } else if visitors.contains(&AgentKind::QLearning) {
+                            heatmap_mats.q_visited.clone()
+                        } else if visitors.contains(&AgentKind::Mcts) {
+                            heatmap_mats.mcts_visited.clone()
+                        } else if visitors.contains(&AgentKind::PotentialField) {
+                            heatmap_mats.pf_visited.clone()
                         } else {
                             default_mat
                         }
@@ -198,11 +211,7 @@ pub fn handle_visual_events(
     query: Query<(Entity, &AgentMarker)>,
 ) {
     for (entity, marker) in &query {
-        let triggered = match marker.kind {
-            AgentKind::Fsm => sim.fsm.did_noise_trigger(),
-            AgentKind::AStar => sim.astar.did_noise_trigger(),
-            AgentKind::BehaviorTree => sim.bt.did_noise_trigger(),
-        };
+        let triggered = sim.agent(marker.kind).is_some_and(|viewer| viewer.agent.did_noise_trigger());
 
         if triggered {
             commands.entity(entity).insert(Shaking {
@@ -241,26 +250,14 @@ pub fn sync_agents(
     mut query: Query<(&AgentMarker, &mut Transform, &mut Visibility)>,
 ) {
     for (marker, mut transform, mut visibility) in &mut query {
-        let done = match marker.kind {
-            AgentKind::Fsm => sim.fsm_done,
-            AgentKind::AStar => sim.astar_done,
-            AgentKind::BehaviorTree => sim.bt_done,
-        };
-
-        if done {
+        let Some(viewer) = sim.agent(marker.kind).filter(|viewer| !viewer.done) else {
             *visibility = Visibility::Hidden;
             continue;
-        }
+        };
 
         *visibility = Visibility::Visible;
 
-        let pos = match marker.kind {
-            AgentKind::Fsm => sim.fsm.position(),
-            AgentKind::AStar => sim.astar.position(),
-            AgentKind::BehaviorTree => sim.bt.position(),
-        };
-
-        let target = grid_to_world(pos, agent_y_offset(marker.kind));
+        let target = grid_to_world(viewer.body.position, agent_y_offset(marker.kind));
         transform.translation = transform.translation.lerp(target, 0.15);
     }
 }
@@ -278,11 +275,7 @@ pub fn draw_gizmos(
     for (marker, transform, vis) in &query {
         if vis == Visibility::Hidden { continue; }
 
-        let radius = match marker.kind {
-            AgentKind::Fsm => sim.fsm.planning_radius(),
-            AgentKind::AStar => sim.astar.planning_radius(),
-            AgentKind::BehaviorTree => sim.bt.planning_radius(),
-        };
+        let radius = sim.agent(marker.kind).and_then(|viewer| viewer.agent.planning_radius());
 
         if let Some(r) = radius {
             let rotation = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
@@ -308,16 +301,17 @@ pub fn rotate_goal(
 
 pub fn render_obstacles(
     mut commands: Commands,
-    sim: Res<SimState>,
+    mut sim: ResMut<SimState>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     obstacle_query: Query<Entity, With<Obstacle>>,
 ) {
-    if sim.total_ticks == 0 {
+    if sim.total_ticks == 0 || sim.obstacles_changed {
         let grid_obstacles = sim.grid.obstacle_positions();
         let current_count = obstacle_query.iter().count();
 
-        if current_count != grid_obstacles.len() {
+        if sim.obstacles_changed || current_count != grid_obstacles.len() {
+            sim.obstacles_changed = false;
             for entity in &obstacle_query {
                 commands.entity(entity).despawn();
             }
@@ -1,13 +1,16 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
+use crate::agents::behavior_tree::{BehaviorTreeAgent, Status};
+use crate::engine::dynamics::ObstacleRule;
 use crate::engine::world::{Grid, Position};
-use super::resources::{SimState, UiState};
+use super::resources::{MapSource, SimState, UiState};
 use super::components::{TrailDot, Obstacle};
 
 pub fn ui_system(
     mut contexts: EguiContexts,
     mut ui_state: ResMut<UiState>,
     mut sim: ResMut<SimState>,
+    map_source: Res<MapSource>,
     mut commands: Commands,
     _obstacle_query: Query<Entity, With<Obstacle>>,
     trail_query: Query<Entity, With<TrailDot>>,
@@ -23,6 +26,7 @@ pub fn ui_system(
             ui.horizontal(|ui| {
                 ui.label(format!("Tick: {}", sim.total_ticks));
             });
+            ui.label(format!("Seed: {}", sim.seed));
             ui.separator();
 
             // Speed Control
@@ -51,14 +55,27 @@ pub fn ui_system(
                     commands.entity(entity).despawn_recursive();
                 }
                 
-                // Reset sim state
-                let w = sim.grid.width;
-                let h = sim.grid.height;
-                let grid = Grid::new(w, h, Position { x: w-1, y: h-1 });
-                // We don't have access to OBSTACLE_DENSITY constant here easily unless we move it or duplicate
-                // For now, hardcode or access from existing config if available.
-                // Or let's just use 0.15 matching viewer.rs constant.
-                sim.reset(grid, 0.15);
+                let dynamics: &[ObstacleRule] = if ui_state.dynamic_obstacles {
+                    &[
+                        ObstacleRule::Wanderers { count: 3, move_chance: 0.5 },
+                        ObstacleRule::SpawnWalls { chance: 0.05, lifetime: 20 },
+                    ]
+                } else {
+                    &[]
+                };
+
+                // Reset sim state: a loaded map is reused as-is.
+                if let Some(map) = &map_source.grid {
+                    sim.reset(map.clone(), 0.0, dynamics);
+                } else {
+                    let w = sim.grid.width;
+                    let h = sim.grid.height;
+                    let grid = Grid::new(w, h, Position { x: w-1, y: h-1 });
+                    // We don't have access to OBSTACLE_DENSITY constant here easily unless we move it or duplicate
+                    // For now, hardcode or access from existing config if available.
+                    // Or let's just use 0.15 matching viewer.rs constant.
+                    sim.reset(grid, 0.15, dynamics);
+                }
             }
 
             ui.separator();
@@ -67,6 +84,9 @@ pub fn ui_system(
             ui.heading("Visuals");
             ui.checkbox(&mut ui_state.show_heatmap, "Show Heatmap");
             ui.checkbox(&mut ui_state.show_path_gizmos, "Show Planning Radius");
+            ui.checkbox(&mut ui_state.show_field, "Show Potential Field");
+            ui.checkbox(&mut ui_state.show_bt_trace, "Show Behavior Tree");
+            ui.checkbox(&mut ui_state.dynamic_obstacles, "Dynamic Obstacles (on restart)");
 
             ui.separator();
 
@@ -76,8 +96,50 @@ pub fn ui_system(
                 if done { "Done ✓".to_string() } else { format!("({}, {})", pos.x, pos.y) }
             };
             
-            ui.label(format!("FSM: {}", status(sim.fsm_done, sim.fsm.position())));
-            ui.label(format!("A*: {}", status(sim.astar_done, sim.astar.position())));
-            ui.label(format!("BT: {}", status(sim.bt_done, sim.bt.position())));
+            for viewer in &sim.agents {
+                ui.label(format!("{}: {}", viewer.kind.label(), status(viewer.done, viewer.body.position)));
+            }
         });
+
+    if ui_state.show_bt_trace && let Some(bt) = sim.bt() {
+        egui::SidePanel::right("bt_panel")
+            .default_width(260.0)
+            .show(ctx, |ui| bt_panel(ui, bt));
+    }
+}
+
+/// The BT agent's tree, one row per node: nodes visited in the last tick
+/// are coloured by the status they returned, and each row counts the
+/// node's results so far (✓ success, ✗ failure, … running).
+fn bt_panel(ui: &mut egui::Ui, agent: &BehaviorTreeAgent) {
+    ui.heading("Behavior Tree");
+    ui.label(format!("Tick {}", agent.ticks()));
+    if agent.trace().is_empty() && agent.ticks() > 0 {
+        ui.label("Last tick: noise move, tree not run");
+    }
+    ui.separator();
+
+    let hits = agent.hit_counts();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (id, node) in agent.tree_outline().iter().enumerate() {
+            let status = agent.trace().iter().find(|&&(visited, _)| visited == id).map(|&(_, status)| status);
+            let color = match status {
+                Some(Status::Success) => egui::Color32::from_rgb(80, 200, 120),
+                Some(Status::Failure) => egui::Color32::from_rgb(230, 90, 80),
+                Some(Status::Running) => egui::Color32::from_rgb(240, 200, 60),
+                None => egui::Color32::GRAY,
+            };
+            let counts = hits.get(id).copied().unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.add_space(node.depth as f32 * 14.0);
+                ui.label(egui::RichText::new(&node.label).color(color).strong())
+                    .on_hover_text(status.map_or("not visited last tick".to_string(), |s| format!("{:?}", s)));
+                ui.label(
+                    egui::RichText::new(format!("✓{} ✗{} …{}", counts.success, counts.failure, counts.running))
+                        .small()
+                        .weak(),
+                );
+            });
+        }
+    });
 }